@@ -0,0 +1 @@
+DROP TABLE habit_period;
\ No newline at end of file
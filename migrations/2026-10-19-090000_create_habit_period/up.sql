@@ -0,0 +1,23 @@
+-- Keep the outcome of every finished habit period
+CREATE TABLE habit_period (
+    hab_per_id UUID PRIMARY KEY,
+
+    -- Both dates are inclusive, end date is the day before the closure date
+    hab_per_start_date DATE NOT NULL,
+    hab_per_end_date DATE NOT NULL,
+
+    hab_per_amount DECIMAL(12,2) NOT NULL,
+    hab_per_goal DECIMAL(10,2) NOT NULL,
+    hab_per_is_met BOOLEAN NOT NULL,
+    hab_per_closed_at TIMESTAMP NOT NULL,
+
+    hab_id UUID NOT NULL,
+
+    --- CONSTRAINTS
+    UNIQUE (hab_id, hab_per_start_date), -- only one record per period
+
+    CONSTRAINT habit_period_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
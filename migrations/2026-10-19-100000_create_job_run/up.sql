@@ -0,0 +1,17 @@
+-- History of background job runs, also used as a lease so only one replica runs each job per tick
+CREATE TABLE job_run (
+    job_run_id UUID PRIMARY KEY,
+    job_run_name VARCHAR(64) NOT NULL,
+
+    -- Fire time of the schedule this run belongs to (UTC), when it was triggered for manual runs
+    job_run_tick TIMESTAMP NOT NULL,
+
+    job_run_replica VARCHAR(255) NOT NULL,
+    job_run_started_at TIMESTAMP NOT NULL,
+    job_run_finished_at TIMESTAMP NULL,
+    job_run_habits_processed INTEGER NOT NULL DEFAULT 0,
+    job_run_error TEXT NULL,
+
+    --- CONSTRAINTS
+    UNIQUE (job_run_name, job_run_tick) -- only one replica may claim a tick
+);
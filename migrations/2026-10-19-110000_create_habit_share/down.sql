@@ -0,0 +1 @@
+DROP TABLE habit_share;
\ No newline at end of file
@@ -0,0 +1,13 @@
+-- Public tokens giving read access to a habit's badge and charts
+CREATE TABLE habit_share (
+    hab_sha_token VARCHAR(64) PRIMARY KEY,
+    hab_sha_created_at TIMESTAMP NOT NULL,
+
+    hab_id UUID NOT NULL,
+
+    --- CONSTRAINTS
+    CONSTRAINT habit_share_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+DROP TABLE user_digest_preference;
\ No newline at end of file
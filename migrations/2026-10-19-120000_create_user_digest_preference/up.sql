@@ -0,0 +1,14 @@
+-- Weekly email digest settings of each user, users without a row are opted out
+CREATE TABLE user_digest_preference (
+    usr_id VARCHAR(24) PRIMARY KEY,
+
+    usr_dig_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- ISO day of week the digest is sent on (1 is Monday)
+    usr_dig_weekday SMALLINT NOT NULL DEFAULT 1 CHECK (usr_dig_weekday BETWEEN 1 AND 7),
+
+    -- Avoids sending the same digest twice when the job runs again on the same day
+    usr_dig_last_sent_on DATE NULL,
+
+    usr_dig_updated_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE habit_data_collected DROP COLUMN hab_dat_mood;
\ No newline at end of file
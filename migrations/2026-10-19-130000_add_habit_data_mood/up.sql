@@ -0,0 +1,3 @@
+-- Optional mood of the user when logging an entry, from 1 (worst) to 5 (best)
+ALTER TABLE habit_data_collected
+    ADD COLUMN hab_dat_mood SMALLINT NULL CHECK (hab_dat_mood BETWEEN 1 AND 5);
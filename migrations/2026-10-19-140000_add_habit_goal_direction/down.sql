@@ -0,0 +1,3 @@
+ALTER TABLE habit DROP COLUMN hab_goal_tolerance, DROP COLUMN hab_goal_direction;
+
+DROP TYPE hab_goal_direction_enum;
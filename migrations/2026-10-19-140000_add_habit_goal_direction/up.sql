@@ -0,0 +1,9 @@
+-- Goals are minimums by default, limit habits fail when going over their goal
+CREATE TYPE hab_goal_direction_enum AS ENUM(
+    'at_least', 'at_most', 'exact'
+);
+
+ALTER TABLE habit
+    ADD COLUMN hab_goal_direction hab_goal_direction_enum NOT NULL DEFAULT 'at_least',
+    -- Accepted distance from the goal for exact targets
+    ADD COLUMN hab_goal_tolerance DECIMAL(10,2) NOT NULL DEFAULT 0 CHECK (hab_goal_tolerance >= 0);
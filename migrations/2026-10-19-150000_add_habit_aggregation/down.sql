@@ -0,0 +1,3 @@
+ALTER TABLE habit DROP COLUMN hab_aggregation;
+
+DROP TYPE hab_aggregation_enum;
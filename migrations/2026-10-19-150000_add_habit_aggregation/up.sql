@@ -0,0 +1,7 @@
+-- How the records within a period make up its amount (body weight is not summed up)
+CREATE TYPE hab_aggregation_enum AS ENUM(
+    'sum', 'last', 'average', 'min', 'max'
+);
+
+ALTER TABLE habit
+    ADD COLUMN hab_aggregation hab_aggregation_enum NOT NULL DEFAULT 'sum';
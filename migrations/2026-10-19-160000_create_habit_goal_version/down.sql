@@ -0,0 +1 @@
+DROP TABLE habit_goal_version;
@@ -0,0 +1,28 @@
+-- Goal definitions of habits over time, each one in effect from its valid from date until the
+-- next one. The habit itself keeps the latest definition
+CREATE TABLE habit_goal_version (
+    hab_ver_id UUID PRIMARY KEY,
+    hab_ver_valid_from DATE NOT NULL,
+
+    hab_ver_goal DECIMAL(10,2) NOT NULL,
+    hab_ver_goal_direction hab_goal_direction_enum NOT NULL,
+    hab_ver_goal_tolerance DECIMAL(10,2) NOT NULL,
+    hab_ver_freq_type hab_freq_type_enum NOT NULL,
+    hab_ver_units VARCHAR(10) NOT NULL,
+
+    hab_id UUID NOT NULL,
+
+    --- CONSTRAINTS
+    UNIQUE (hab_id, hab_ver_valid_from), -- changes made on the same day replace each other
+
+    CONSTRAINT habit_goal_version_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
+
+-- Existing habits have kept their current definition since they were created
+INSERT INTO habit_goal_version
+SELECT gen_random_uuid(), hab_created_at::date, hab_goal, hab_goal_direction,
+    hab_goal_tolerance, hab_freq_type, hab_units, hab_id
+FROM habit;
@@ -0,0 +1,4 @@
+ALTER TABLE habit
+    DROP CONSTRAINT habit_schedule_check,
+    DROP COLUMN hab_start_date,
+    DROP COLUMN hab_end_date;
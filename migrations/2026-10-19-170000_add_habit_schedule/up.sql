@@ -0,0 +1,5 @@
+-- Habits can be scheduled to start later, and time-boxed challenges end at a given date
+ALTER TABLE habit
+    ADD COLUMN hab_start_date DATE,
+    ADD COLUMN hab_end_date DATE,
+    ADD CONSTRAINT habit_schedule_check CHECK (hab_end_date >= hab_start_date);
@@ -0,0 +1,7 @@
+DROP TABLE challenge_invitation;
+
+DROP TABLE challenge_participant;
+
+DROP TABLE challenge;
+
+DROP TYPE chl_par_privacy_enum;
@@ -0,0 +1,70 @@
+-- What participants of a challenge let others see about them
+CREATE TYPE chl_par_privacy_enum AS ENUM(
+    'public', 'anonymous', 'hidden'
+);
+
+-- Shared time-boxed goals, every participant follows it through a habit of their own
+CREATE TABLE challenge (
+    chl_id UUID PRIMARY KEY,
+    chl_name VARCHAR(255) NOT NULL,
+    chl_description VARCHAR(255) NOT NULL,
+    chl_created_at TIMESTAMP NOT NULL,
+
+    chl_is_yn BOOLEAN NOT NULL,
+    chl_units VARCHAR(10) NOT NULL,
+    chl_goal DECIMAL(10,2) NOT NULL,
+    chl_goal_direction hab_goal_direction_enum NOT NULL,
+    chl_freq_type hab_freq_type_enum NOT NULL,
+    chl_aggregation hab_aggregation_enum NOT NULL,
+
+    chl_start_date DATE NOT NULL,
+    chl_end_date DATE NOT NULL,
+
+    -- Anyone can join public challenges, others need an invitation
+    chl_is_public BOOLEAN NOT NULL,
+
+    usr_id VARCHAR(24) NOT NULL, -- owner
+
+    --- CONSTRAINTS
+    CONSTRAINT challenge_dates_check CHECK (chl_end_date >= chl_start_date)
+);
+
+CREATE TABLE challenge_participant (
+    chl_par_id UUID PRIMARY KEY,
+    chl_par_joined_at TIMESTAMP NOT NULL,
+    chl_par_privacy chl_par_privacy_enum NOT NULL,
+
+    chl_id UUID NOT NULL,
+    usr_id VARCHAR(24) NOT NULL,
+    hab_id UUID NOT NULL, -- habit created when joining
+
+    --- CONSTRAINTS
+    UNIQUE (chl_id, usr_id),
+
+    CONSTRAINT challenge_participant_chl_id_fk
+        FOREIGN KEY (chl_id)
+            REFERENCES challenge(chl_id)
+            ON DELETE CASCADE,
+
+    -- Deleting the habit leaves the challenge
+    CONSTRAINT challenge_participant_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
+
+CREATE TABLE challenge_invitation (
+    chl_inv_id UUID PRIMARY KEY,
+    chl_inv_created_at TIMESTAMP NOT NULL,
+
+    chl_id UUID NOT NULL,
+    usr_id VARCHAR(24) NOT NULL, -- invited user
+
+    --- CONSTRAINTS
+    UNIQUE (chl_id, usr_id),
+
+    CONSTRAINT challenge_invitation_chl_id_fk
+        FOREIGN KEY (chl_id)
+            REFERENCES challenge(chl_id)
+            ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+DROP TABLE routine_habit;
+
+DROP TABLE routine;
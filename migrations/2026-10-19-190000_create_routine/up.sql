@@ -0,0 +1,31 @@
+-- Ordered groups of habits done together, such as a morning routine
+CREATE TABLE routine (
+    rou_id UUID PRIMARY KEY,
+    rou_name VARCHAR(255) NOT NULL,
+    rou_description VARCHAR(255) NOT NULL,
+    rou_created_at TIMESTAMP NOT NULL,
+
+    usr_id VARCHAR(24) NOT NULL -- owner
+);
+
+CREATE TABLE routine_habit (
+    rou_id UUID NOT NULL,
+    hab_id UUID NOT NULL,
+
+    -- Order of the habit within the routine, starting at 0
+    rou_hab_position INTEGER NOT NULL,
+
+    --- CONSTRAINTS
+    PRIMARY KEY (rou_id, hab_id),
+
+    CONSTRAINT routine_habit_rou_id_fk
+        FOREIGN KEY (rou_id)
+            REFERENCES routine(rou_id)
+            ON DELETE CASCADE,
+
+    -- Deleting a habit takes it out of its routines
+    CONSTRAINT routine_habit_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
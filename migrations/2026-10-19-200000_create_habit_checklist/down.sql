@@ -0,0 +1,7 @@
+DROP TABLE habit_checklist_check;
+
+DROP TABLE habit_checklist_item;
+
+ALTER TABLE habit DROP COLUMN hab_checklist_mode;
+
+DROP TYPE hab_checklist_mode_enum;
@@ -0,0 +1,38 @@
+-- What the daily amount of a habit with a checklist is made of: items checked, or their share
+CREATE TYPE hab_checklist_mode_enum AS ENUM(
+    'count', 'fraction'
+);
+
+ALTER TABLE habit
+    ADD COLUMN hab_checklist_mode hab_checklist_mode_enum NOT NULL DEFAULT 'count';
+
+-- Sub-items of a habit, such as the steps of an evening shutdown
+CREATE TABLE habit_checklist_item (
+    hab_chk_id UUID PRIMARY KEY,
+    hab_chk_name VARCHAR(255) NOT NULL,
+    hab_chk_position INTEGER NOT NULL, -- starting at 0
+    hab_chk_created_at TIMESTAMP NOT NULL,
+
+    hab_id UUID NOT NULL,
+
+    --- CONSTRAINTS
+    CONSTRAINT habit_checklist_item_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
+
+-- Items checked on a day, unchecked ones have no row
+CREATE TABLE habit_checklist_check (
+    hab_chk_id UUID NOT NULL,
+    hab_chk_chk_date DATE NOT NULL,
+    hab_chk_chk_checked_at TIMESTAMP NOT NULL,
+
+    --- CONSTRAINTS
+    PRIMARY KEY (hab_chk_id, hab_chk_chk_date),
+
+    CONSTRAINT habit_checklist_check_hab_chk_id_fk
+        FOREIGN KEY (hab_chk_id)
+            REFERENCES habit_checklist_item(hab_chk_id)
+            ON DELETE CASCADE
+);
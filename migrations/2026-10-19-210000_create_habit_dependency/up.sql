@@ -0,0 +1,21 @@
+-- Habit stacking, a habit is prompted once its trigger habit is done
+CREATE TABLE habit_dependency (
+    hab_id UUID PRIMARY KEY, -- dependent habit, with a single trigger
+    hab_dep_trigger_id UUID NOT NULL,
+    hab_dep_created_at TIMESTAMP NOT NULL,
+
+    --- CONSTRAINTS
+    CONSTRAINT habit_dependency_self_check CHECK (hab_id <> hab_dep_trigger_id),
+
+    CONSTRAINT habit_dependency_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE,
+
+    CONSTRAINT habit_dependency_hab_dep_trigger_id_fk
+        FOREIGN KEY (hab_dep_trigger_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX habit_dependency_trigger_idx ON habit_dependency(hab_dep_trigger_id);
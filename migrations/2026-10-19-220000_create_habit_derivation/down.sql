@@ -0,0 +1,3 @@
+DROP TABLE habit_derivation_source;
+DROP TABLE habit_derivation;
+DROP TYPE hab_derivation_enum;
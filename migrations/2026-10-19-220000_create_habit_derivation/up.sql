@@ -0,0 +1,41 @@
+-- How the daily value of a derived habit comes from its sources: the sum of their amounts, or
+-- done (Y/N) once enough of them were done
+CREATE TYPE hab_derivation_enum AS ENUM(
+    'sum', 'quorum'
+);
+
+-- Habits computed from other habits instead of being logged
+CREATE TABLE habit_derivation (
+    hab_id UUID PRIMARY KEY,
+    hab_der_kind hab_derivation_enum NOT NULL,
+    hab_der_quorum INTEGER, -- sources to be done, quorum derivations only
+    hab_der_created_at TIMESTAMP NOT NULL,
+
+    --- CONSTRAINTS
+    CONSTRAINT habit_derivation_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
+
+CREATE TABLE habit_derivation_source (
+    hab_id UUID NOT NULL,
+    hab_src_id UUID NOT NULL,
+
+    --- CONSTRAINTS
+    PRIMARY KEY (hab_id, hab_src_id),
+
+    CONSTRAINT habit_derivation_source_self_check CHECK (hab_id <> hab_src_id),
+
+    CONSTRAINT habit_derivation_source_hab_id_fk
+        FOREIGN KEY (hab_id)
+            REFERENCES habit_derivation(hab_id)
+            ON DELETE CASCADE,
+
+    CONSTRAINT habit_derivation_source_hab_src_id_fk
+        FOREIGN KEY (hab_src_id)
+            REFERENCES habit(hab_id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX habit_derivation_source_hab_src_id_idx ON habit_derivation_source(hab_src_id);
@@ -0,0 +1,3 @@
+-- When an entry was logged, breaks ties between entries of the same day
+ALTER TABLE habit_data_collected
+    ADD COLUMN hab_dat_created_at TIMESTAMP NOT NULL DEFAULT NOW();
@@ -0,0 +1,3 @@
+ALTER TABLE habit_goal_version
+    DROP COLUMN hab_ver_aggregation,
+    DROP COLUMN hab_ver_is_yn;
@@ -0,0 +1,9 @@
+-- How amounts were counted towards each goal version, taken from the current habit definition
+ALTER TABLE habit_goal_version
+    ADD COLUMN hab_ver_aggregation hab_aggregation_enum NOT NULL DEFAULT 'sum',
+    ADD COLUMN hab_ver_is_yn BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE habit_goal_version v
+SET hab_ver_aggregation = h.hab_aggregation, hab_ver_is_yn = h.hab_is_yn
+FROM habit h
+WHERE h.hab_id = v.hab_id;
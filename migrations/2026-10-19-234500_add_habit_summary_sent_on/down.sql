@@ -0,0 +1 @@
+ALTER TABLE habit DROP COLUMN hab_summary_sent_on;
@@ -0,0 +1,9 @@
+-- Day the completion summary of a finished habit was sent, so runs missed around its end date
+-- still send it once
+ALTER TABLE habit
+    ADD COLUMN hab_summary_sent_on DATE NULL;
+
+-- Habits that ended before yesterday already had their summary sent
+UPDATE habit
+SET hab_summary_sent_on = hab_end_date + 1
+WHERE hab_end_date < CURRENT_DATE - 1;
@@ -144,9 +144,9 @@ impl DBManager {
     pub fn get_write_connection(
         &self,
     ) -> Result<PooledConnection<ConnectionManager<PgConnection>>, Error> {
-        if self.connection_read.is_none() {
+        if self.connection_write.is_none() {
             return Err(Error::DBConnectionError2(ConnectionError::BadConnection(
-                "No read connection".to_string(),
+                "No write connection".to_string(),
             )));
         }
 
@@ -26,6 +26,9 @@ pub enum Error {
 
     #[error("Bad request: {0}")]
     BadRequest(String),
+
+    #[error("Job error: {0}")]
+    JobError(String),
 }
 
 #[derive(Serialize)]
@@ -112,6 +115,11 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
                 format!("Bad request: {}", error),
                 None,
             ),
+            Error::JobError(error) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Job error: {}", error),
+                None,
+            ),
             Error::DBError(error) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Database Error: {}", error),
@@ -0,0 +1,174 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    jobs::{apply_job, plan_job, JOB_NAMES, REPLICA_ID},
+    models::api::{jobs_api_models::*, *},
+};
+
+use warp::{
+    http::StatusCode,
+    reply::{json, with_status},
+    Rejection, Reply,
+};
+
+// GET Route
+pub async fn get_job_runs_handler(
+    job_params: JobRunParams,
+    params: RangeParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Only administrators can check jobs history
+    if !matches!(authentication.role, AuthRole::Admin) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not an administrator".to_string(),
+        )));
+    }
+
+    let result = manager.get_job_runs(job_params.job_name, params.jobs_page, params.jobs_per_page);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = JobRunMultipleQueryResponse {
+        message: "Successfully retrieved job runs".to_string(),
+        runs: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// POST Route
+pub async fn trigger_job_handler(
+    job_name: String,
+    job_params: JobTriggerParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Only administrators can trigger jobs
+    if !matches!(authentication.role, AuthRole::Admin) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not an administrator".to_string(),
+        )));
+    }
+
+    if !JOB_NAMES.contains(&job_name.as_str()) {
+        return Err(warp::reject::custom(Error::BadRequest(format!(
+            "Unknown job {}",
+            job_name
+        ))));
+    }
+
+    let today = chrono::Local::now().naive_local().date();
+    let as_of = job_params.as_of.unwrap_or(today);
+    let dry_run = job_params.dry_run.unwrap_or(false);
+
+    // Running ahead of time would close periods that are still in progress
+    if as_of > today && !dry_run {
+        return Err(warp::reject::custom(Error::BadRequest(
+            "Jobs can only run as of a future date in dry runs".to_string(),
+        )));
+    }
+
+    // Dry runs only report the plan
+    if dry_run {
+        let plan = plan_job(&manager, &job_name, as_of);
+
+        if plan.is_err() {
+            let error = plan.err().unwrap();
+            return Err(warp::reject::custom(error));
+        }
+
+        let response = JobTriggerResponse {
+            message: format!("Successfully planned job {}", job_name),
+            dry_run,
+            run: None,
+            plan: plan.unwrap(),
+        };
+
+        return Ok(with_status(json(&response), StatusCode::OK));
+    }
+
+    // Manual runs take the same lock as scheduled ones, held until the run is over. The plan is
+    // only made once holding it, so it can't predate a run that just finished
+    let job_lock = manager.lock_job(&job_name);
+
+    if job_lock.is_err() {
+        let error = job_lock.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    let job_lock = job_lock.unwrap();
+
+    if job_lock.is_none() {
+        return Err(warp::reject::custom(Error::BadRequest(format!(
+            "Job {} is already running",
+            job_name
+        ))));
+    }
+
+    // Manual runs get their own tick in the history
+    let job_run = manager.claim_job_run(
+        &job_name,
+        chrono::Utc::now().naive_utc(),
+        &format!("{} (manual)", *REPLICA_ID),
+    );
+
+    if job_run.is_err() {
+        let error = job_run.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    let job_run = job_run.unwrap();
+
+    if job_run.is_none() {
+        return Err(warp::reject::custom(Error::BadRequest(format!(
+            "Job {} is already running",
+            job_name
+        ))));
+    }
+
+    let job_run = job_run.unwrap();
+
+    let plan = plan_job(&manager, &job_name, as_of);
+
+    if plan.is_err() {
+        let error = plan.err().unwrap();
+        let finish = manager.finish_job_run(job_run.job_run_id, 0, Some(format!("{:?}", error)));
+
+        if finish.is_err() {
+            return Err(warp::reject::custom(finish.err().unwrap()));
+        }
+
+        return Err(warp::reject::custom(error));
+    }
+
+    let plan = plan.unwrap();
+
+    let result = apply_job(&manager, &plan).await;
+    let error = result.clone().err();
+
+    let finish = manager.finish_job_run(job_run.job_run_id, plan.habits.len() as i32, error);
+
+    if finish.is_err() {
+        let error = finish.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    if result.is_err() {
+        return Err(warp::reject::custom(Error::JobError(result.err().unwrap())));
+    }
+
+    // Return response
+    let response = JobTriggerResponse {
+        message: format!("Successfully ran job {}", job_name),
+        dry_run,
+        run: Some(job_run),
+        plan,
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
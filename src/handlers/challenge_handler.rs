@@ -0,0 +1,281 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::api::{challenge_api_models::*, *},
+};
+
+use warp::{
+    http::StatusCode,
+    reply::{json, with_status},
+    Rejection, Reply,
+};
+
+use uuid::Uuid;
+use validator::Validate;
+
+// Challenges are only visible to users who can join them
+fn check_challenge_access(manager: &DBManager, user_id: String, id: Uuid) -> Result<(), Rejection> {
+    let result = manager.is_challenge_accessible_by_user(user_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not invited to the challenge".to_string(),
+        )));
+    }
+
+    Ok(())
+}
+
+// POST Route
+pub async fn create_challenge_handler(
+    manager: DBManager,
+    authentication: AuthData,
+    data: ChallengeCreateSchema,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    let result = manager.add_challenge(authentication.requester_id, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = ChallengeCreateResponse {
+        message: "Challenge created successfully".to_string(),
+        id: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::CREATED))
+}
+
+// GET Route
+pub async fn get_challenge_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    check_challenge_access(&manager, authentication.requester_id.clone(), id)?;
+
+    let challenge = manager.get_challenge(id);
+
+    if challenge.is_err() {
+        return Err(warp::reject::custom(challenge.err().unwrap()));
+    }
+
+    let participants = manager.get_challenge_participants(id);
+
+    if participants.is_err() {
+        return Err(warp::reject::custom(participants.err().unwrap()));
+    }
+
+    let participants = participants.unwrap();
+
+    // Return response
+    let response = ChallengeSingleQueryResponse {
+        message: "Successfully retrieved challenge".to_string(),
+        challenge: challenge.unwrap(),
+        participants_count: participants.len() as i64,
+        participant: participants
+            .into_iter()
+            .find(|participant| participant.usr_id == authentication.requester_id),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// POST Route
+pub async fn create_challenge_invitation_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: ChallengeInvitationCreateSchema,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    // Only the owner can invite, checked along with the invitation
+    let result = manager.add_challenge_invitation(id, authentication.requester_id, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = ChallengeInvitationCreateResponse {
+        message: "Invitation created successfully".to_string(),
+        invitation: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::CREATED))
+}
+
+// POST Route
+pub async fn join_challenge_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: ChallengeJoinSchema,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    check_challenge_access(&manager, authentication.requester_id.clone(), id)?;
+
+    let result = manager.join_challenge(id, authentication.requester_id, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = ChallengeJoinResponse {
+        message: "Challenge joined successfully".to_string(),
+        participant: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::CREATED))
+}
+
+// POST Route
+pub async fn leave_challenge_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let result = manager.leave_challenge(id, authentication.requester_id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = GeneralResponse {
+        message: "Challenge left successfully".to_string(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// UPDATE (PATCH) Route
+pub async fn update_challenge_privacy_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: ChallengePrivacySchema,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let result = manager.update_challenge_privacy(id, authentication.requester_id, data.privacy);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = ChallengeJoinResponse {
+        message: "Privacy updated successfully".to_string(),
+        participant: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_challenge_leaderboard_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    check_challenge_access(&manager, authentication.requester_id.clone(), id)?;
+
+    let result = manager.get_challenge_leaderboard(id, authentication.requester_id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = ChallengeLeaderboardQueryResponse {
+        message: "Successfully retrieved leaderboard".to_string(),
+        leaderboard: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
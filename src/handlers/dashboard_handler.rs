@@ -0,0 +1,43 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::api::{dashboard_api_models::*, *},
+};
+
+use warp::{
+    http::StatusCode,
+    reply::{json, with_status},
+    Rejection, Reply,
+};
+
+// GET Route
+pub async fn get_dashboard_handler(
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let current_date = chrono::Local::now().naive_local().date();
+
+    // Get current periods progress from database
+    let result = manager.get_user_dashboard(authentication.requester_id, current_date);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = DashboardQueryResponse {
+        message: "Successfully retrieved dashboard".to_string(),
+        date: current_date,
+        habits: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
@@ -0,0 +1,78 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::api::{digest_api_models::*, *},
+};
+
+use validator::Validate;
+use warp::{
+    http::StatusCode,
+    reply::{json, with_status},
+    Rejection, Reply,
+};
+
+// GET Route
+pub async fn get_digest_preference_handler(
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check a user is logged in / provided the action
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let result = manager.get_digest_preference(authentication.requester_id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = DigestPreferenceQueryResponse {
+        message: "Successfully retrieved digest preference".to_string(),
+        preference: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// UPDATE (PUT) Route
+pub async fn update_digest_preference_handler(
+    manager: DBManager,
+    authentication: AuthData,
+    data: DigestPreferenceUpdateSchema,
+) -> Result<impl Reply, Rejection> {
+    // Check a user is logged in / provided the action
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    let result = manager.update_digest_preference(authentication.requester_id, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = DigestPreferenceQueryResponse {
+        message: "Digest preference updated successfully".to_string(),
+        preference: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
@@ -45,6 +45,7 @@ pub async fn get_next_events_by_habit_handler(
 pub async fn get_data_by_habit_handler(
     date_params: DateParams,
     id: Uuid,
+    group_params: CalendarGroupParams,
     manager: DBManager,
     authentication: AuthData,
 ) -> Result<impl Reply, Rejection> {
@@ -76,6 +77,7 @@ pub async fn get_data_by_habit_handler(
         Some(id),
         date_params.start_date,
         date_params.end_date,
+        group_params.group_by,
     );
 
     if result.is_err() {
@@ -97,6 +99,7 @@ pub async fn get_data_by_habit_handler(
 // GET Route
 pub async fn get_data_by_user_handler(
     date_params: DateParams,
+    group_params: CalendarGroupParams,
     manager: DBManager,
     authentication: AuthData,
 ) -> Result<impl Reply, Rejection> {
@@ -115,6 +118,7 @@ pub async fn get_data_by_user_handler(
         None,
         date_params.start_date,
         date_params.end_date,
+        group_params.group_by,
     );
 
     if result.is_err() {
@@ -132,3 +136,178 @@ pub async fn get_data_by_user_handler(
 
     Ok(with_status(json(&response), StatusCode::OK))
 }
+
+// GET Route
+pub async fn get_calendar_periods_by_habit_handler(
+    date_params: DateParams,
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    let result = manager.get_habits_calendar_periods(
+        None,
+        Some(id),
+        date_params.start_date,
+        date_params.end_date,
+    );
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = CalendarPeriodsMultipleQueryResponse {
+        message: "Successfully retrieved habit calendar periods".to_string(),
+        periods: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_calendar_periods_by_user_handler(
+    date_params: DateParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check a user is logged in / provided the action
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let result = manager.get_habits_calendar_periods(
+        Some(authentication.requester_id),
+        None,
+        date_params.start_date,
+        date_params.end_date,
+    );
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = CalendarPeriodsMultipleQueryResponse {
+        message: "Successfully retrieved user's calendar periods".to_string(),
+        periods: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_heatmap_by_habit_handler(
+    id: Uuid,
+    heatmap_params: HeatmapParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    let year = heatmap_params
+        .year
+        .unwrap_or(chrono::Datelike::year(&chrono::Local::now()));
+
+    let result = manager.get_habits_heatmap(None, Some(id), None, year);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    let (start_date, end_date, days) = result.unwrap();
+
+    // Return response
+    let response = HeatmapQueryResponse {
+        message: "Successfully retrieved habit heatmap".to_string(),
+        year,
+        start_date,
+        end_date,
+        days,
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_heatmap_by_user_handler(
+    category_id: Option<Uuid>,
+    heatmap_params: HeatmapParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check a user is logged in / provided the action
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let year = heatmap_params
+        .year
+        .unwrap_or(chrono::Datelike::year(&chrono::Local::now()));
+
+    // Only the user's habits are considered, also within a category
+    let result =
+        manager.get_habits_heatmap(Some(authentication.requester_id), None, category_id, year);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    let (start_date, end_date, days) = result.unwrap();
+
+    // Return response
+    let response = HeatmapQueryResponse {
+        message: "Successfully retrieved user's heatmap".to_string(),
+        year,
+        start_date,
+        end_date,
+        days,
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
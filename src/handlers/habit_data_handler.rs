@@ -1,7 +1,11 @@
 use crate::{
     db::DBManager,
     error::Error,
-    models::api::{data_api_models::*, *},
+    models::{
+        api::{data_api_models::*, *},
+        database::HabitDataCollected,
+    },
+    services::reminders_service::{build_dependency_notifications, enqueue_reminders_service},
 };
 
 use warp::{
@@ -13,6 +17,69 @@ use warp::{
 use uuid::Uuid;
 use validator::Validate;
 
+// Derived habits are computed from their sources, their data can't be written directly
+fn check_habit_not_derived(manager: &DBManager, hab_id: Uuid) -> Result<(), Rejection> {
+    let result = manager.is_habit_derived(hab_id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if result.unwrap() {
+        return Err(warp::reject::custom(Error::BadRequest(
+            "Derived habits can't be logged directly".to_string(),
+        )));
+    }
+
+    Ok(())
+}
+
+// Habits with a checklist are logged by checking their items
+fn check_habit_without_checklist(manager: &DBManager, hab_id: Uuid) -> Result<(), Rejection> {
+    let result = manager.has_habit_checklist(hab_id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if result.unwrap() {
+        return Err(warp::reject::custom(Error::BadRequest(
+            "Habits with a checklist are logged by checking their items".to_string(),
+        )));
+    }
+
+    Ok(())
+}
+
+// Prompt the habits stacked on a just logged one that are still to be done
+pub fn prompt_habit_dependents(manager: &DBManager, data: &HabitDataCollected) {
+    let result = manager.get_habit_by_id(data.hab_id).and_then(|habit| {
+        manager
+            .get_pending_habit_dependents(habit.hab_id, data.hab_dat_collected_at)
+            .map(|dependents| {
+                build_dependency_notifications(&habit, &dependents, data.hab_dat_collected_at)
+            })
+    });
+
+    match result {
+        // The gateway shouldn't hold the response back
+        Ok(notifications) if !notifications.is_empty() => {
+            tokio::spawn(async move {
+                let result = enqueue_reminders_service(notifications).await;
+
+                if result.is_err() {
+                    println!(
+                        "Error enqueuing dependency prompts: {}",
+                        result.err().unwrap()
+                    );
+                }
+            });
+        }
+        Ok(_) => {}
+        Err(error) => println!("Error prompting habit dependents: {:?}", error),
+    }
+}
+
 // POST Route
 pub async fn create_habit_data_handler(
     manager: DBManager,
@@ -41,6 +108,9 @@ pub async fn create_habit_data_handler(
         )));
     }
 
+    check_habit_not_derived(&manager, data.habit_id)?;
+    check_habit_without_checklist(&manager, data.habit_id)?;
+
     // Validate input
     let validation_result = data.validate();
 
@@ -78,6 +148,8 @@ pub async fn create_habit_data_handler(
 
     let data = result.unwrap();
 
+    prompt_habit_dependents(&manager, &data);
+
     // Return response
     let response = HabitDataCreateResponse {
         message: "Habit data created successfully".to_string(),
@@ -114,6 +186,17 @@ pub async fn update_habit_data_handler(
         )));
     }
 
+    let habit_data = manager.get_habit_data_by_id(id);
+
+    if habit_data.is_err() {
+        return Err(warp::reject::custom(habit_data.err().unwrap()));
+    }
+
+    let hab_id = habit_data.unwrap().hab_id;
+
+    check_habit_not_derived(&manager, hab_id)?;
+    check_habit_without_checklist(&manager, hab_id)?;
+
     // Validate input
     let validation_result = data.validate();
 
@@ -164,6 +247,17 @@ pub async fn delete_habit_data_handler(
         )));
     }
 
+    let habit_data = manager.get_habit_data_by_id(id);
+
+    if habit_data.is_err() {
+        return Err(warp::reject::custom(habit_data.err().unwrap()));
+    }
+
+    let hab_id = habit_data.unwrap().hab_id;
+
+    check_habit_not_derived(&manager, hab_id)?;
+    check_habit_without_checklist(&manager, hab_id)?;
+
     let result = manager.delete_habit_data(id);
 
     if result.is_err() {
@@ -103,8 +103,9 @@ pub async fn update_habits_handler(
     }
 
     // Return response
-    let response = GeneralResponse {
+    let response = HabitUpdateResponse {
         message: "Habit updated successfully".to_string(),
+        in_flight_period: result.unwrap(),
     };
 
     Ok(with_status(json(&response), StatusCode::OK))
@@ -322,3 +323,262 @@ pub async fn get_habit_by_id_handler(
 
     return Ok(with_status(json(&response), StatusCode::OK));
 }
+
+// Checklist, dependency and derivation routes are only accessible by the owner of the habit
+fn get_accessible_habit(
+    manager: &DBManager,
+    authentication: AuthData,
+    id: Uuid,
+) -> Result<Habit, Rejection> {
+    // Check a user is logged in / provided the action
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User has not access to this habit".to_string(),
+        )));
+    }
+
+    let result = manager.get_habit_by_id(id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    Ok(result.unwrap())
+}
+
+// GET Route
+pub async fn get_habit_checklist_handler(
+    id: Uuid,
+    params: HabitChecklistParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    let habit = get_accessible_habit(&manager, authentication, id)?;
+
+    let date = params
+        .date
+        .unwrap_or_else(|| chrono::Utc::now().naive_utc().date());
+
+    let result = manager.get_habit_checklist(&habit, date);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitChecklistQueryResponse {
+        message: "Successfully retrieved checklist".to_string(),
+        checklist: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// UPDATE (PUT) Route
+pub async fn update_habit_checklist_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: HabitChecklistUpdateSchema,
+) -> Result<impl Reply, Rejection> {
+    let habit = get_accessible_habit(&manager, authentication, id)?;
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    let result = manager.update_habit_checklist(id, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Today's state of the new checklist
+    let result = manager.get_habit_checklist(&habit, chrono::Utc::now().naive_utc().date());
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitChecklistQueryResponse {
+        message: "Checklist updated successfully".to_string(),
+        checklist: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// UPDATE (PUT) Route
+pub async fn set_habit_checklist_checks_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: HabitChecklistChecksSchema,
+) -> Result<impl Reply, Rejection> {
+    let habit = get_accessible_habit(&manager, authentication, id)?;
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    let result = manager.set_habit_checklist_checks(&habit, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    let (checklist, data) = result.unwrap();
+
+    // Return response
+    let response = HabitChecklistChecksResponse {
+        message: "Checklist checked successfully".to_string(),
+        checklist,
+        data,
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// UPDATE (PUT) Route
+pub async fn set_habit_trigger_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: HabitTriggerSchema,
+) -> Result<impl Reply, Rejection> {
+    let user_id = authentication.requester_id.clone();
+
+    get_accessible_habit(&manager, authentication, id)?;
+
+    let result = manager.set_habit_trigger(user_id, id, data.trigger_id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitTriggerResponse {
+        message: "Trigger updated successfully".to_string(),
+        dependency: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_habit_chain_handler(
+    id: Uuid,
+    date_params: DateParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    let user_id = authentication.requester_id.clone();
+
+    get_accessible_habit(&manager, authentication, id)?;
+
+    let result = manager.get_habit_chain(user_id, id, date_params.start_date, date_params.end_date);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitChainQueryResponse {
+        message: "Successfully retrieved habit chain".to_string(),
+        chain: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_habit_derivation_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    get_accessible_habit(&manager, authentication, id)?;
+
+    let result = manager.get_habit_derivation(id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitDerivationQueryResponse {
+        message: "Successfully retrieved habit derivation".to_string(),
+        derivation: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// UPDATE (PUT) Route
+pub async fn set_habit_derivation_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: HabitDerivationUpdateSchema,
+) -> Result<impl Reply, Rejection> {
+    let user_id = authentication.requester_id.clone();
+
+    let habit = get_accessible_habit(&manager, authentication, id)?;
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    let result = manager.set_habit_derivation(user_id, &habit, data.derivation);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitDerivationQueryResponse {
+        message: "Derivation updated successfully".to_string(),
+        derivation: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
@@ -1,5 +1,14 @@
+pub mod admin_handler;
 pub mod category_handler;
+pub mod challenge_handler;
+pub mod dashboard_handler;
+pub mod digest_handler;
 pub mod events_handler;
 pub mod habit_data_handler;
 pub mod habit_handler;
 pub mod ownership_handler;
+pub mod period_handler;
+pub mod report_handler;
+pub mod routine_handler;
+pub mod share_handler;
+pub mod stats_handler;
@@ -0,0 +1,64 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::api::{period_api_models::*, *},
+};
+
+use warp::{
+    http::StatusCode,
+    reply::{json, with_status},
+    Rejection, Reply,
+};
+
+use uuid::Uuid;
+
+// GET Route
+pub async fn get_periods_by_habit_handler(
+    id: Uuid,
+    date_params: DateParams,
+    params: RangeParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    // Get closed periods from database
+    let result = manager.get_habit_periods(
+        id,
+        date_params.start_date,
+        date_params.end_date,
+        params.periods_page,
+        params.periods_per_page,
+    );
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitPeriodMultipleQueryResponse {
+        message: "Successfully retrieved habit periods".to_string(),
+        periods: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
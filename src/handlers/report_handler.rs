@@ -0,0 +1,66 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::api::{report_api_models::*, *},
+    utils::reports::{render_review_html, render_review_markdown},
+};
+
+use warp::{
+    http::StatusCode,
+    reply::{json, with_header, with_status},
+    Rejection, Reply,
+};
+
+// GET Route
+pub async fn get_review_handler(
+    review_params: ReviewParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<warp::reply::Response, Rejection> {
+    // Check a user is logged in / provided the action
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let current_date = chrono::Local::now().naive_local().date();
+
+    let result = manager.get_user_review(
+        authentication.requester_id,
+        review_params.period.unwrap_or(ReviewPeriod::Week),
+        review_params.date.unwrap_or(current_date),
+        current_date,
+    );
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    let review = result.unwrap();
+
+    // Rendered digests can be used as is in a notification body
+    match review_params.format.unwrap_or(ReportFormat::Json) {
+        ReportFormat::Markdown => Ok(with_header(
+            render_review_markdown(&review),
+            "content-type",
+            "text/markdown; charset=utf-8",
+        )
+        .into_response()),
+        ReportFormat::Html => Ok(with_header(
+            render_review_html(&review),
+            "content-type",
+            "text/html; charset=utf-8",
+        )
+        .into_response()),
+        ReportFormat::Json => {
+            let response = ReviewQueryResponse {
+                message: "Successfully retrieved review".to_string(),
+                review,
+            };
+
+            Ok(with_status(json(&response), StatusCode::OK).into_response())
+        }
+    }
+}
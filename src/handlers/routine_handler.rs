@@ -0,0 +1,278 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    handlers::habit_data_handler::prompt_habit_dependents,
+    models::api::{routine_api_models::*, *},
+};
+
+use warp::{
+    http::StatusCode,
+    reply::{json, with_status},
+    Rejection, Reply,
+};
+
+use uuid::Uuid;
+use validator::Validate;
+
+// Routines are only accessible by their owner
+fn check_routine_access(manager: &DBManager, user_id: String, id: Uuid) -> Result<(), Rejection> {
+    let result = manager.is_routine_accessible_by_user(user_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the routine".to_string(),
+        )));
+    }
+
+    Ok(())
+}
+
+// POST Route
+pub async fn create_routine_handler(
+    manager: DBManager,
+    authentication: AuthData,
+    data: RoutineCreateSchema,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    let result = manager.add_routine(authentication.requester_id, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = RoutineCreateResponse {
+        message: "Routine created successfully".to_string(),
+        id: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::CREATED))
+}
+
+// GET Route
+pub async fn get_routines_handler(
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let result = manager.get_routines(authentication.requester_id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = RoutineMultipleQueryResponse {
+        message: "Successfully retrieved routines".to_string(),
+        routines: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_routine_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    check_routine_access(&manager, authentication.requester_id, id)?;
+
+    let result = manager.get_routine(id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = RoutineSingleQueryResponse {
+        message: "Successfully retrieved routine".to_string(),
+        routine: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// UPDATE (PATCH) Route
+pub async fn update_routine_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: RoutineUpdateSchema,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    check_routine_access(&manager, authentication.requester_id.clone(), id)?;
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    let result = manager.update_routine(id, authentication.requester_id, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = GeneralResponse {
+        message: "Routine updated successfully".to_string(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// DELETE Route
+pub async fn delete_routine_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    check_routine_access(&manager, authentication.requester_id, id)?;
+
+    let result = manager.delete_routine(id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = GeneralResponse {
+        message: "Routine deleted successfully".to_string(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// POST Route
+pub async fn complete_routine_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+    data: RoutineCompleteSchema,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    check_routine_access(&manager, authentication.requester_id, id)?;
+
+    // Validate input
+    let validation_result = data.validate();
+
+    if validation_result.is_err() {
+        return Err(warp::reject::custom(Error::ValidationError(
+            validation_result.err().unwrap(),
+        )));
+    }
+
+    let result = manager.complete_routine(id, data);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    let data = result.unwrap();
+
+    for item in &data {
+        prompt_habit_dependents(&manager, item);
+    }
+
+    // Return response
+    let response = RoutineCompleteResponse {
+        message: "Routine completed successfully".to_string(),
+        data,
+    };
+
+    Ok(with_status(json(&response), StatusCode::CREATED))
+}
+
+// GET Route
+pub async fn get_routine_progress_handler(
+    id: Uuid,
+    params: RoutineProgressParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    check_routine_access(&manager, authentication.requester_id, id)?;
+
+    let result = manager.get_routine_progress(id, params.date);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = RoutineProgressQueryResponse {
+        message: "Successfully retrieved routine progress".to_string(),
+        progress: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
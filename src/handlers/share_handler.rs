@@ -0,0 +1,353 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::api::{share_api_models::*, *},
+    utils::{
+        svg::{get_svg_color, render_badge, render_bar_chart, render_heatmap, render_line_chart},
+        time::{parse_range_days, MAXIMUM_DATE, MINIMUM_DATE},
+        MAX_CALENDAR_DAYS,
+    },
+};
+
+use bigdecimal::ToPrimitive;
+use chrono::Datelike;
+use warp::{
+    http::StatusCode,
+    reply::{json, with_header, with_status},
+    Rejection, Reply,
+};
+
+use uuid::Uuid;
+
+// Embeds are fetched by third party pages, let them cache the image for a while
+fn svg_reply(svg: String) -> impl Reply {
+    with_header(
+        with_header(svg, "content-type", "image/svg+xml"),
+        "cache-control",
+        "public, max-age=300",
+    )
+}
+
+// POST Route
+pub async fn create_habit_share_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    let result = manager.add_habit_share(id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitShareCreateResponse {
+        message: "Share token created successfully".to_string(),
+        share: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::CREATED))
+}
+
+// GET Route
+pub async fn get_habit_shares_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    let result = manager.get_habit_shares(id);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitShareMultipleQueryResponse {
+        message: "Successfully retrieved share tokens".to_string(),
+        shares: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// DELETE Route
+pub async fn delete_habit_share_handler(
+    token: String,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let share = manager.get_habit_share(token.clone());
+
+    if share.is_err() {
+        return Err(warp::reject::custom(share.err().unwrap()));
+    }
+
+    // Only the owner of the shared habit can revoke its tokens
+    let result =
+        manager.is_habit_accessible_by_user(authentication.requester_id, share.unwrap().hab_id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    let result = manager.delete_habit_share(token);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = GeneralResponse {
+        message: "Share token revoked successfully".to_string(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route (public, through share token)
+pub async fn get_share_badge_handler(
+    token: String,
+    manager: DBManager,
+) -> Result<impl Reply, Rejection> {
+    let share = manager.get_habit_share(token);
+
+    if share.is_err() {
+        return Err(warp::reject::custom(share.err().unwrap()));
+    }
+
+    let id = share.unwrap().hab_id;
+
+    let habit = manager.get_habit_by_id(id);
+
+    if habit.is_err() {
+        return Err(warp::reject::custom(habit.err().unwrap()));
+    }
+
+    let habit = habit.unwrap();
+
+    // Completion rate over the whole life of the habit
+    let stats = manager.get_habit_stats(id, None, None);
+
+    if stats.is_err() {
+        return Err(warp::reject::custom(stats.err().unwrap()));
+    }
+
+    let streaks = manager.get_habits_streaks(&[id]);
+
+    if streaks.is_err() {
+        return Err(warp::reject::custom(streaks.err().unwrap()));
+    }
+
+    let streak = streaks
+        .unwrap()
+        .first()
+        .map(|streak| streak.streak)
+        .unwrap_or(0);
+
+    let completion = match stats.unwrap().completion_rate {
+        Some(rate) => format!("{:.0}%", rate * 100.0),
+        None => "-".to_string(),
+    };
+
+    let svg = render_badge(
+        &habit.hab_name,
+        &format!("streak {} | {}", streak, completion),
+        &get_svg_color(&habit.hab_color),
+    );
+
+    Ok(svg_reply(svg))
+}
+
+// GET Route (public, through share token)
+pub async fn get_share_chart_handler(
+    token: String,
+    date_params: DateParams,
+    chart_params: ChartParams,
+    manager: DBManager,
+) -> Result<impl Reply, Rejection> {
+    let share = manager.get_habit_share(token);
+
+    if share.is_err() {
+        return Err(warp::reject::custom(share.err().unwrap()));
+    }
+
+    let id = share.unwrap().hab_id;
+
+    let habit = manager.get_habit_by_id(id);
+
+    if habit.is_err() {
+        return Err(warp::reject::custom(habit.err().unwrap()));
+    }
+
+    let habit = habit.unwrap();
+    let color = get_svg_color(&habit.hab_color);
+    let current_date = chrono::Local::now().naive_local().date();
+
+    let kind = chart_params.kind.unwrap_or(ChartKind::Bar);
+
+    // Without a range, heatmaps cover the year of the end date, same as the heatmap endpoints
+    if matches!(kind, ChartKind::Heatmap) && chart_params.range.is_none() {
+        let year = date_params.end_date.unwrap_or(current_date).year();
+        let result = manager.get_habits_heatmap(None, Some(id), None, year);
+
+        if result.is_err() {
+            return Err(warp::reject::custom(result.err().unwrap()));
+        }
+
+        let (start_date, _, days) = result.unwrap();
+        let title = format!("{} ({})", habit.hab_name, year);
+
+        return Ok(svg_reply(render_heatmap(&title, &days, start_date, &color)));
+    }
+
+    // By default, show the last month
+    let end_date = date_params.end_date.unwrap_or(current_date);
+    let start_date = match &chart_params.range {
+        Some(range) => {
+            let days = parse_range_days(range);
+
+            if days.is_none() {
+                return Err(warp::reject::custom(Error::BadRequest(
+                    "Range must be a number of days, weeks, months or years (30d, 12w, 6m, 1y)"
+                        .to_string(),
+                )));
+            }
+
+            // Longer ranges are rejected below, without computing dates out of bounds
+            let start_date = end_date.checked_sub_signed(chrono::Duration::days(
+                days.unwrap().min(MAX_CALENDAR_DAYS + 2) - 1,
+            ));
+
+            if start_date.is_none() {
+                return Err(warp::reject::custom(Error::BadRequest(
+                    "Range goes back too far from the end date".to_string(),
+                )));
+            }
+
+            start_date.unwrap()
+        }
+        None => date_params
+            .start_date
+            .unwrap_or(end_date - chrono::Duration::days(30)),
+    };
+
+    // Public embeds are capped the same way as calendar requests
+    if end_date < start_date {
+        return Err(warp::reject::custom(Error::BadRequest(
+            "End date must not be before start date".to_string(),
+        )));
+    }
+
+    if (end_date - start_date).num_days() > MAX_CALENDAR_DAYS {
+        return Err(warp::reject::custom(Error::BadRequest(format!(
+            "Date range must not exceed {} days",
+            MAX_CALENDAR_DAYS
+        ))));
+    }
+
+    if start_date < MINIMUM_DATE.unwrap() || end_date > MAXIMUM_DATE.unwrap() {
+        return Err(warp::reject::custom(Error::BadRequest(format!(
+            "Dates must be between {} and {}",
+            MINIMUM_DATE.unwrap(),
+            MAXIMUM_DATE.unwrap()
+        ))));
+    }
+
+    if let ChartKind::Heatmap = kind {
+        let result = manager.get_habits_heatmap_between(None, Some(id), None, start_date, end_date);
+
+        if result.is_err() {
+            return Err(warp::reject::custom(result.err().unwrap()));
+        }
+
+        let (start_date, _, days) = result.unwrap();
+
+        return Ok(svg_reply(render_heatmap(
+            &habit.hab_name,
+            &days,
+            start_date,
+            &color,
+        )));
+    }
+
+    let result = manager.get_habitdata_as_calendar(
+        None,
+        Some(id),
+        Some(start_date),
+        Some(end_date),
+        chart_params.group_by,
+    );
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    let points: Vec<(chrono::NaiveDate, f64)> = result
+        .unwrap()
+        .iter()
+        .map(|event| (event.date, event.data.to_f64().unwrap_or(0.0)))
+        .collect();
+
+    let svg = match kind {
+        ChartKind::Line => render_line_chart(&habit.hab_name, &points, &color),
+        _ => render_bar_chart(&habit.hab_name, &points, &color),
+    };
+
+    Ok(svg_reply(svg))
+}
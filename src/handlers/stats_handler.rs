@@ -0,0 +1,199 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::api::{stats_api_models::*, *},
+};
+
+use warp::{
+    http::StatusCode,
+    reply::{json, with_status},
+    Rejection, Reply,
+};
+
+use uuid::Uuid;
+
+// GET Route
+pub async fn get_habit_stats_handler(
+    id: Uuid,
+    date_params: DateParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    // Compute statistics from database
+    let result = manager.get_habit_stats(id, date_params.start_date, date_params.end_date);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitStatsQueryResponse {
+        message: "Successfully retrieved habit stats".to_string(),
+        stats: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_habit_summary_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    let habit = manager.get_habit_by_id(id);
+
+    if habit.is_err() {
+        return Err(warp::reject::custom(habit.err().unwrap()));
+    }
+
+    let current_date = chrono::Local::now().naive_local().date();
+    let result = manager.get_habit_summary(&habit.unwrap(), current_date);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitSummaryQueryResponse {
+        message: "Successfully retrieved habit summary".to_string(),
+        summary: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_habits_correlations_handler(
+    date_params: DateParams,
+    correlation_params: CorrelationParams,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check a user is logged in / provided the action
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    let result = manager.get_habits_correlations(
+        authentication.requester_id,
+        date_params.start_date,
+        date_params.end_date,
+        correlation_params.min_overlap,
+        correlation_params.include_mood.unwrap_or(false),
+    );
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    // Return response
+    let response = HabitCorrelationsQueryResponse {
+        message: "Successfully retrieved habits correlations".to_string(),
+        analysis: result.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
+
+// GET Route
+pub async fn get_habit_progress_handler(
+    id: Uuid,
+    manager: DBManager,
+    authentication: AuthData,
+) -> Result<impl Reply, Rejection> {
+    // Check if user is logged in
+    if matches!(authentication.role, AuthRole::Guest) {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "Missing user id in request header (user_id)".to_string(),
+        )));
+    }
+
+    // Check if habit is accessible by user
+    let result = manager.is_habit_accessible_by_user(authentication.requester_id, id);
+
+    if result.is_err() {
+        return Err(warp::reject::custom(result.err().unwrap()));
+    }
+
+    if !result.unwrap() {
+        return Err(warp::reject::custom(Error::AuthorizationError(
+            "User is not the owner of the habit".to_string(),
+        )));
+    }
+
+    let habit = manager.get_habit_by_id(id);
+
+    if habit.is_err() {
+        return Err(warp::reject::custom(habit.err().unwrap()));
+    }
+
+    let current_date = chrono::Local::now().naive_local().date();
+    let result = manager.get_habits_forecasts(&[habit.unwrap()], current_date);
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        return Err(warp::reject::custom(error));
+    }
+
+    let progress = result.unwrap().into_iter().next();
+
+    if progress.is_none() {
+        return Err(warp::reject::custom(Error::BadRequest(
+            "Habit has no period in progress".to_string(),
+        )));
+    }
+
+    // Return response
+    let response = HabitProgressQueryResponse {
+        message: "Successfully retrieved habit progress".to_string(),
+        progress: progress.unwrap(),
+    };
+
+    Ok(with_status(json(&response), StatusCode::OK))
+}
@@ -1,34 +1,457 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use serde_derive::Serialize;
+use std::str::FromStr;
+
 use crate::{
     db::{DBManager, PostgresPool},
-    services::reminders_service::enqueue_reminders_service,
+    error::Error,
+    models::{
+        api::report_api_models::ReviewPeriod,
+        database::{Habit, HabitPeriod},
+    },
+    services::reminders_service::{
+        build_challenge_notification, build_digest_notification, build_forecast_notifications,
+        build_reminder_notifications, enqueue_reminders_service, ReminderNotification,
+    },
+    utils::{periods::HabitClosure, JOB_TICK_TOLERANCE_SECONDS},
 };
 
-pub async fn check_reminders_update(pool_write: PostgresPool) {
+pub const REMINDERS_UPDATE_JOB: &str = "reminders_update";
+pub const WEEKLY_DIGEST_JOB: &str = "weekly_digest";
+pub const FORECAST_REMINDERS_JOB: &str = "forecast_reminders";
+pub const CHALLENGES_FINISHED_JOB: &str = "challenges_finished";
+
+pub const JOB_NAMES: [&str; 4] = [
+    REMINDERS_UPDATE_JOB,
+    WEEKLY_DIGEST_JOB,
+    FORECAST_REMINDERS_JOB,
+    CHALLENGES_FINISHED_JOB,
+];
+
+lazy_static::lazy_static! {
+    // Identifies this service instance in the jobs history
+    pub static ref REPLICA_ID: String =
+        std::env::var("HOSTNAME").unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
+}
+
+// Everything a job would do, computed without writing nor calling the gateway
+#[derive(Debug, Serialize)]
+pub struct JobPlan {
+    pub job_name: String,
+
+    pub as_of: NaiveDate,
+
+    // Habits whose period would be closed, or whose summary would be sent
+    pub habits: Vec<Habit>,
+
+    // Period records that would be saved
+    pub periods: Vec<HabitPeriod>,
+
+    // Closure dates habits would move to
+    pub closures: Vec<HabitClosure>,
+
+    // Notifications that would be sent through the gateway
+    pub notifications: Vec<ReminderNotification>,
+}
+
+// Fire time of the schedule a run started for, the same on every replica despite clock skew or
+// scheduler jitter within the tolerance
+pub fn get_scheduled_tick(schedule: &str, started_at: DateTime<Utc>) -> Option<NaiveDateTime> {
+    let schedule = cron::Schedule::from_str(schedule).ok()?;
+
+    schedule
+        .after(&(started_at - Duration::seconds(JOB_TICK_TOLERANCE_SECONDS)))
+        .next()
+        .map(|tick| tick.naive_utc())
+}
+
+// Run a scheduled job only if no other replica claimed the current tick
+pub async fn run_scheduled_job(pool_write: PostgresPool, job_name: &str, schedule: &str) {
     let manager = DBManager::new(Some(pool_write), None);
 
-    let updated_habits = manager.get_update_pending_habits();
+    let tick = get_scheduled_tick(schedule, chrono::Utc::now());
+
+    if tick.is_none() {
+        println!("[JOBS] Invalid schedule {} for job {}", schedule, job_name);
+        return;
+    }
+
+    let tick = tick.unwrap();
 
-    if updated_habits.is_err() {
+    // Held until the run is over
+    let job_lock = manager.lock_job(job_name);
+
+    if job_lock.is_err() {
         println!(
-            "Error getting updated habits: {:?}",
-            updated_habits.err().unwrap()
+            "[JOBS] Error locking job {}: {:?}",
+            job_name,
+            job_lock.err().unwrap()
         );
         return;
     }
 
-    let updated_habits = updated_habits.unwrap();
+    let job_lock = job_lock.unwrap();
 
-    if updated_habits.is_empty() {
-        println!("No updated habits");
+    if job_lock.is_none() {
+        println!("[JOBS] Job {} is already running", job_name);
         return;
     }
 
-    let result = enqueue_reminders_service(updated_habits).await;
+    let job_run = manager.claim_job_run(job_name, tick, &REPLICA_ID);
 
-    if result.is_err() {
-        println!("Error enqueuing reminders: {:?}", result.err().unwrap());
+    if job_run.is_err() {
+        println!(
+            "[JOBS] Error claiming job {}: {:?}",
+            job_name,
+            job_run.err().unwrap()
+        );
         return;
     }
 
+    let job_run = job_run.unwrap();
+
+    if job_run.is_none() {
+        println!(
+            "[JOBS] Job {} tick already claimed by another replica",
+            job_name
+        );
+        return;
+    }
+
+    let job_run = job_run.unwrap();
+
+    let as_of = chrono::Local::now().naive_local().date();
+    let result = run_job(&manager, job_name, as_of).await;
+
+    // Planning errors and gateway failures alike are kept in the runs history
+    let (habits_processed, error) = match result {
+        Ok(plan) => (plan.habits.len() as i32, None),
+        Err(error) => (0, Some(error)),
+    };
+
+    let finish = manager.finish_job_run(job_run.job_run_id, habits_processed, error);
+
+    if finish.is_err() {
+        println!(
+            "[JOBS] Error saving job {} run: {:?}",
+            job_name,
+            finish.err().unwrap()
+        );
+    }
+}
+
+// Plan and apply a job, returns the applied plan
+pub async fn run_job(
+    manager: &DBManager,
+    job_name: &str,
+    as_of: NaiveDate,
+) -> Result<JobPlan, String> {
+    let plan = plan_job(manager, job_name, as_of);
+
+    if plan.is_err() {
+        let error = format!("Error planning job {}: {:?}", job_name, plan.err().unwrap());
+        println!("{}", error);
+        return Err(error);
+    }
+
+    let plan = plan.unwrap();
+    let result = apply_job(manager, &plan).await;
+
+    if result.is_err() {
+        let error = result.err().unwrap();
+        println!("{}", error);
+        return Err(error);
+    }
+
+    Ok(plan)
+}
+
+// Compute what a job would do as of a given date
+pub fn plan_job(manager: &DBManager, job_name: &str, as_of: NaiveDate) -> Result<JobPlan, Error> {
+    match job_name {
+        REMINDERS_UPDATE_JOB => plan_reminders_update(manager, as_of),
+        WEEKLY_DIGEST_JOB => plan_weekly_digest(manager, as_of),
+        FORECAST_REMINDERS_JOB => plan_forecast_reminders(manager, as_of),
+        CHALLENGES_FINISHED_JOB => plan_challenges_finished(manager, as_of),
+        _ => Err(Error::BadRequest(format!("Unknown job {}", job_name))),
+    }
+}
+
+// Execute a previously computed plan
+pub async fn apply_job(manager: &DBManager, plan: &JobPlan) -> Result<(), String> {
+    match plan.job_name.as_str() {
+        REMINDERS_UPDATE_JOB => apply_reminders_update(manager, plan).await,
+        WEEKLY_DIGEST_JOB => apply_weekly_digest(manager, plan).await,
+        FORECAST_REMINDERS_JOB => apply_forecast_reminders(plan).await,
+        CHALLENGES_FINISHED_JOB => apply_challenges_finished(manager, plan).await,
+        _ => Err(format!("Unknown job {}", plan.job_name)),
+    }
+}
+
+pub fn plan_reminders_update(manager: &DBManager, as_of: NaiveDate) -> Result<JobPlan, Error> {
+    let habits = manager.get_pending_habits(as_of);
+
+    if habits.is_err() {
+        return Err(habits.err().unwrap());
+    }
+
+    let habits = habits.unwrap();
+
+    let periods = manager.build_closing_periods(&habits, as_of, chrono::Local::now().naive_local());
+
+    if periods.is_err() {
+        return Err(periods.err().unwrap());
+    }
+
+    let (periods, closures) = periods.unwrap();
+    let notifications = build_reminder_notifications(&habits, as_of);
+
+    Ok(JobPlan {
+        job_name: REMINDERS_UPDATE_JOB.to_string(),
+        as_of,
+        habits,
+        periods,
+        closures,
+        notifications,
+    })
+}
+
+pub async fn apply_reminders_update(manager: &DBManager, plan: &JobPlan) -> Result<(), String> {
+    if plan.habits.is_empty() {
+        println!("No updated habits");
+        return Ok(());
+    }
+
+    let result = manager.close_habit_periods(&plan.periods, &plan.closures);
+
+    if result.is_err() {
+        return Err(format!(
+            "Error updating pending habits: {:?}",
+            result.err().unwrap()
+        ));
+    }
+
+    let result = enqueue_reminders_service(plan.notifications.clone()).await;
+
+    if result.is_err() {
+        return Err(format!(
+            "Error enqueuing reminders: {}",
+            result.err().unwrap()
+        ));
+    }
+
     println!("Enqueued reminders");
+
+    Ok(())
+}
+
+// Review of the last full week for every user whose digest is due
+pub fn plan_weekly_digest(manager: &DBManager, as_of: NaiveDate) -> Result<JobPlan, Error> {
+    let users = manager.get_due_digest_users(as_of);
+
+    if users.is_err() {
+        return Err(users.err().unwrap());
+    }
+
+    let mut notifications: Vec<ReminderNotification> = Vec::new();
+
+    for user_id in users.unwrap() {
+        let review = manager.get_user_review(
+            user_id.clone(),
+            ReviewPeriod::Week,
+            as_of - chrono::Duration::days(7),
+            as_of,
+        );
+
+        if review.is_err() {
+            return Err(review.err().unwrap());
+        }
+
+        let review = review.unwrap();
+
+        // Nothing to tell users without habits
+        if review.habits.is_empty() {
+            continue;
+        }
+
+        notifications.push(build_digest_notification(user_id, &review, as_of));
+    }
+
+    Ok(JobPlan {
+        job_name: WEEKLY_DIGEST_JOB.to_string(),
+        as_of,
+        habits: Vec::new(),
+        periods: Vec::new(),
+        closures: Vec::new(),
+        notifications,
+    })
+}
+
+pub async fn apply_weekly_digest(manager: &DBManager, plan: &JobPlan) -> Result<(), String> {
+    if plan.notifications.is_empty() {
+        println!("No digests to send");
+        return Ok(());
+    }
+
+    let result = enqueue_reminders_service(plan.notifications.clone()).await;
+
+    if result.is_err() {
+        return Err(format!(
+            "Error enqueuing digests: {}",
+            result.err().unwrap()
+        ));
+    }
+
+    // Only digests the gateway took are recorded, failed ones are sent again by the next run
+    let user_ids: Vec<String> = plan
+        .notifications
+        .iter()
+        .map(|notification| notification.user_id.clone())
+        .collect();
+
+    let result = manager.mark_digests_sent(&user_ids, plan.as_of);
+
+    if result.is_err() {
+        return Err(format!(
+            "Error saving sent digests: {:?}",
+            result.err().unwrap()
+        ));
+    }
+
+    println!("Enqueued digests");
+
+    Ok(())
+}
+
+// Reminders for habits whose current period is at risk of missing the goal
+pub fn plan_forecast_reminders(manager: &DBManager, as_of: NaiveDate) -> Result<JobPlan, Error> {
+    let habits = manager.get_habits_in_progress(as_of);
+
+    if habits.is_err() {
+        return Err(habits.err().unwrap());
+    }
+
+    let habits = habits.unwrap();
+
+    let forecasts = manager.get_habits_forecasts(&habits, as_of);
+
+    if forecasts.is_err() {
+        return Err(forecasts.err().unwrap());
+    }
+
+    let notifications = build_forecast_notifications(&habits, &forecasts.unwrap(), as_of);
+
+    Ok(JobPlan {
+        job_name: FORECAST_REMINDERS_JOB.to_string(),
+        as_of,
+        habits: Vec::new(),
+        periods: Vec::new(),
+        closures: Vec::new(),
+        notifications,
+    })
+}
+
+pub async fn apply_forecast_reminders(plan: &JobPlan) -> Result<(), String> {
+    if plan.notifications.is_empty() {
+        println!("No habits at risk");
+        return Ok(());
+    }
+
+    let result = enqueue_reminders_service(plan.notifications.clone()).await;
+
+    if result.is_err() {
+        return Err(format!(
+            "Error enqueuing forecast reminders: {}",
+            result.err().unwrap()
+        ));
+    }
+
+    println!("Enqueued forecast reminders");
+
+    Ok(())
+}
+
+// Completion summary of every challenge that ended, including those missed by earlier runs
+pub fn plan_challenges_finished(manager: &DBManager, as_of: NaiveDate) -> Result<JobPlan, Error> {
+    let habits = manager.get_finished_habits(as_of);
+
+    if habits.is_err() {
+        return Err(habits.err().unwrap());
+    }
+
+    let habits = habits.unwrap();
+    let mut notifications: Vec<ReminderNotification> = Vec::new();
+
+    for habit in &habits {
+        let summary = manager.get_habit_summary(habit, as_of);
+
+        if summary.is_err() {
+            return Err(summary.err().unwrap());
+        }
+
+        notifications.push(build_challenge_notification(
+            habit,
+            &summary.unwrap(),
+            as_of,
+        ));
+    }
+
+    Ok(JobPlan {
+        job_name: CHALLENGES_FINISHED_JOB.to_string(),
+        as_of,
+        habits,
+        periods: Vec::new(),
+        closures: Vec::new(),
+        notifications,
+    })
+}
+
+pub async fn apply_challenges_finished(manager: &DBManager, plan: &JobPlan) -> Result<(), String> {
+    if plan.notifications.is_empty() {
+        println!("No finished challenges");
+        return Ok(());
+    }
+
+    // Summaries are sent user by user, so a failed send only leaves that user's pending
+    let mut users: std::collections::BTreeMap<&str, (Vec<uuid::Uuid>, Vec<ReminderNotification>)> =
+        std::collections::BTreeMap::new();
+
+    for (habit, notification) in plan.habits.iter().zip(&plan.notifications) {
+        let (habit_ids, notifications) = users.entry(&habit.usr_id).or_default();
+
+        habit_ids.push(habit.hab_id);
+        notifications.push(notification.clone());
+    }
+
+    let mut sent_ids: Vec<uuid::Uuid> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    for (user_id, (habit_ids, notifications)) in users {
+        let result = enqueue_reminders_service(notifications).await;
+
+        match result {
+            Ok(_) => sent_ids.extend(habit_ids),
+            Err(error) => errors.push(format!("{} ({})", error, user_id)),
+        }
+    }
+
+    let result = manager.mark_summaries_sent(&sent_ids, plan.as_of);
+
+    if result.is_err() {
+        return Err(format!(
+            "Error saving sent challenge summaries: {:?}",
+            result.err().unwrap()
+        ));
+    }
+
+    if !errors.is_empty() {
+        return Err(format!(
+            "Error enqueuing challenge summaries: {}",
+            errors.join(", ")
+        ));
+    }
+
+    println!("Enqueued challenge summaries");
+
+    Ok(())
 }
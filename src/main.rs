@@ -78,40 +78,58 @@ async fn main() {
 
     if !sched.is_err() {
         let sched = sched.unwrap();
-        let job = Job::new_async("0 0 0 12 * *", move |_, _| {
-            let jobs_pool = db::create_pool_write();
 
-            if jobs_pool.is_err() {
-                println!("[JOBS] Error creating pool: {:?}", jobs_pool.err());
-                return Box::pin(async move {});
+        // Digests run daily, each user picks the weekday they're sent on
+        let schedules = [
+            ("0 0 0 12 * *", jobs::REMINDERS_UPDATE_JOB),
+            ("0 0 8 * * *", jobs::WEEKLY_DIGEST_JOB),
+            ("0 0 17 * * *", jobs::FORECAST_REMINDERS_JOB),
+            ("0 0 9 * * *", jobs::CHALLENGES_FINISHED_JOB),
+        ];
+
+        let mut scheduled = true;
+
+        for (schedule, job_name) in schedules {
+            let job = Job::new_async(schedule, move |_, _| {
+                let jobs_pool = db::create_pool_write();
+
+                if jobs_pool.is_err() {
+                    println!("[JOBS] Error creating pool: {:?}", jobs_pool.err());
+                    return Box::pin(async move {});
+                }
+
+                let jobs_pool = jobs_pool.unwrap();
+
+                Box::pin(async move {
+                    jobs::run_scheduled_job(jobs_pool.clone(), job_name, schedule).await;
+                })
+            });
+
+            if job.is_err() {
+                println!("Error creating job: {:?}", job.err());
+                scheduled = false;
+                break;
             }
 
-            let jobs_pool = jobs_pool.unwrap();
-
-            Box::pin(async move {
-                jobs::check_reminders_update(jobs_pool.clone()).await;
-            })
-        });
-
-        if !job.is_err() {
-            let job = job.unwrap();
-            let result = sched.add(job).await;
-
-            if !result.is_err() {
-                // Start the scheduler
-                tokio::spawn(async move {
-                    let result = sched.start().await;
-                    if !result.is_err() {
-                        println!("Scheduler exited with result: {:?}", result);
-                    } else {
-                        println!("Scheduler exited with error: {:?}", result.err());
-                    }
-                });
-            } else {
+            let result = sched.add(job.unwrap()).await;
+
+            if result.is_err() {
                 println!("Error adding job: {:?}", result.err());
+                scheduled = false;
+                break;
             }
-        } else {
-            println!("Error creating job: {:?}", job.err());
+        }
+
+        if scheduled {
+            // Start the scheduler
+            tokio::spawn(async move {
+                let result = sched.start().await;
+                if result.is_ok() {
+                    println!("Scheduler exited with result: {:?}", result);
+                } else {
+                    println!("Scheduler exited with error: {:?}", result.err());
+                }
+            });
         }
     } else {
         println!("Error creating scheduler: {:?}", sched.err());
@@ -0,0 +1,144 @@
+use crate::models::database::{
+    Challenge, ChallengeInvitation, ChallengeParticipant, ChlParPrivacyEnum, HabAggregationEnum,
+    HabFreqTypeEnum, HabGoalDirectionEnum,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use uuid::Uuid;
+use validator::Validate;
+
+// Embedded models
+#[derive(Debug, Serialize)]
+pub struct LeaderboardEntry {
+    // Participants with the same results share their rank
+    pub rank: i64,
+
+    // Left out for anonymous participants
+    pub usr_id: Option<String>,
+
+    pub is_requester: bool,
+
+    // Periods already over, and how many of them met the goal
+    pub periods_count: i64,
+
+    pub periods_met: i64,
+
+    pub completion_rate: Option<f64>,
+
+    pub amount: BigDecimal,
+
+    pub days_logged: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeLeaderboard {
+    pub chl_id: Uuid,
+
+    pub start_date: NaiveDate,
+
+    pub end_date: NaiveDate,
+
+    // Hidden participants count here but aren't listed to others
+    pub participants_count: i64,
+
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+// Requests schemas
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChallengeCreateSchema {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+
+    #[validate(length(min = 1, max = 255))]
+    pub description: String,
+
+    pub is_yn: bool,
+
+    #[validate(length(min = 1, max = 10))]
+    pub units: String,
+
+    #[validate(custom = "crate::validators::validate_bigdecimal")]
+    pub goal: BigDecimal,
+
+    pub frequency_type: HabFreqTypeEnum,
+
+    // At least by default
+    pub goal_direction: Option<HabGoalDirectionEnum>,
+
+    // Sum by default
+    pub aggregation: Option<HabAggregationEnum>,
+
+    pub start_date: NaiveDate,
+
+    pub end_date: NaiveDate,
+
+    // Invitation only by default
+    pub is_public: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChallengeJoinSchema {
+    // Category of the habit created for the challenge
+    pub category: Uuid,
+
+    #[validate(length(min = 6, max = 6))]
+    pub color: Option<String>,
+
+    // Public by default
+    pub privacy: Option<ChlParPrivacyEnum>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChallengePrivacySchema {
+    pub privacy: ChlParPrivacyEnum,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChallengeInvitationCreateSchema {
+    #[validate(length(min = 1, max = 24))]
+    pub user_id: String,
+}
+
+// Responses
+#[derive(Debug, Serialize)]
+pub struct ChallengeCreateResponse {
+    pub message: String,
+
+    pub id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeSingleQueryResponse {
+    pub message: String,
+
+    pub challenge: Challenge,
+
+    pub participants_count: i64,
+
+    // Requester's own participation, if any
+    pub participant: Option<ChallengeParticipant>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeJoinResponse {
+    pub message: String,
+
+    pub participant: ChallengeParticipant,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeInvitationCreateResponse {
+    pub message: String,
+
+    pub invitation: ChallengeInvitation,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeLeaderboardQueryResponse {
+    pub message: String,
+
+    pub leaderboard: ChallengeLeaderboard,
+}
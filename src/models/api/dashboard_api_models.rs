@@ -0,0 +1,39 @@
+use crate::models::database::Habit;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use serde_derive::Serialize;
+
+// Embedded models
+#[derive(Debug, Serialize)]
+pub struct DashboardHabit {
+    pub habit: Habit,
+
+    // Current period bounds (both inclusive)
+    pub period_start_date: NaiveDate,
+
+    pub period_end_date: NaiveDate,
+
+    pub amount: BigDecimal,
+
+    // Amount over goal, may go beyond 1 when the goal is exceeded
+    pub progress: f64,
+
+    pub is_goal_met: bool,
+
+    // Consecutive periods meeting the goal, including the current one once met
+    pub streak: i64,
+
+    pub today_logged: bool,
+
+    pub next_closure_date: NaiveDate,
+}
+
+// Response schemas
+#[derive(Debug, Serialize)]
+pub struct DashboardQueryResponse {
+    pub message: String,
+
+    pub date: NaiveDate,
+
+    pub habits: Vec<DashboardHabit>,
+}
@@ -20,6 +20,10 @@ pub struct HabitDataCreateSchema {
 
     // Optional for update only
     pub habit_id: Uuid,
+
+    // Mood when logging the entry, from 1 to 5
+    #[validate(range(min = 1, max = 5))]
+    pub mood: Option<i16>,
 }
 
 #[derive(Debug, Deserialize, Validate, AsChangeset)]
@@ -28,6 +32,10 @@ pub struct HabitDataUpdateSchema {
     #[validate(custom = "crate::validators::validate_bigdecimal")]
     #[diesel(column_name = "hab_dat_amount")]
     pub amount: BigDecimal,
+
+    #[validate(range(min = 1, max = 5))]
+    #[diesel(column_name = "hab_dat_mood")]
+    pub mood: Option<i16>,
 }
 
 // Response schemas
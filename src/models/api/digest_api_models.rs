@@ -0,0 +1,21 @@
+use crate::models::database::UserDigestPreference;
+use serde_derive::{Deserialize, Serialize};
+use validator::Validate;
+
+// Requests schemas
+#[derive(Debug, Deserialize, Validate)]
+pub struct DigestPreferenceUpdateSchema {
+    pub enabled: bool,
+
+    // ISO day of week (1 is Monday)
+    #[validate(range(min = 1, max = 7))]
+    pub weekday: i16,
+}
+
+// Response schemas
+#[derive(Debug, Serialize)]
+pub struct DigestPreferenceQueryResponse {
+    pub message: String,
+
+    pub preference: UserDigestPreference,
+}
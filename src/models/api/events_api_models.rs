@@ -1,6 +1,41 @@
 use bigdecimal::BigDecimal;
 use chrono::NaiveDate;
-use serde_derive::Serialize;
+use diesel::{
+    sql_types::{BigInt, Date, Numeric},
+    QueryableByName,
+};
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Query params
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarGrouping {
+    Day,
+    Week,
+    Month,
+}
+
+impl CalendarGrouping {
+    // Precision name understood by PostgreSQL date_trunc
+    pub fn as_date_trunc_field(&self) -> &'static str {
+        match self {
+            CalendarGrouping::Day => "day",
+            CalendarGrouping::Week => "week",
+            CalendarGrouping::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarGroupParams {
+    pub group_by: Option<CalendarGrouping>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeatmapParams {
+    pub year: Option<i32>,
+}
 
 // Embedded models
 #[derive(Debug, Serialize)]
@@ -8,13 +43,72 @@ pub struct Event {
     pub date: NaiveDate,
 }
 
-#[derive(Debug, Serialize)]
+// Data summarized by day (or by first day of the week / month when grouped)
+#[derive(Debug, Serialize, QueryableByName)]
 pub struct CalendarEvent {
+    #[diesel(sql_type = Date)]
     pub date: NaiveDate,
+
+    #[diesel(sql_type = Numeric)]
     pub data: BigDecimal,
+
+    #[diesel(sql_type = Numeric)]
     pub relative_frequency: BigDecimal,
 }
 
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodStatus {
+    Done,
+    Partial,
+    Missed,
+    Paused,
+    Upcoming,
+}
+
+// Expected occurrence (period) of a habit along with what was collected within it
+#[derive(Debug, Serialize)]
+pub struct CalendarPeriod {
+    pub hab_id: Uuid,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub amount: BigDecimal,
+    pub goal: BigDecimal,
+    pub status: PeriodStatus,
+}
+
+// Data of a habit summarized by day
+#[derive(Debug, QueryableByName)]
+pub struct HabitDailyAmount {
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    pub hab_id: Uuid,
+
+    #[diesel(sql_type = Date)]
+    pub date: NaiveDate,
+
+    #[diesel(sql_type = Numeric)]
+    pub amount: BigDecimal,
+
+    // Records the amount was aggregated from
+    #[diesel(sql_type = BigInt)]
+    pub records: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HeatmapDayStatus {
+    Active,
+    Paused,
+    Upcoming,
+}
+
+// Day intensity, from 0 (nothing logged) to 1 (goal reached)
+#[derive(Debug, Serialize)]
+pub struct HeatmapDay {
+    pub value: f64,
+    pub status: HeatmapDayStatus,
+}
+
 // Response models
 #[derive(Debug, Serialize)]
 pub struct EventsMultipleQueryResponse {
@@ -27,3 +121,18 @@ pub struct CalendarEventsMultipleQueryResponse {
     pub message: String,
     pub events: Vec<CalendarEvent>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct CalendarPeriodsMultipleQueryResponse {
+    pub message: String,
+    pub periods: Vec<CalendarPeriod>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeatmapQueryResponse {
+    pub message: String,
+    pub year: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub days: Vec<HeatmapDay>,
+}
@@ -1,4 +1,7 @@
-use crate::models::database::{HabFreqTypeEnum, Habit, HabitDataCollected};
+use crate::models::database::{
+    HabAggregationEnum, HabChecklistModeEnum, HabDerivationEnum, HabFreqTypeEnum,
+    HabGoalDirectionEnum, Habit, HabitDataCollected, HabitDependency, HabitDerivation, HabitPeriod,
+};
 use crate::schema::habit;
 use diesel::query_builder::AsChangeset;
 use serde_derive::{Deserialize, Serialize};
@@ -28,6 +31,18 @@ pub struct HabitWithData {
 
     pub hab_freq_type: HabFreqTypeEnum,
 
+    pub hab_goal_direction: HabGoalDirectionEnum,
+
+    pub hab_goal_tolerance: BigDecimal,
+
+    pub hab_aggregation: HabAggregationEnum,
+
+    pub hab_start_date: Option<chrono::NaiveDate>,
+
+    pub hab_end_date: Option<chrono::NaiveDate>,
+
+    pub hab_checklist_mode: HabChecklistModeEnum,
+
     pub usr_id: String,
 
     pub cat_id: Uuid,
@@ -35,6 +50,26 @@ pub struct HabitWithData {
     pub data: Vec<HabitDataCollected>,
 }
 
+// How the period in progress was handled when a habit's frequency changed
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodTreatment {
+    // Closed the day before the change and judged against the previous goal, prorated
+    Closed,
+
+    // Started on the day of the change, so it starts over with the new definition
+    Restarted,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InFlightPeriod {
+    pub treatment: PeriodTreatment,
+
+    pub closed_period: Option<HabitPeriod>,
+
+    pub next_closure_date: chrono::NaiveDate,
+}
+
 // Requests schemas
 #[derive(Debug, Deserialize, Validate)]
 pub struct HabitCreateSchema {
@@ -62,6 +97,32 @@ pub struct HabitCreateSchema {
     pub category: Uuid,
 
     pub location: Option<String>,
+
+    // At least by default
+    pub goal_direction: Option<HabGoalDirectionEnum>,
+
+    #[validate(custom = "crate::validators::validate_bigdecimal")]
+    pub goal_tolerance: Option<BigDecimal>,
+
+    // Sum by default
+    pub aggregation: Option<HabAggregationEnum>,
+
+    // Creation date by default
+    pub start_date: Option<chrono::NaiveDate>,
+
+    // Habits without an end date run forever
+    pub end_date: Option<chrono::NaiveDate>,
+
+    // Names of the checklist items, in order
+    #[validate(custom = "crate::validators::validate_checklist")]
+    pub checklist: Option<Vec<String>>,
+
+    // Count by default
+    pub checklist_mode: Option<HabChecklistModeEnum>,
+
+    // Computed from other habits instead of being logged
+    #[validate]
+    pub derivation: Option<HabitDerivationSchema>,
 }
 
 // Requests schemas
@@ -102,6 +163,19 @@ pub struct HabitUpdateSchema {
 
     #[diesel(column_name = "hab_location")]
     pub location: Option<String>,
+
+    #[diesel(column_name = "hab_goal_direction")]
+    pub goal_direction: Option<HabGoalDirectionEnum>,
+
+    #[validate(custom = "crate::validators::validate_bigdecimal")]
+    #[diesel(column_name = "hab_goal_tolerance")]
+    pub goal_tolerance: Option<BigDecimal>,
+
+    #[diesel(column_name = "hab_aggregation")]
+    pub aggregation: Option<HabAggregationEnum>,
+
+    #[diesel(column_name = "hab_checklist_mode")]
+    pub checklist_mode: Option<HabChecklistModeEnum>,
 }
 
 // Responses
@@ -112,6 +186,14 @@ pub struct HabitCreateResponse {
     pub id: Uuid,
 }
 
+#[derive(Debug, Serialize)]
+pub struct HabitUpdateResponse {
+    pub message: String,
+
+    // Only present when the frequency changed
+    pub in_flight_period: Option<InFlightPeriod>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct HabitMultipleQueryResponse {
     pub message: String,
@@ -139,3 +221,183 @@ pub struct HabitAndDataSingleQueryResponse {
 
     pub habit: HabitWithData,
 }
+
+// Checklist query params
+#[derive(Debug, Deserialize)]
+pub struct HabitChecklistParams {
+    // Current date by default
+    pub date: Option<chrono::NaiveDate>,
+}
+
+// Checklist embedded models
+#[derive(Debug, Serialize)]
+pub struct HabitChecklistItemState {
+    pub hab_chk_id: Uuid,
+
+    pub hab_chk_name: String,
+
+    pub hab_chk_position: i32,
+
+    pub is_checked: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitChecklist {
+    pub hab_id: Uuid,
+
+    pub date: chrono::NaiveDate,
+
+    pub mode: HabChecklistModeEnum,
+
+    pub items: Vec<HabitChecklistItemState>,
+
+    pub checked_count: i64,
+
+    // Daily amount of the habit the checked items make up
+    pub amount: BigDecimal,
+}
+
+// Checklist requests schemas
+#[derive(Debug, Deserialize, Validate)]
+pub struct HabitChecklistItemSchema {
+    // Existing item being kept, new one otherwise
+    pub id: Option<Uuid>,
+
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct HabitChecklistUpdateSchema {
+    // Replaces the items of the checklist and their order, missing ones are deleted
+    #[validate]
+    pub items: Vec<HabitChecklistItemSchema>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct HabitChecklistChecksSchema {
+    #[validate(custom = "crate::validators::validate_habdata_collected_at")]
+    pub date: Option<chrono::NaiveDate>,
+
+    // Items checked on the day, the others are unchecked
+    pub checked: Vec<Uuid>,
+}
+
+// Checklist responses
+#[derive(Debug, Serialize)]
+pub struct HabitChecklistQueryResponse {
+    pub message: String,
+
+    pub checklist: HabitChecklist,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitChecklistChecksResponse {
+    pub message: String,
+
+    pub checklist: HabitChecklist,
+
+    // Record of the day derived from the checklist, none when nothing is checked
+    pub data: Option<HabitDataCollected>,
+}
+
+// Dependency embedded models
+#[derive(Debug, Serialize)]
+pub struct HabitChainLink {
+    pub trigger_id: Uuid,
+
+    pub dependent_id: Uuid,
+
+    // Days the trigger was logged
+    pub trigger_days: i64,
+
+    // Days both habits were logged
+    pub together_days: i64,
+
+    pub completion_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitChain {
+    pub hab_id: Uuid,
+
+    // From the first trigger of the chain down to the habit
+    pub habits: Vec<Uuid>,
+
+    // Habits prompted once the habit is done
+    pub dependents: Vec<Uuid>,
+
+    pub start_date: chrono::NaiveDate,
+
+    pub end_date: chrono::NaiveDate,
+
+    pub links: Vec<HabitChainLink>,
+
+    // Days the first trigger was logged
+    pub trigger_days: i64,
+
+    // Days every habit of the chain was logged
+    pub completed_days: i64,
+
+    pub completion_rate: Option<f64>,
+}
+
+// Dependency requests schemas
+#[derive(Debug, Deserialize)]
+pub struct HabitTriggerSchema {
+    // Habit done right before, none removes the current trigger
+    pub trigger_id: Option<Uuid>,
+}
+
+// Dependency responses
+#[derive(Debug, Serialize)]
+pub struct HabitTriggerResponse {
+    pub message: String,
+
+    pub dependency: Option<HabitDependency>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitChainQueryResponse {
+    pub message: String,
+
+    pub chain: HabitChain,
+}
+
+// Derivation embedded models
+#[derive(Debug, Serialize)]
+pub struct HabitDerivationWithSources {
+    pub derivation: HabitDerivation,
+
+    pub sources: Vec<Uuid>,
+}
+
+// Derivation requests schemas
+#[derive(Debug, Deserialize, Validate, Clone)]
+pub struct HabitDerivationSchema {
+    pub kind: HabDerivationEnum,
+
+    // Logged habits of the user the value is computed from
+    #[validate(length(min = 1))]
+    pub sources: Vec<Uuid>,
+
+    // Sources to be done on a day, quorum derivations only
+    #[validate(range(min = 1))]
+    pub quorum: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct HabitDerivationUpdateSchema {
+    // Replaces the definition and recomputes the habit's data, none turns it back into a logged
+    // habit keeping its data
+    #[validate]
+    pub derivation: Option<HabitDerivationSchema>,
+}
+
+// Derivation responses
+#[derive(Debug, Serialize)]
+pub struct HabitDerivationQueryResponse {
+    pub message: String,
+
+    pub derivation: Option<HabitDerivationWithSources>,
+}
@@ -0,0 +1,34 @@
+use crate::{jobs::JobPlan, models::database::JobRun};
+use serde_derive::{Deserialize, Serialize};
+
+// Query params
+#[derive(Debug, Deserialize)]
+pub struct JobRunParams {
+    pub job_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobTriggerParams {
+    pub as_of: Option<chrono::NaiveDate>,
+    pub dry_run: Option<bool>,
+}
+
+// Response schemas
+#[derive(Debug, Serialize)]
+pub struct JobRunMultipleQueryResponse {
+    pub message: String,
+
+    pub runs: Vec<JobRun>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobTriggerResponse {
+    pub message: String,
+
+    pub dry_run: bool,
+
+    // Only present when the job was actually run
+    pub run: Option<JobRun>,
+
+    pub plan: JobPlan,
+}
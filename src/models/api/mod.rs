@@ -1,7 +1,16 @@
 pub mod category_api_models;
+pub mod challenge_api_models;
+pub mod dashboard_api_models;
 pub mod data_api_models;
+pub mod digest_api_models;
 pub mod events_api_models;
 pub mod habit_api_models;
+pub mod jobs_api_models;
+pub mod period_api_models;
+pub mod report_api_models;
+pub mod routine_api_models;
+pub mod share_api_models;
+pub mod stats_api_models;
 
 use serde_derive::{Deserialize, Serialize};
 
@@ -21,6 +30,10 @@ pub struct RangeParams {
     pub data_page: Option<i64>,
     pub data_per_page: Option<i64>,
     pub events_limit: Option<i64>,
+    pub periods_page: Option<i64>,
+    pub periods_per_page: Option<i64>,
+    pub jobs_page: Option<i64>,
+    pub jobs_per_page: Option<i64>,
 }
 
 // Authentication data matcher
@@ -28,6 +41,9 @@ pub struct RangeParams {
 pub enum AuthRole {
     User,
     Guest,
+
+    // Requests carrying the administration token, see with_authenticator
+    Admin,
 }
 
 #[derive(Debug, Deserialize)]
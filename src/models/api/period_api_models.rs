@@ -0,0 +1,33 @@
+use crate::models::database::HabitPeriod;
+use bigdecimal::BigDecimal;
+use diesel::{
+    sql_types::{BigInt, Numeric},
+    QueryableByName,
+};
+use serde_derive::Serialize;
+use uuid::Uuid;
+
+// Embedded models, amounts come ordered by period start date
+#[derive(Debug, QueryableByName)]
+pub struct PeriodAmount {
+    #[diesel(sql_type = Numeric)]
+    pub amount: BigDecimal,
+}
+
+// Consecutive closed periods meeting the goal, up to the most recent one
+#[derive(Debug, QueryableByName)]
+pub struct HabitStreak {
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    pub hab_id: Uuid,
+
+    #[diesel(sql_type = BigInt)]
+    pub streak: i64,
+}
+
+// Response schemas
+#[derive(Debug, Serialize)]
+pub struct HabitPeriodMultipleQueryResponse {
+    pub message: String,
+
+    pub periods: Vec<HabitPeriod>,
+}
@@ -0,0 +1,77 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Query params
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewPeriod {
+    Week,
+    Month,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Json,
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewParams {
+    pub period: Option<ReviewPeriod>,
+    pub date: Option<NaiveDate>,
+    pub format: Option<ReportFormat>,
+}
+
+// Embedded models
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewStreakChange {
+    Started,
+    Broken,
+    Kept,
+    None,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitReview {
+    pub hab_id: Uuid,
+    pub hab_name: String,
+    pub hab_units: String,
+
+    // Amount collected within each window, aggregated the way the habit aggregates its periods
+    pub current_total: BigDecimal,
+    pub previous_total: BigDecimal,
+    pub total_delta: BigDecimal,
+
+    // Share of the periods ended within each window that met the goal
+    pub current_completion: Option<f64>,
+    pub previous_completion: Option<f64>,
+    pub completion_delta: Option<f64>,
+
+    pub streak: ReviewStreakChange,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Review {
+    pub period: ReviewPeriod,
+    pub current_start_date: NaiveDate,
+    pub current_end_date: NaiveDate,
+    pub previous_start_date: NaiveDate,
+    pub previous_end_date: NaiveDate,
+    pub habits: Vec<HabitReview>,
+
+    // Habits with the highest and lowest completion within the current window
+    pub most_consistent: Option<Uuid>,
+    pub least_consistent: Option<Uuid>,
+}
+
+// Response schemas
+#[derive(Debug, Serialize)]
+pub struct ReviewQueryResponse {
+    pub message: String,
+    pub review: Review,
+}
@@ -0,0 +1,140 @@
+use crate::models::database::{HabitDataCollected, Routine};
+use serde_derive::{Deserialize, Serialize};
+
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use uuid::Uuid;
+use validator::Validate;
+
+// Query params
+#[derive(Debug, Deserialize)]
+pub struct RoutineProgressParams {
+    // Current date by default
+    pub date: Option<NaiveDate>,
+}
+
+// Embedded models
+#[derive(Debug, Serialize)]
+pub struct RoutineWithHabits {
+    pub routine: Routine,
+
+    // Habits of the routine, in order
+    pub habits: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutineHabitProgress {
+    pub hab_id: Uuid,
+
+    pub hab_name: String,
+
+    pub amount: BigDecimal,
+
+    // Logged on the day
+    pub is_done: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutineProgress {
+    pub rou_id: Uuid,
+
+    pub date: NaiveDate,
+
+    // Habits active on the day, in order
+    pub habits: Vec<RoutineHabitProgress>,
+
+    pub done_count: i64,
+
+    pub completion_rate: Option<f64>,
+
+    pub is_complete: bool,
+
+    // Consecutive complete days, the day itself only counts once complete
+    pub streak: i64,
+
+    pub longest_streak: i64,
+}
+
+// Requests schemas
+#[derive(Debug, Deserialize, Validate)]
+pub struct RoutineCreateSchema {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+
+    #[validate(length(min = 1, max = 255))]
+    pub description: String,
+
+    // Habits of the routine, in order
+    #[validate(length(min = 1))]
+    pub habits: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RoutineUpdateSchema {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+
+    #[validate(length(min = 1, max = 255))]
+    pub description: Option<String>,
+
+    // Replaces the habits of the routine and their order
+    #[validate(length(min = 1))]
+    pub habits: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RoutineHabitAmount {
+    pub habit_id: Uuid,
+
+    #[validate(custom = "crate::validators::validate_bigdecimal")]
+    pub amount: BigDecimal,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RoutineCompleteSchema {
+    #[validate(custom = "crate::validators::validate_habdata_collected_at")]
+    pub collected_at: Option<NaiveDate>,
+
+    #[validate(range(min = 1, max = 5))]
+    pub mood: Option<i16>,
+
+    // Habits without an amount here are logged once
+    #[validate]
+    pub amounts: Option<Vec<RoutineHabitAmount>>,
+}
+
+// Responses
+#[derive(Debug, Serialize)]
+pub struct RoutineCreateResponse {
+    pub message: String,
+
+    pub id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutineSingleQueryResponse {
+    pub message: String,
+
+    pub routine: RoutineWithHabits,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutineMultipleQueryResponse {
+    pub message: String,
+
+    pub routines: Vec<RoutineWithHabits>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutineCompleteResponse {
+    pub message: String,
+
+    pub data: Vec<HabitDataCollected>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutineProgressQueryResponse {
+    pub message: String,
+
+    pub progress: RoutineProgress,
+}
@@ -0,0 +1,35 @@
+use crate::models::{api::events_api_models::CalendarGrouping, database::HabitShare};
+use serde_derive::{Deserialize, Serialize};
+
+// Query params
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartKind {
+    Heatmap,
+    Bar,
+    Line,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChartParams {
+    pub kind: Option<ChartKind>,
+    pub group_by: Option<CalendarGrouping>,
+
+    // Days up to the end date, such as 30d, 12w, 6m or 1y, instead of a start date
+    pub range: Option<String>,
+}
+
+// Response schemas
+#[derive(Debug, Serialize)]
+pub struct HabitShareCreateResponse {
+    pub message: String,
+
+    pub share: HabitShare,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitShareMultipleQueryResponse {
+    pub message: String,
+
+    pub shares: Vec<HabitShare>,
+}
@@ -0,0 +1,211 @@
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, Weekday};
+use diesel::{
+    sql_types::{BigInt, Double, Integer, Nullable, Numeric},
+    QueryableByName,
+};
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Query params
+#[derive(Debug, Deserialize)]
+pub struct CorrelationParams {
+    // Minimum number of days both series must have data on
+    pub min_overlap: Option<i64>,
+    pub include_mood: Option<bool>,
+}
+
+// Embedded models
+#[derive(Debug, QueryableByName)]
+pub struct HabitDataSummary {
+    #[diesel(sql_type = BigInt)]
+    pub days_logged: i64,
+
+    #[diesel(sql_type = Numeric)]
+    pub total_amount: BigDecimal,
+
+    #[diesel(sql_type = Nullable<Numeric>)]
+    pub mean_amount: Option<BigDecimal>,
+
+    #[diesel(sql_type = Nullable<Numeric>)]
+    pub median_amount: Option<BigDecimal>,
+
+    #[diesel(sql_type = Nullable<Numeric>)]
+    pub min_amount: Option<BigDecimal>,
+
+    #[diesel(sql_type = Nullable<Numeric>)]
+    pub max_amount: Option<BigDecimal>,
+
+    // ISO day of week (1 is Monday)
+    #[diesel(sql_type = Nullable<Integer>)]
+    pub best_weekday: Option<i32>,
+
+    #[diesel(sql_type = Nullable<Double>)]
+    pub trend_slope: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitStats {
+    pub hab_id: Uuid,
+
+    pub start_date: NaiveDate,
+
+    pub end_date: NaiveDate,
+
+    // Periods already over within the range, and how many of them met the goal
+    pub periods_count: i64,
+
+    pub periods_met: i64,
+
+    pub completion_rate: Option<f64>,
+
+    // Amount statistics are computed over the days with data
+    pub days_logged: i64,
+
+    pub total_amount: BigDecimal,
+
+    pub mean_amount: Option<BigDecimal>,
+
+    pub median_amount: Option<BigDecimal>,
+
+    pub min_amount: Option<BigDecimal>,
+
+    pub max_amount: Option<BigDecimal>,
+
+    // Weekday with the highest total amount
+    pub best_weekday: Option<Weekday>,
+
+    // Daily amount change over the range, days without data counting as zero
+    pub trend_slope: Option<f64>,
+}
+
+// Completion summary of a habit with an end date (challenge) over its whole run
+#[derive(Debug, Serialize)]
+pub struct HabitSummary {
+    pub hab_id: Uuid,
+
+    pub hab_name: String,
+
+    pub start_date: NaiveDate,
+
+    pub end_date: NaiveDate,
+
+    // Days the challenge lasts
+    pub days: i64,
+
+    // Whether the end date is over, the summary is partial otherwise
+    pub is_finished: bool,
+
+    pub stats: HabitStats,
+}
+
+// Pearson correlation of the daily values of two habits, days without data counting as zero.
+// Sample size counts the days both habits existed, overlap the days both have data
+#[derive(Debug, Serialize, QueryableByName)]
+pub struct HabitCorrelation {
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    pub hab_id_a: Uuid,
+
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    pub hab_id_b: Uuid,
+
+    // Null when one of the series doesn't vary
+    #[diesel(sql_type = Nullable<Double>)]
+    pub correlation: Option<f64>,
+
+    #[diesel(sql_type = BigInt)]
+    pub sample_size: i64,
+
+    #[diesel(sql_type = BigInt)]
+    pub overlap: i64,
+}
+
+// Correlation of a habit's daily values with the average mood of the days it was recorded on
+#[derive(Debug, Serialize, QueryableByName)]
+pub struct MoodCorrelation {
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    pub hab_id: Uuid,
+
+    #[diesel(sql_type = Nullable<Double>)]
+    pub correlation: Option<f64>,
+
+    #[diesel(sql_type = BigInt)]
+    pub sample_size: i64,
+
+    #[diesel(sql_type = BigInt)]
+    pub overlap: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CorrelationAnalysis {
+    pub start_date: NaiveDate,
+
+    pub end_date: NaiveDate,
+
+    // Strongest correlations first
+    pub habits: Vec<HabitCorrelation>,
+
+    // Only when mood was asked for
+    pub mood: Option<Vec<MoodCorrelation>>,
+}
+
+// Expected outcome of a habit's current period
+#[derive(Debug, Serialize)]
+pub struct PeriodForecast {
+    pub hab_id: Uuid,
+
+    pub period_start_date: NaiveDate,
+
+    pub period_end_date: NaiveDate,
+
+    pub next_closure_date: NaiveDate,
+
+    pub goal: BigDecimal,
+
+    // Amount collected so far within the period
+    pub amount: BigDecimal,
+
+    // Amount still needed to reach the goal, for limits what can still be collected without
+    // going over it
+    pub remaining_amount: BigDecimal,
+
+    // Days left to collect the remaining amount, today included until something is logged
+    pub remaining_days: i64,
+
+    // Only for summed habits
+    pub needed_per_day: f64,
+
+    pub expected_amount: f64,
+
+    // Chance of meeting the goal, null without any past nor current data to go on
+    pub probability: Option<f64>,
+}
+
+// Response schemas
+#[derive(Debug, Serialize)]
+pub struct HabitStatsQueryResponse {
+    pub message: String,
+
+    pub stats: HabitStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitCorrelationsQueryResponse {
+    pub message: String,
+
+    pub analysis: CorrelationAnalysis,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitSummaryQueryResponse {
+    pub message: String,
+
+    pub summary: HabitSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HabitProgressQueryResponse {
+    pub message: String,
+
+    pub progress: PeriodForecast,
+}
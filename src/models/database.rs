@@ -4,7 +4,7 @@ use diesel::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(diesel_derive_enum::DbEnum, Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(diesel_derive_enum::DbEnum, Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 #[ExistingTypePath = "crate::schema::sql_types::HabFreqTypeEnum"]
 pub enum HabFreqTypeEnum {
     daily,
@@ -15,6 +15,46 @@ pub enum HabFreqTypeEnum {
     monthly2,
 }
 
+#[derive(diesel_derive_enum::DbEnum, Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::HabGoalDirectionEnum"]
+pub enum HabGoalDirectionEnum {
+    at_least,
+    at_most,
+    exact,
+}
+
+#[derive(diesel_derive_enum::DbEnum, Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::HabAggregationEnum"]
+pub enum HabAggregationEnum {
+    sum,
+    last,
+    average,
+    min,
+    max,
+}
+
+#[derive(diesel_derive_enum::DbEnum, Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::HabChecklistModeEnum"]
+pub enum HabChecklistModeEnum {
+    count,
+    fraction,
+}
+
+#[derive(diesel_derive_enum::DbEnum, Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::HabDerivationEnum"]
+pub enum HabDerivationEnum {
+    sum,
+    quorum,
+}
+
+#[derive(diesel_derive_enum::DbEnum, Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::ChlParPrivacyEnum"]
+pub enum ChlParPrivacyEnum {
+    public,
+    anonymous,
+    hidden,
+}
+
 #[derive(
     Debug, Deserialize, Queryable, Selectable, Insertable, Serialize, AsChangeset, Identifiable,
 )]
@@ -73,6 +113,23 @@ pub struct Habit {
     pub cat_id: Uuid,
 
     pub hab_location: Option<String>,
+
+    pub hab_goal_direction: HabGoalDirectionEnum,
+
+    // Accepted distance from the goal for exact targets
+    pub hab_goal_tolerance: BigDecimal,
+
+    // How the records of a period make up its amount, Y/N habits always count them
+    pub hab_aggregation: HabAggregationEnum,
+
+    // Scheduled start, habits start when created otherwise
+    pub hab_start_date: Option<chrono::NaiveDate>,
+
+    // Last day of time-boxed habits (challenges)
+    pub hab_end_date: Option<chrono::NaiveDate>,
+
+    // Daily amount made of the checklist items checked, or their share
+    pub hab_checklist_mode: HabChecklistModeEnum,
 }
 
 #[derive(
@@ -99,4 +156,367 @@ pub struct HabitDataCollected {
     pub hab_dat_collected_at: NaiveDate,
 
     pub hab_id: Uuid,
+
+    pub hab_dat_mood: Option<i16>,
+
+    pub hab_dat_created_at: NaiveDateTime,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    AsChangeset,
+    Identifiable,
+    Associations,
+    Clone,
+)]
+#[diesel(belongs_to(Habit, foreign_key = hab_id))]
+#[diesel(primary_key(hab_ver_id))]
+#[diesel(table_name=crate::schema::habit_goal_version)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HabitGoalVersion {
+    pub hab_ver_id: Uuid,
+
+    // In effect until the next version's valid from date
+    pub hab_ver_valid_from: NaiveDate,
+
+    pub hab_ver_goal: BigDecimal,
+
+    pub hab_ver_goal_direction: HabGoalDirectionEnum,
+
+    pub hab_ver_goal_tolerance: BigDecimal,
+
+    pub hab_ver_freq_type: HabFreqTypeEnum,
+
+    pub hab_ver_units: String,
+
+    pub hab_id: Uuid,
+
+    pub hab_ver_aggregation: HabAggregationEnum,
+
+    pub hab_ver_is_yn: bool,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    AsChangeset,
+    Identifiable,
+    Associations,
+    Clone,
+)]
+#[diesel(belongs_to(Habit, foreign_key = hab_id))]
+#[diesel(primary_key(hab_per_id))]
+#[diesel(table_name=crate::schema::habit_period)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HabitPeriod {
+    pub hab_per_id: Uuid,
+
+    pub hab_per_start_date: NaiveDate,
+
+    pub hab_per_end_date: NaiveDate,
+
+    pub hab_per_amount: BigDecimal,
+
+    pub hab_per_goal: BigDecimal,
+
+    pub hab_per_is_met: bool,
+
+    pub hab_per_closed_at: chrono::NaiveDateTime,
+
+    pub hab_id: Uuid,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    Identifiable,
+    Associations,
+    Clone,
+)]
+#[diesel(belongs_to(Habit, foreign_key = hab_id))]
+#[diesel(primary_key(hab_sha_token))]
+#[diesel(table_name=crate::schema::habit_share)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HabitShare {
+    pub hab_sha_token: String,
+
+    pub hab_sha_created_at: chrono::NaiveDateTime,
+
+    pub hab_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, Queryable, Selectable, Insertable, Serialize, Identifiable, Clone)]
+#[diesel(primary_key(job_run_id))]
+#[diesel(table_name=crate::schema::job_run)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct JobRun {
+    pub job_run_id: Uuid,
+
+    pub job_run_name: String,
+
+    pub job_run_tick: chrono::NaiveDateTime,
+
+    pub job_run_replica: String,
+
+    pub job_run_started_at: chrono::NaiveDateTime,
+
+    pub job_run_finished_at: Option<chrono::NaiveDateTime>,
+
+    pub job_run_habits_processed: i32,
+
+    pub job_run_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Queryable, Selectable, Insertable, Serialize, Identifiable, Clone)]
+#[diesel(primary_key(usr_id))]
+#[diesel(table_name=crate::schema::user_digest_preference)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UserDigestPreference {
+    pub usr_id: String,
+
+    pub usr_dig_enabled: bool,
+
+    pub usr_dig_weekday: i16,
+
+    pub usr_dig_last_sent_on: Option<NaiveDate>,
+
+    pub usr_dig_updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Queryable, Selectable, Insertable, Serialize, Identifiable, Clone)]
+#[diesel(primary_key(chl_id))]
+#[diesel(table_name=crate::schema::challenge)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Challenge {
+    pub chl_id: Uuid,
+
+    pub chl_name: String,
+
+    pub chl_description: String,
+
+    pub chl_created_at: chrono::NaiveDateTime,
+
+    // Goal definition given to the habit of every participant
+    pub chl_is_yn: bool,
+
+    pub chl_units: String,
+
+    pub chl_goal: BigDecimal,
+
+    pub chl_goal_direction: HabGoalDirectionEnum,
+
+    pub chl_freq_type: HabFreqTypeEnum,
+
+    pub chl_aggregation: HabAggregationEnum,
+
+    pub chl_start_date: NaiveDate,
+
+    pub chl_end_date: NaiveDate,
+
+    // Anyone can join public challenges, others need an invitation
+    pub chl_is_public: bool,
+
+    pub usr_id: String,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    Identifiable,
+    Associations,
+    Clone,
+)]
+#[diesel(belongs_to(Challenge, foreign_key = chl_id))]
+#[diesel(primary_key(chl_par_id))]
+#[diesel(table_name=crate::schema::challenge_participant)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ChallengeParticipant {
+    pub chl_par_id: Uuid,
+
+    pub chl_par_joined_at: chrono::NaiveDateTime,
+
+    // What other participants get to see
+    pub chl_par_privacy: ChlParPrivacyEnum,
+
+    pub chl_id: Uuid,
+
+    pub usr_id: String,
+
+    // Habit the participant follows the challenge with
+    pub hab_id: Uuid,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    Identifiable,
+    Associations,
+    Clone,
+)]
+#[diesel(belongs_to(Challenge, foreign_key = chl_id))]
+#[diesel(primary_key(chl_inv_id))]
+#[diesel(table_name=crate::schema::challenge_invitation)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ChallengeInvitation {
+    pub chl_inv_id: Uuid,
+
+    pub chl_inv_created_at: chrono::NaiveDateTime,
+
+    pub chl_id: Uuid,
+
+    pub usr_id: String,
+}
+
+#[derive(Debug, Deserialize, Queryable, Selectable, Insertable, Serialize, Identifiable, Clone)]
+#[diesel(primary_key(rou_id))]
+#[diesel(table_name=crate::schema::routine)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Routine {
+    pub rou_id: Uuid,
+
+    pub rou_name: String,
+
+    pub rou_description: String,
+
+    pub rou_created_at: chrono::NaiveDateTime,
+
+    pub usr_id: String,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    Identifiable,
+    Associations,
+    Clone,
+)]
+#[diesel(belongs_to(Routine, foreign_key = rou_id))]
+#[diesel(primary_key(rou_id, hab_id))]
+#[diesel(table_name=crate::schema::routine_habit)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RoutineHabit {
+    pub rou_id: Uuid,
+
+    pub hab_id: Uuid,
+
+    // Order of the habit within the routine, starting at 0
+    pub rou_hab_position: i32,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    Identifiable,
+    Associations,
+    Clone,
+)]
+#[diesel(belongs_to(Habit, foreign_key = hab_id))]
+#[diesel(primary_key(hab_chk_id))]
+#[diesel(table_name=crate::schema::habit_checklist_item)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HabitChecklistItem {
+    pub hab_chk_id: Uuid,
+
+    pub hab_chk_name: String,
+
+    // Order of the item within the checklist, starting at 0
+    pub hab_chk_position: i32,
+
+    pub hab_chk_created_at: chrono::NaiveDateTime,
+
+    pub hab_id: Uuid,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Serialize,
+    Identifiable,
+    Associations,
+    Clone,
+)]
+#[diesel(belongs_to(HabitChecklistItem, foreign_key = hab_chk_id))]
+#[diesel(primary_key(hab_chk_id, hab_chk_chk_date))]
+#[diesel(table_name=crate::schema::habit_checklist_check)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HabitChecklistCheck {
+    pub hab_chk_id: Uuid,
+
+    pub hab_chk_chk_date: NaiveDate,
+
+    pub hab_chk_chk_checked_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Queryable, Selectable, Insertable, Serialize, Identifiable, Clone)]
+#[diesel(primary_key(hab_id))]
+#[diesel(table_name=crate::schema::habit_dependency)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HabitDependency {
+    // Dependent habit, prompted once its trigger is done
+    pub hab_id: Uuid,
+
+    pub hab_dep_trigger_id: Uuid,
+
+    pub hab_dep_created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Queryable, Selectable, Insertable, Serialize, Identifiable, Clone)]
+#[diesel(primary_key(hab_id))]
+#[diesel(table_name=crate::schema::habit_derivation)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HabitDerivation {
+    // Derived habit, its data is computed from its sources
+    pub hab_id: Uuid,
+
+    pub hab_der_kind: HabDerivationEnum,
+
+    // Sources to be done on a day for quorum derivations
+    pub hab_der_quorum: Option<i32>,
+
+    pub hab_der_created_at: chrono::NaiveDateTime,
+}
+
+#[derive(
+    Debug, Deserialize, Queryable, Selectable, Insertable, Serialize, Identifiable, Associations,
+)]
+#[diesel(belongs_to(HabitDerivation, foreign_key = hab_id))]
+#[diesel(primary_key(hab_id, hab_src_id))]
+#[diesel(table_name=crate::schema::habit_derivation_source)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HabitDerivationSource {
+    pub hab_id: Uuid,
+
+    pub hab_src_id: Uuid,
 }
@@ -0,0 +1,515 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::{
+            challenge_api_models::*, events_api_models::HabitDailyAmount,
+            habit_api_models::HabitCreateSchema,
+        },
+        database::{
+            Challenge, ChallengeInvitation, ChallengeParticipant, ChlParPrivacyEnum,
+            HabAggregationEnum, HabGoalDirectionEnum, Habit,
+        },
+    },
+    schema::*,
+    utils::{challenges::build_leaderboard, periods::get_goal_version, queries::build_new_habit},
+};
+
+use diesel::prelude::*;
+
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+
+impl DBManager {
+    // Public challenges are open to anyone, others to their owner, participants and invited users
+    pub fn is_challenge_accessible_by_user(
+        &self,
+        user_id: String,
+        challenge_id: Uuid,
+    ) -> Result<bool, Error> {
+        let challenge = self.get_challenge(challenge_id);
+
+        if challenge.is_err() {
+            return Err(challenge.err().unwrap());
+        }
+
+        let challenge = challenge.unwrap();
+
+        if challenge.chl_is_public || challenge.usr_id == user_id {
+            return Ok(true);
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let participants = challenge_participant::table
+            .filter(challenge_participant::chl_id.eq(challenge_id))
+            .filter(challenge_participant::usr_id.eq(&user_id))
+            .count()
+            .get_result::<i64>(&mut conn);
+
+        if participants.is_err() {
+            return Err(Error::QueryError(participants.err().unwrap()));
+        }
+
+        let invitations = challenge_invitation::table
+            .filter(challenge_invitation::chl_id.eq(challenge_id))
+            .filter(challenge_invitation::usr_id.eq(&user_id))
+            .count()
+            .get_result::<i64>(&mut conn);
+
+        if invitations.is_err() {
+            return Err(Error::QueryError(invitations.err().unwrap()));
+        }
+
+        Ok(participants.unwrap() + invitations.unwrap() > 0)
+    }
+
+    // Create a challenge owned by a user
+    pub fn add_challenge(
+        &self,
+        user_id: String,
+        data: ChallengeCreateSchema,
+    ) -> Result<Uuid, Error> {
+        if data.end_date < data.start_date {
+            return Err(Error::BadRequest(
+                "Challenge end date must not be before its start date".to_string(),
+            ));
+        }
+
+        let challenge = Challenge {
+            chl_id: Uuid::new_v4(),
+            chl_name: data.name,
+            chl_description: data.description,
+            chl_created_at: chrono::Local::now().naive_local(),
+            chl_is_yn: data.is_yn,
+            chl_units: data.units,
+            chl_goal: data.goal,
+            chl_goal_direction: data
+                .goal_direction
+                .unwrap_or(HabGoalDirectionEnum::at_least),
+            chl_freq_type: data.frequency_type,
+            chl_aggregation: data.aggregation.unwrap_or(HabAggregationEnum::sum),
+            chl_start_date: data.start_date,
+            chl_end_date: data.end_date,
+            chl_is_public: data.is_public.unwrap_or(false),
+            usr_id: user_id,
+        };
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let result = diesel::insert_into(challenge::table)
+            .values(&challenge)
+            .execute(&mut conn.unwrap());
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        Ok(challenge.chl_id)
+    }
+
+    // Get a challenge, NotFound when it doesn't exist
+    pub fn get_challenge(&self, id: Uuid) -> Result<Challenge, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = challenge::table
+            .select(Challenge::as_select())
+            .find(id)
+            .first::<Challenge>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Get every participant of a challenge
+    pub fn get_challenge_participants(&self, id: Uuid) -> Result<Vec<ChallengeParticipant>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = challenge_participant::table
+            .select(ChallengeParticipant::as_select())
+            .filter(challenge_participant::chl_id.eq(id))
+            .order_by(challenge_participant::chl_par_joined_at.asc())
+            .load::<ChallengeParticipant>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Get the participation of a user in a challenge, if any
+    pub fn get_challenge_participant(
+        &self,
+        id: Uuid,
+        user_id: String,
+    ) -> Result<Option<ChallengeParticipant>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = challenge_participant::table
+            .select(ChallengeParticipant::as_select())
+            .filter(challenge_participant::chl_id.eq(id))
+            .filter(challenge_participant::usr_id.eq(user_id))
+            .first::<ChallengeParticipant>(&mut conn.unwrap())
+            .optional();
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Habits joined to a challenge follow its goal, participants are ranked by it
+    pub fn is_habit_in_challenge(&self, hab_id: Uuid) -> Result<bool, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = diesel::select(diesel::dsl::exists(
+            challenge_participant::table.filter(challenge_participant::hab_id.eq(hab_id)),
+        ))
+        .get_result::<bool>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Invite a user, only the owner of the challenge can
+    pub fn add_challenge_invitation(
+        &self,
+        id: Uuid,
+        user_id: String,
+        data: ChallengeInvitationCreateSchema,
+    ) -> Result<ChallengeInvitation, Error> {
+        let challenge = self.get_challenge(id);
+
+        if challenge.is_err() {
+            return Err(challenge.err().unwrap());
+        }
+
+        if challenge.unwrap().usr_id != user_id {
+            return Err(Error::AuthorizationError(
+                "User is not the owner of the challenge".to_string(),
+            ));
+        }
+
+        let participant = self.get_challenge_participant(id, data.user_id.clone());
+
+        if participant.is_err() {
+            return Err(participant.err().unwrap());
+        }
+
+        if participant.unwrap().is_some() {
+            return Err(Error::BadRequest(
+                "User already takes part in the challenge".to_string(),
+            ));
+        }
+
+        let invitation = ChallengeInvitation {
+            chl_inv_id: Uuid::new_v4(),
+            chl_inv_created_at: chrono::Local::now().naive_local(),
+            chl_id: id,
+            usr_id: data.user_id,
+        };
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let result = diesel::insert_into(challenge_invitation::table)
+            .values(&invitation)
+            .on_conflict_do_nothing()
+            .execute(&mut conn.unwrap());
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        if result.unwrap() == 0 {
+            return Err(Error::BadRequest(
+                "User is already invited to the challenge".to_string(),
+            ));
+        }
+
+        Ok(invitation)
+    }
+
+    // Join a challenge, which creates the habit the user follows it with. The habit runs over the
+    // challenge dates with its goal, and any pending invitation is used up
+    pub fn join_challenge(
+        &self,
+        id: Uuid,
+        user_id: String,
+        data: ChallengeJoinSchema,
+    ) -> Result<ChallengeParticipant, Error> {
+        let current_datetime = chrono::Local::now().naive_local();
+        let current_date = current_datetime.date();
+
+        let challenge = self.get_challenge(id);
+
+        if challenge.is_err() {
+            return Err(challenge.err().unwrap());
+        }
+
+        let challenge = challenge.unwrap();
+
+        if challenge.chl_end_date < current_date {
+            return Err(Error::BadRequest("Challenge is already over".to_string()));
+        }
+
+        let participant = self.get_challenge_participant(id, user_id.clone());
+
+        if participant.is_err() {
+            return Err(participant.err().unwrap());
+        }
+
+        if participant.unwrap().is_some() {
+            return Err(Error::BadRequest(
+                "User already takes part in the challenge".to_string(),
+            ));
+        }
+
+        let habit = build_new_habit(
+            user_id.clone(),
+            HabitCreateSchema {
+                name: challenge.chl_name.clone(),
+                description: challenge.chl_description.clone(),
+                is_favorite: false,
+                is_yn: challenge.chl_is_yn,
+                color: data.color.unwrap_or("ffffff".to_string()),
+                units: challenge.chl_units.clone(),
+                goal: challenge.chl_goal.clone(),
+                frequency_type: challenge.chl_freq_type,
+                category: data.category,
+                location: None,
+                goal_direction: Some(challenge.chl_goal_direction),
+                goal_tolerance: Some(BigDecimal::from(0)),
+                aggregation: Some(challenge.chl_aggregation),
+                start_date: Some(challenge.chl_start_date),
+                end_date: Some(challenge.chl_end_date),
+                checklist: None,
+                checklist_mode: None,
+                derivation: None,
+            },
+            current_datetime,
+        );
+
+        if habit.is_err() {
+            return Err(habit.err().unwrap());
+        }
+
+        let habit = habit.unwrap();
+
+        let version = get_goal_version(&habit, current_date);
+
+        let participant = ChallengeParticipant {
+            chl_par_id: Uuid::new_v4(),
+            chl_par_joined_at: current_datetime,
+            chl_par_privacy: data.privacy.unwrap_or(ChlParPrivacyEnum::public),
+            chl_id: id,
+            usr_id: user_id.clone(),
+            hab_id: habit.hab_id,
+        };
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = conn.unwrap().transaction(|conn| {
+            diesel::insert_into(habit::table)
+                .values(&habit)
+                .execute(conn)?;
+
+            diesel::insert_into(habit_goal_version::table)
+                .values(&version)
+                .execute(conn)?;
+
+            diesel::insert_into(challenge_participant::table)
+                .values(&participant)
+                .execute(conn)?;
+
+            diesel::delete(
+                challenge_invitation::table
+                    .filter(challenge_invitation::chl_id.eq(id))
+                    .filter(challenge_invitation::usr_id.eq(&user_id)),
+            )
+            .execute(conn)?;
+
+            Ok::<(), diesel::result::Error>(())
+        });
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(participant)
+    }
+
+    // Leave a challenge, the habit created when joining stays with the user
+    pub fn leave_challenge(&self, id: Uuid, user_id: String) -> Result<Uuid, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = diesel::delete(
+            challenge_participant::table
+                .filter(challenge_participant::chl_id.eq(id))
+                .filter(challenge_participant::usr_id.eq(user_id)),
+        )
+        .execute(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        if search.unwrap() == 0 {
+            return Err(Error::BadRequest(
+                "User doesn't take part in the challenge".to_string(),
+            ));
+        }
+
+        Ok(id)
+    }
+
+    // Change what other participants get to see of a user
+    pub fn update_challenge_privacy(
+        &self,
+        id: Uuid,
+        user_id: String,
+        privacy: ChlParPrivacyEnum,
+    ) -> Result<ChallengeParticipant, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = diesel::update(
+            challenge_participant::table
+                .filter(challenge_participant::chl_id.eq(id))
+                .filter(challenge_participant::usr_id.eq(user_id)),
+        )
+        .set(challenge_participant::chl_par_privacy.eq(privacy))
+        .returning(ChallengeParticipant::as_returning())
+        .get_result::<ChallengeParticipant>(&mut conn.unwrap())
+        .optional();
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        match search.unwrap() {
+            Some(participant) => Ok(participant),
+            None => Err(Error::BadRequest(
+                "User doesn't take part in the challenge".to_string(),
+            )),
+        }
+    }
+
+    // Rank the participants of a challenge as seen by a user
+    pub fn get_challenge_leaderboard(
+        &self,
+        id: Uuid,
+        user_id: String,
+    ) -> Result<ChallengeLeaderboard, Error> {
+        let current_date = chrono::Local::now().naive_local().date();
+
+        let challenge = self.get_challenge(id);
+
+        if challenge.is_err() {
+            return Err(challenge.err().unwrap());
+        }
+
+        let challenge = challenge.unwrap();
+
+        let participants = self.get_challenge_participants(id);
+
+        if participants.is_err() {
+            return Err(participants.err().unwrap());
+        }
+
+        let participants = participants.unwrap();
+        let habit_ids: Vec<Uuid> = participants.iter().map(|item| item.hab_id).collect();
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let habits = habit::table
+            .select(Habit::as_select())
+            .filter(habit::hab_id.eq_any(&habit_ids))
+            .load::<Habit>(&mut conn.unwrap());
+
+        if habits.is_err() {
+            return Err(Error::QueryError(habits.err().unwrap()));
+        }
+
+        let habits = self.get_habits_versions(&habits.unwrap());
+
+        if habits.is_err() {
+            return Err(habits.err().unwrap());
+        }
+
+        let daily_amounts: Result<Vec<HabitDailyAmount>, Error> =
+            match challenge.chl_start_date > current_date {
+                true => Ok(Vec::new()),
+                false => self.get_habits_daily_amounts(
+                    &habit_ids,
+                    challenge.chl_start_date,
+                    challenge.chl_end_date.min(current_date),
+                ),
+            };
+
+        if daily_amounts.is_err() {
+            return Err(daily_amounts.err().unwrap());
+        }
+
+        Ok(build_leaderboard(
+            &challenge,
+            &participants,
+            &habits.unwrap(),
+            &daily_amounts.unwrap(),
+            &user_id,
+            current_date,
+        ))
+    }
+}
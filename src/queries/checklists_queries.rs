@@ -0,0 +1,341 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::{data_api_models::HabitDataCreateSchema, habit_api_models::*},
+        database::{Habit, HabitChecklistCheck, HabitChecklistItem, HabitDataCollected},
+    },
+    queries::derivations_queries::refresh_derived_habits_data,
+    schema::*,
+    utils::{
+        checklists::{build_checklist, get_checklist_amount},
+        queries::build_habit_data,
+        MAX_CHECKLIST_ITEMS,
+    },
+};
+
+use diesel::prelude::*;
+
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+impl DBManager {
+    // Get the checklist items of a habit, in order
+    pub fn get_habit_checklist_items(
+        &self,
+        hab_id: Uuid,
+    ) -> Result<Vec<HabitChecklistItem>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_checklist_item::table
+            .select(HabitChecklistItem::as_select())
+            .filter(habit_checklist_item::hab_id.eq(hab_id))
+            .order_by(habit_checklist_item::hab_chk_position.asc())
+            .load::<HabitChecklistItem>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Get which of some habits have a checklist
+    pub fn get_checklist_habit_ids(&self, habit_ids: &[Uuid]) -> Result<Vec<Uuid>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_checklist_item::table
+            .select(habit_checklist_item::hab_id)
+            .distinct()
+            .filter(habit_checklist_item::hab_id.eq_any(habit_ids))
+            .load::<Uuid>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Habits with a checklist are logged by checking their items, not directly
+    pub fn has_habit_checklist(&self, hab_id: Uuid) -> Result<bool, Error> {
+        let checklist_ids = self.get_checklist_habit_ids(&[hab_id]);
+
+        if checklist_ids.is_err() {
+            return Err(checklist_ids.err().unwrap());
+        }
+
+        Ok(!checklist_ids.unwrap().is_empty())
+    }
+
+    // State of the checklist of a habit on a day
+    pub fn get_habit_checklist(
+        &self,
+        habit: &Habit,
+        date: NaiveDate,
+    ) -> Result<HabitChecklist, Error> {
+        let items = self.get_habit_checklist_items(habit.hab_id);
+
+        if items.is_err() {
+            return Err(items.err().unwrap());
+        }
+
+        let items = items.unwrap();
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let checks = HabitChecklistCheck::belonging_to(&items)
+            .select(HabitChecklistCheck::as_select())
+            .filter(habit_checklist_check::hab_chk_chk_date.eq(date))
+            .load::<HabitChecklistCheck>(&mut conn.unwrap());
+
+        if checks.is_err() {
+            return Err(Error::QueryError(checks.err().unwrap()));
+        }
+
+        Ok(build_checklist(habit, &items, &checks.unwrap(), date))
+    }
+
+    // Replace the checklist items of a habit. Kept items keep their checks, removed ones lose
+    // them, amounts already derived for past days stay as they are
+    pub fn update_habit_checklist(
+        &self,
+        hab_id: Uuid,
+        data: HabitChecklistUpdateSchema,
+    ) -> Result<Vec<HabitChecklistItem>, Error> {
+        if data.items.len() > MAX_CHECKLIST_ITEMS {
+            return Err(Error::BadRequest("Too many checklist items".to_string()));
+        }
+
+        let is_derived = self.is_habit_derived(hab_id);
+
+        if is_derived.is_err() {
+            return Err(is_derived.err().unwrap());
+        }
+
+        if is_derived.unwrap() && !data.items.is_empty() {
+            return Err(Error::BadRequest(
+                "Derived habits can't have a checklist".to_string(),
+            ));
+        }
+
+        let items = self.get_habit_checklist_items(hab_id);
+
+        if items.is_err() {
+            return Err(items.err().unwrap());
+        }
+
+        let items = items.unwrap();
+
+        let kept_ids: Vec<Uuid> = data.items.iter().filter_map(|item| item.id).collect();
+
+        let mut unique_ids = kept_ids.clone();
+        unique_ids.sort();
+        unique_ids.dedup();
+
+        if unique_ids.len() != kept_ids.len()
+            || kept_ids
+                .iter()
+                .any(|id| !items.iter().any(|item| item.hab_chk_id == *id))
+        {
+            return Err(Error::BadRequest(
+                "Checklist items must belong to the habit".to_string(),
+            ));
+        }
+
+        let current_datetime = chrono::Local::now().naive_local();
+
+        // Positions follow the order given, new items get their own ids
+        let updated_items: Vec<HabitChecklistItem> = data
+            .items
+            .iter()
+            .enumerate()
+            .map(|(position, item)| {
+                let existing = items
+                    .iter()
+                    .find(|existing| Some(existing.hab_chk_id) == item.id);
+
+                HabitChecklistItem {
+                    hab_chk_id: existing
+                        .map(|existing| existing.hab_chk_id)
+                        .unwrap_or_else(Uuid::new_v4),
+                    hab_chk_name: item.name.trim().to_string(),
+                    hab_chk_position: position as i32,
+                    hab_chk_created_at: existing
+                        .map(|existing| existing.hab_chk_created_at)
+                        .unwrap_or(current_datetime),
+                    hab_id,
+                }
+            })
+            .collect();
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = conn.unwrap().transaction(|conn| {
+            diesel::delete(
+                habit_checklist_item::table
+                    .filter(habit_checklist_item::hab_id.eq(hab_id))
+                    .filter(habit_checklist_item::hab_chk_id.ne_all(&kept_ids)),
+            )
+            .execute(conn)?;
+
+            for item in &updated_items {
+                diesel::insert_into(habit_checklist_item::table)
+                    .values(item)
+                    .on_conflict(habit_checklist_item::hab_chk_id)
+                    .do_update()
+                    .set((
+                        habit_checklist_item::hab_chk_name.eq(&item.hab_chk_name),
+                        habit_checklist_item::hab_chk_position.eq(item.hab_chk_position),
+                    ))
+                    .execute(conn)?;
+            }
+
+            Ok::<(), diesel::result::Error>(())
+        });
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(updated_items)
+    }
+
+    // Set the items checked on a day, the others are unchecked. The checklist owns the data of
+    // the habit on that day, which is replaced by a single record with the derived amount
+    pub fn set_habit_checklist_checks(
+        &self,
+        habit: &Habit,
+        data: HabitChecklistChecksSchema,
+    ) -> Result<(HabitChecklist, Option<HabitDataCollected>), Error> {
+        let date = data
+            .date
+            .unwrap_or_else(|| chrono::Utc::now().naive_utc().date());
+
+        let is_derived = self.is_habit_derived(habit.hab_id);
+
+        if is_derived.is_err() {
+            return Err(is_derived.err().unwrap());
+        }
+
+        if is_derived.unwrap() {
+            return Err(Error::BadRequest(
+                "Derived habits can't be logged directly".to_string(),
+            ));
+        }
+
+        let items = self.get_habit_checklist_items(habit.hab_id);
+
+        if items.is_err() {
+            return Err(items.err().unwrap());
+        }
+
+        let items = items.unwrap();
+
+        if items.is_empty() {
+            return Err(Error::BadRequest("Habit has no checklist".to_string()));
+        }
+
+        let mut checked = data.checked;
+        checked.sort();
+        checked.dedup();
+
+        if checked
+            .iter()
+            .any(|id| !items.iter().any(|item| item.hab_chk_id == *id))
+        {
+            return Err(Error::BadRequest(
+                "Checklist items must belong to the habit".to_string(),
+            ));
+        }
+
+        let current_datetime = chrono::Local::now().naive_local();
+
+        let checks: Vec<HabitChecklistCheck> = checked
+            .iter()
+            .map(|id| HabitChecklistCheck {
+                hab_chk_id: *id,
+                hab_chk_chk_date: date,
+                hab_chk_chk_checked_at: current_datetime,
+            })
+            .collect();
+
+        let amount = get_checklist_amount(
+            habit.hab_checklist_mode,
+            checks.len() as i64,
+            items.len() as i64,
+        );
+
+        // Logged through the same path as any other record, nothing is logged for empty days
+        let habit_data = match amount > BigDecimal::from(0) {
+            true => Some(build_habit_data(HabitDataCreateSchema {
+                amount,
+                collected_at: Some(date),
+                habit_id: habit.hab_id,
+                mood: None,
+            })),
+            false => None,
+        };
+
+        let item_ids: Vec<Uuid> = items.iter().map(|item| item.hab_chk_id).collect();
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = conn.unwrap().transaction(|conn| {
+            diesel::delete(
+                habit_checklist_check::table
+                    .filter(habit_checklist_check::hab_chk_id.eq_any(&item_ids))
+                    .filter(habit_checklist_check::hab_chk_chk_date.eq(date)),
+            )
+            .execute(conn)?;
+
+            diesel::insert_into(habit_checklist_check::table)
+                .values(&checks)
+                .execute(conn)?;
+
+            diesel::delete(
+                habit_data_collected::table
+                    .filter(habit_data_collected::hab_id.eq(habit.hab_id))
+                    .filter(habit_data_collected::hab_dat_collected_at.eq(date)),
+            )
+            .execute(conn)?;
+
+            if let Some(habit_data) = &habit_data {
+                diesel::insert_into(habit_data_collected::table)
+                    .values(habit_data)
+                    .execute(conn)?;
+            }
+
+            refresh_derived_habits_data(conn, &[habit.hab_id], &[date])?;
+
+            Ok::<(), diesel::result::Error>(())
+        });
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok((build_checklist(habit, &items, &checks, date), habit_data))
+    }
+}
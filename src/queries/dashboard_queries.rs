@@ -0,0 +1,173 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::dashboard_api_models::DashboardHabit,
+        database::{Habit, HabitDataCollected},
+    },
+    schema::*,
+    utils::periods::{
+        aggregate_period_amount, get_goal_progress, get_versioned_period_bounds, is_goal_met,
+        is_goal_settled,
+    },
+};
+
+use bigdecimal::ToPrimitive;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+impl DBManager {
+    // Current period progress of every habit of a user due at the given date
+    pub fn get_user_dashboard(
+        &self,
+        user_id: String,
+        current_date: chrono::NaiveDate,
+    ) -> Result<Vec<DashboardHabit>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let habits = habit::table
+            .select(Habit::as_select())
+            .filter(habit::usr_id.eq(user_id))
+            .order_by((habit::hab_is_favorite.desc(), habit::hab_name.asc()))
+            .load::<Habit>(&mut conn);
+
+        if habits.is_err() {
+            return Err(Error::QueryError(habits.err().unwrap()));
+        }
+
+        let habits = habits.unwrap();
+        let versions = self.get_habits_versions(&habits);
+
+        if versions.is_err() {
+            return Err(versions.err().unwrap());
+        }
+
+        // Habits that haven't started yet have no current period. The habit itself holds the
+        // latest goal version, which is the one the current period follows
+        let (habits, bounds): (Vec<Habit>, Vec<(chrono::NaiveDate, chrono::NaiveDate)>) = habits
+            .into_iter()
+            .zip(versions.unwrap())
+            .filter_map(|(habit, versions)| {
+                let bounds = get_versioned_period_bounds(&versions, current_date, current_date);
+
+                bounds
+                    .first()
+                    .map(|(period_start, period_end, _)| (habit, (*period_start, *period_end)))
+            })
+            .unzip();
+
+        if habits.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let min_date = bounds
+            .iter()
+            .map(|(start_date, _)| *start_date)
+            .min()
+            .unwrap_or(current_date);
+
+        // Data of every current period in a single query
+        let habits_data = HabitDataCollected::belonging_to(&habits)
+            .select(HabitDataCollected::as_select())
+            .filter(habit_data_collected::hab_dat_collected_at.ge(min_date))
+            .filter(habit_data_collected::hab_dat_collected_at.le(current_date))
+            .load::<HabitDataCollected>(&mut conn);
+
+        if habits_data.is_err() {
+            return Err(Error::QueryError(habits_data.err().unwrap()));
+        }
+
+        let habits_data = habits_data.unwrap().grouped_by(&habits);
+
+        let habit_ids: Vec<Uuid> = habits.iter().map(|habit| habit.hab_id).collect();
+        let streaks = self.get_habits_streaks(&habit_ids);
+
+        if streaks.is_err() {
+            return Err(streaks.err().unwrap());
+        }
+
+        let streaks = streaks.unwrap();
+
+        // Periods that ended since the closure job last ran aren't recorded yet, they are
+        // evaluated here so streaks and closure dates stay current in between
+        let pending = self.build_closing_periods(
+            &habits,
+            current_date,
+            current_date.and_time(chrono::NaiveTime::MIN),
+        );
+
+        if pending.is_err() {
+            return Err(pending.err().unwrap());
+        }
+
+        let (pending_periods, closures) = pending.unwrap();
+
+        let mut dashboard: Vec<DashboardHabit> = Vec::new();
+
+        for ((habit, data), (period_start, period_end)) in
+            habits.into_iter().zip(habits_data).zip(bounds)
+        {
+            let period_data: Vec<&HabitDataCollected> = data
+                .iter()
+                .filter(|item| item.hab_dat_collected_at >= period_start)
+                .collect();
+
+            let amount = aggregate_period_amount(&habit, &period_data);
+            let goal_met = is_goal_met(&habit, &amount);
+
+            // Limits are fully on track while under them
+            let progress = get_goal_progress(&habit, amount.to_f64().unwrap_or(0.0));
+
+            let mut streak = streaks
+                .iter()
+                .find(|streak| streak.hab_id == habit.hab_id)
+                .map(|streak| streak.streak)
+                .unwrap_or(0);
+
+            // Pending periods come oldest first, a missed one starts the streak over
+            for period in pending_periods
+                .iter()
+                .filter(|period| period.hab_id == habit.hab_id)
+            {
+                streak = match period.hab_per_is_met {
+                    true => streak + 1,
+                    false => 0,
+                };
+            }
+
+            // Current period only joins the streak once it's met for good, limits could still
+            // be exceeded before the period ends
+            if goal_met && is_goal_settled(&habit) {
+                streak += 1;
+            }
+
+            let today_logged = period_data
+                .iter()
+                .any(|item| item.hab_dat_collected_at == current_date);
+
+            dashboard.push(DashboardHabit {
+                period_start_date: period_start,
+                period_end_date: period_end,
+                amount,
+                progress,
+                is_goal_met: goal_met,
+                streak,
+                today_logged,
+                next_closure_date: closures
+                    .iter()
+                    .find(|closure| closure.hab_id == habit.hab_id)
+                    .map(|closure| closure.next_closure_date)
+                    .unwrap_or(habit.hab_next_closure_date),
+                habit,
+            });
+        }
+
+        Ok(dashboard)
+    }
+}
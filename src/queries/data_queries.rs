@@ -6,8 +6,9 @@ use crate::{
         api::habit_api_models::HabitWithData,
         database::{Habit, HabitDataCollected},
     },
+    queries::derivations_queries::refresh_derived_habits_data,
     schema::*,
-    utils::queries::join_habit_with_data,
+    utils::queries::{build_habit_data, join_habit_with_data},
     utils::time::{MAXIMUM_DATE, MINIMUM_DATE},
     utils::{DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT},
 };
@@ -86,16 +87,32 @@ impl DBManager {
         Ok(search.unwrap())
     }
 
+    // Date of the latest record among the given habits, if any
+    pub fn get_last_habits_data_date(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Option<chrono::NaiveDate>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_data_collected::table
+            .select(diesel::dsl::max(habit_data_collected::hab_dat_collected_at))
+            .filter(habit_data_collected::hab_id.eq_any(ids))
+            .first::<Option<chrono::NaiveDate>>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
     // Add a habit data record
     pub fn add_habit_data(&self, data: HabitDataCreateSchema) -> Result<HabitDataCollected, Error> {
-        let habit_data = HabitDataCollected {
-            hab_dat_id: Uuid::new_v4(),
-            hab_dat_amount: data.amount,
-            hab_dat_collected_at: data
-                .collected_at
-                .unwrap_or_else(|| chrono::Utc::now().naive_utc().date()),
-            hab_id: data.habit_id,
-        };
+        let habit_data = build_habit_data(data);
 
         let conn = self.get_write_connection();
 
@@ -103,9 +120,56 @@ impl DBManager {
             return Err(conn.err().unwrap());
         }
 
-        let query = diesel::insert_into(habit_data_collected::table)
-            .values(&habit_data)
-            .get_result(&mut conn.unwrap());
+        let query = conn.unwrap().transaction(|conn| {
+            let habit_data = diesel::insert_into(habit_data_collected::table)
+                .values(&habit_data)
+                .get_result::<HabitDataCollected>(conn)?;
+
+            // Habits derived from this one follow its data
+            refresh_derived_habits_data(
+                conn,
+                &[habit_data.hab_id],
+                &[habit_data.hab_dat_collected_at],
+            )?;
+
+            Ok::<HabitDataCollected, diesel::result::Error>(habit_data)
+        });
+
+        if query.is_err() {
+            return Err(Error::QueryError(query.err().unwrap()));
+        }
+
+        Ok(query.unwrap())
+    }
+
+    // Add habit data records of several habits at once, all of them or none
+    pub fn add_habits_data(
+        &self,
+        data: Vec<HabitDataCreateSchema>,
+    ) -> Result<Vec<HabitDataCollected>, Error> {
+        let habits_data: Vec<HabitDataCollected> = data.into_iter().map(build_habit_data).collect();
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let habit_ids: Vec<Uuid> = habits_data.iter().map(|item| item.hab_id).collect();
+        let dates: Vec<chrono::NaiveDate> = habits_data
+            .iter()
+            .map(|item| item.hab_dat_collected_at)
+            .collect();
+
+        let query = conn.unwrap().transaction(|conn| {
+            let habits_data = diesel::insert_into(habit_data_collected::table)
+                .values(&habits_data)
+                .get_results::<HabitDataCollected>(conn)?;
+
+            refresh_derived_habits_data(conn, &habit_ids, &dates)?;
+
+            Ok::<Vec<HabitDataCollected>, diesel::result::Error>(habits_data)
+        });
 
         if query.is_err() {
             return Err(Error::QueryError(query.err().unwrap()));
@@ -122,10 +186,20 @@ impl DBManager {
             return Err(conn.err().unwrap());
         }
 
-        let query = diesel::delete(
-            habit_data_collected::table.filter(habit_data_collected::hab_dat_id.eq(id)),
-        )
-        .get_result::<HabitDataCollected>(&mut conn.unwrap());
+        let query = conn.unwrap().transaction(|conn| {
+            let habit_data = diesel::delete(
+                habit_data_collected::table.filter(habit_data_collected::hab_dat_id.eq(id)),
+            )
+            .get_result::<HabitDataCollected>(conn)?;
+
+            refresh_derived_habits_data(
+                conn,
+                &[habit_data.hab_id],
+                &[habit_data.hab_dat_collected_at],
+            )?;
+
+            Ok::<HabitDataCollected, diesel::result::Error>(habit_data)
+        });
 
         if query.is_err() {
             return Err(Error::QueryError(query.err().unwrap()));
@@ -146,11 +220,21 @@ impl DBManager {
             return Err(conn.err().unwrap());
         }
 
-        let query = diesel::update(
-            habit_data_collected::table.filter(habit_data_collected::hab_dat_id.eq(id)),
-        )
-        .set(&data)
-        .get_result::<HabitDataCollected>(&mut conn.unwrap());
+        let query = conn.unwrap().transaction(|conn| {
+            let habit_data = diesel::update(
+                habit_data_collected::table.filter(habit_data_collected::hab_dat_id.eq(id)),
+            )
+            .set(&data)
+            .get_result::<HabitDataCollected>(conn)?;
+
+            refresh_derived_habits_data(
+                conn,
+                &[habit_data.hab_id],
+                &[habit_data.hab_dat_collected_at],
+            )?;
+
+            Ok::<HabitDataCollected, diesel::result::Error>(habit_data)
+        });
 
         if query.is_err() {
             return Err(Error::QueryError(query.err().unwrap()));
@@ -0,0 +1,286 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::habit_api_models::*,
+        database::{Habit, HabitDependency},
+    },
+    schema::*,
+    utils::{
+        dependencies::{build_habit_chain, get_trigger_chain, would_create_cycle},
+        periods::{get_habit_reference_date, is_habit_active},
+        MAX_CALENDAR_DAYS,
+    },
+};
+
+use diesel::prelude::*;
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+impl DBManager {
+    // Get the dependencies between the habits of a user
+    pub fn get_user_habit_dependencies(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<HabitDependency>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_dependency::table
+            .inner_join(habit::table.on(habit::hab_id.eq(habit_dependency::hab_id)))
+            .select(HabitDependency::as_select())
+            .filter(habit::usr_id.eq(user_id))
+            .load::<HabitDependency>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Set the habit a habit is done right after, or remove it. Both habits must belong to the
+    // user and chains can't loop back
+    pub fn set_habit_trigger(
+        &self,
+        user_id: String,
+        hab_id: Uuid,
+        trigger_id: Option<Uuid>,
+    ) -> Result<Option<HabitDependency>, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        if trigger_id.is_none() {
+            let search = diesel::delete(habit_dependency::table.find(hab_id)).execute(&mut conn);
+
+            if search.is_err() {
+                return Err(Error::QueryError(search.err().unwrap()));
+            }
+
+            return Ok(None);
+        }
+
+        let trigger_id = trigger_id.unwrap();
+
+        if trigger_id == hab_id {
+            return Err(Error::BadRequest(
+                "A habit can't be its own trigger".to_string(),
+            ));
+        }
+
+        let owned = habit::table
+            .filter(habit::hab_id.eq(trigger_id))
+            .filter(habit::usr_id.eq(&user_id))
+            .count()
+            .get_result::<i64>(&mut conn);
+
+        if owned.is_err() {
+            return Err(Error::QueryError(owned.err().unwrap()));
+        }
+
+        if owned.unwrap() == 0 {
+            return Err(Error::BadRequest(
+                "Trigger habit must belong to the user".to_string(),
+            ));
+        }
+
+        let dependency = HabitDependency {
+            hab_id,
+            hab_dep_trigger_id: trigger_id,
+            hab_dep_created_at: chrono::Local::now().naive_local(),
+        };
+
+        let search = conn.transaction(|conn| {
+            // Trigger changes of a user go one at a time, so concurrent ones can't close a loop
+            // the other didn't see
+            diesel::sql_query(
+                "SELECT pg_advisory_xact_lock(hashtext('habit_dependency'), hashtext($1))",
+            )
+            .bind::<diesel::sql_types::Text, _>(&user_id)
+            .execute(conn)?;
+
+            let dependencies = habit_dependency::table
+                .inner_join(habit::table.on(habit::hab_id.eq(habit_dependency::hab_id)))
+                .select(HabitDependency::as_select())
+                .filter(habit::usr_id.eq(&user_id))
+                .load::<HabitDependency>(conn)?;
+
+            if would_create_cycle(&dependencies, hab_id, trigger_id) {
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+
+            diesel::insert_into(habit_dependency::table)
+                .values(&dependency)
+                .on_conflict(habit_dependency::hab_id)
+                .do_update()
+                .set((
+                    habit_dependency::hab_dep_trigger_id.eq(dependency.hab_dep_trigger_id),
+                    habit_dependency::hab_dep_created_at.eq(dependency.hab_dep_created_at),
+                ))
+                .execute(conn)?;
+
+            Ok::<(), diesel::result::Error>(())
+        });
+
+        if let Err(diesel::result::Error::RollbackTransaction) = search {
+            return Err(Error::BadRequest(
+                "Trigger habit already depends on the habit".to_string(),
+            ));
+        }
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(Some(dependency))
+    }
+
+    // Get the habits done right after a habit
+    pub fn get_habit_dependents(&self, trigger_id: Uuid) -> Result<Vec<Habit>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_dependency::table
+            .inner_join(habit::table.on(habit::hab_id.eq(habit_dependency::hab_id)))
+            .select(Habit::as_select())
+            .filter(habit_dependency::hab_dep_trigger_id.eq(trigger_id))
+            .order_by(habit::hab_name.asc())
+            .load::<Habit>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Dependents of a habit still to be done on a day, the ones to prompt once it's done
+    pub fn get_pending_habit_dependents(
+        &self,
+        trigger_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<Vec<Habit>, Error> {
+        let dependents = self.get_habit_dependents(trigger_id);
+
+        if dependents.is_err() {
+            return Err(dependents.err().unwrap());
+        }
+
+        let dependents: Vec<Habit> = dependents
+            .unwrap()
+            .into_iter()
+            .filter(|habit| is_habit_active(habit, date))
+            .collect();
+
+        let dependent_ids: Vec<Uuid> = dependents.iter().map(|habit| habit.hab_id).collect();
+
+        let daily_amounts = self.get_habits_daily_amounts(&dependent_ids, date, date);
+
+        if daily_amounts.is_err() {
+            return Err(daily_amounts.err().unwrap());
+        }
+
+        let daily_amounts = daily_amounts.unwrap();
+
+        Ok(dependents
+            .into_iter()
+            .filter(|habit| {
+                !daily_amounts
+                    .iter()
+                    .any(|item| item.hab_id == habit.hab_id && item.records > 0)
+            })
+            .collect())
+    }
+
+    // How often the chain leading to a habit completes together
+    pub fn get_habit_chain(
+        &self,
+        user_id: String,
+        hab_id: Uuid,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<HabitChain, Error> {
+        let dependencies = self.get_user_habit_dependencies(&user_id);
+
+        if dependencies.is_err() {
+            return Err(dependencies.err().unwrap());
+        }
+
+        let dependencies = dependencies.unwrap();
+
+        let chain = get_trigger_chain(&dependencies, hab_id);
+
+        let dependents: Vec<Uuid> = dependencies
+            .iter()
+            .filter(|item| item.hab_dep_trigger_id == hab_id)
+            .map(|item| item.hab_id)
+            .collect();
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let habits = habit::table
+            .select(Habit::as_select())
+            .filter(habit::hab_id.eq_any(&chain))
+            .load::<Habit>(&mut conn.unwrap());
+
+        if habits.is_err() {
+            return Err(Error::QueryError(habits.err().unwrap()));
+        }
+
+        // The chain can only complete once all of its habits exist
+        let end_date = end_date.unwrap_or(chrono::Local::now().naive_local().date());
+        let start_date = start_date.unwrap_or(
+            habits
+                .unwrap()
+                .iter()
+                .map(get_habit_reference_date)
+                .max()
+                .unwrap_or(end_date)
+                .max(end_date - chrono::Duration::days(MAX_CALENDAR_DAYS))
+                .min(end_date),
+        );
+
+        if end_date < start_date {
+            return Err(Error::BadRequest(
+                "End date must not be before start date".to_string(),
+            ));
+        }
+
+        if (end_date - start_date).num_days() > MAX_CALENDAR_DAYS {
+            return Err(Error::BadRequest(format!(
+                "Date range must not exceed {} days",
+                MAX_CALENDAR_DAYS
+            )));
+        }
+
+        let daily_amounts = self.get_habits_daily_amounts(&chain, start_date, end_date);
+
+        if daily_amounts.is_err() {
+            return Err(daily_amounts.err().unwrap());
+        }
+
+        Ok(build_habit_chain(
+            &chain,
+            &dependents,
+            &daily_amounts.unwrap(),
+            start_date,
+            end_date,
+        ))
+    }
+}
@@ -0,0 +1,397 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::{
+            data_api_models::HabitDataCreateSchema, events_api_models::HabitDailyAmount,
+            habit_api_models::*,
+        },
+        database::{
+            HabDerivationEnum, HabFreqTypeEnum, Habit, HabitDerivation, HabitDerivationSource,
+            HabitGoalVersion,
+        },
+    },
+    schema::*,
+    utils::{
+        derivations::{build_habit_derivation, check_habit_derivation, get_derived_amount},
+        periods::get_habit_versions,
+        queries::{build_habit_data, get_goal_version_join_sql, AGGREGATED_AMOUNT_SQL},
+    },
+};
+
+use diesel::{
+    prelude::*,
+    sql_types::{Array, Date},
+};
+
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+// Daily amounts of habits on some days, read from the connection writing their data
+fn load_daily_amounts(
+    conn: &mut PgConnection,
+    habit_ids: &[Uuid],
+    dates: &[NaiveDate],
+) -> QueryResult<Vec<HabitDailyAmount>> {
+    let version_join = get_goal_version_join_sql("hd.hab_dat_collected_at");
+
+    diesel::sql_query(format!(
+        "SELECT h.hab_id, hd.hab_dat_collected_at AS date, \
+            {AGGREGATED_AMOUNT_SQL} AS amount, COUNT(*) AS records \
+         FROM habit_data_collected hd \
+         INNER JOIN habit h ON h.hab_id = hd.hab_id \
+         {version_join} \
+         WHERE hd.hab_id = ANY($1) \
+            AND hd.hab_dat_collected_at = ANY($2) \
+         GROUP BY 1, 2",
+    ))
+    .bind::<Array<diesel::sql_types::Uuid>, _>(habit_ids)
+    .bind::<Array<Date>, _>(dates)
+    .load::<HabitDailyAmount>(conn)
+}
+
+// Lock derived habits until the end of the transaction, so writes of their sources recompute them
+// one after the other, each one seeing the data the previous one committed. Locked in the same
+// order everywhere to avoid deadlocks
+fn lock_derived_habits(conn: &mut PgConnection, derived_ids: &[Uuid]) -> QueryResult<()> {
+    habit_derivation::table
+        .select(habit_derivation::hab_id)
+        .filter(habit_derivation::hab_id.eq_any(derived_ids))
+        .order_by(habit_derivation::hab_id.asc())
+        .for_update()
+        .load::<Uuid>(conn)?;
+
+    Ok(())
+}
+
+// Compute the data of derived habits on some days, a single record holds the value of each day
+// and days without any value are left empty
+fn compute_derived_habits_data(
+    conn: &mut PgConnection,
+    derived_ids: &[Uuid],
+    dates: &[NaiveDate],
+) -> QueryResult<()> {
+    if derived_ids.is_empty() || dates.is_empty() {
+        return Ok(());
+    }
+
+    lock_derived_habits(conn, derived_ids)?;
+
+    let derivations = habit_derivation::table
+        .select(HabitDerivation::as_select())
+        .filter(habit_derivation::hab_id.eq_any(derived_ids))
+        .load::<HabitDerivation>(conn)?;
+
+    let sources = HabitDerivationSource::belonging_to(&derivations)
+        .select(HabitDerivationSource::as_select())
+        .load::<HabitDerivationSource>(conn)?;
+
+    let source_ids: Vec<Uuid> = sources.iter().map(|source| source.hab_src_id).collect();
+
+    let daily_amounts = load_daily_amounts(conn, &source_ids, dates)?;
+
+    // Quorums judge each source against the goal it had on the day
+    let source_habits = habit::table
+        .select(Habit::as_select())
+        .filter(habit::hab_id.eq_any(&source_ids))
+        .load::<Habit>(conn)?;
+
+    let source_versions = HabitGoalVersion::belonging_to(&source_habits)
+        .select(HabitGoalVersion::as_select())
+        .load::<HabitGoalVersion>(conn)?;
+
+    let source_versions: Vec<Vec<Habit>> = source_habits
+        .iter()
+        .zip(source_versions.grouped_by(&source_habits))
+        .map(|(habit, versions)| get_habit_versions(habit, &versions))
+        .collect();
+
+    let mut habits_data = vec![];
+
+    for (derivation, sources) in derivations.iter().zip(sources.grouped_by(&derivations)) {
+        let sources: Vec<Vec<Habit>> = source_versions
+            .iter()
+            .filter(|versions| {
+                sources
+                    .iter()
+                    .any(|source| source.hab_src_id == versions[0].hab_id)
+            })
+            .cloned()
+            .collect();
+
+        for date in dates {
+            let amount = get_derived_amount(derivation, &sources, &daily_amounts, *date);
+
+            if amount > BigDecimal::from(0) {
+                habits_data.push(build_habit_data(HabitDataCreateSchema {
+                    amount,
+                    collected_at: Some(*date),
+                    habit_id: derivation.hab_id,
+                    mood: None,
+                }));
+            }
+        }
+    }
+
+    diesel::delete(
+        habit_data_collected::table
+            .filter(habit_data_collected::hab_id.eq_any(derived_ids))
+            .filter(habit_data_collected::hab_dat_collected_at.eq_any(dates)),
+    )
+    .execute(conn)?;
+
+    diesel::insert_into(habit_data_collected::table)
+        .values(&habits_data)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+// Keep the habits derived from some habits in line with their data on some days, to be run in
+// the transaction writing that data
+pub fn refresh_derived_habits_data(
+    conn: &mut PgConnection,
+    source_ids: &[Uuid],
+    dates: &[NaiveDate],
+) -> QueryResult<()> {
+    let derived_ids = habit_derivation_source::table
+        .select(habit_derivation_source::hab_id)
+        .filter(habit_derivation_source::hab_src_id.eq_any(source_ids))
+        .distinct()
+        .load::<Uuid>(conn)?;
+
+    compute_derived_habits_data(conn, &derived_ids, dates)
+}
+
+// Recompute the whole history of a derived habit out of the days its sources were logged
+pub fn rebuild_derived_habit_data(conn: &mut PgConnection, hab_id: Uuid) -> QueryResult<()> {
+    lock_derived_habits(conn, &[hab_id])?;
+
+    let source_ids = habit_derivation_source::table
+        .select(habit_derivation_source::hab_src_id)
+        .filter(habit_derivation_source::hab_id.eq(hab_id))
+        .load::<Uuid>(conn)?;
+
+    let dates = habit_data_collected::table
+        .select(habit_data_collected::hab_dat_collected_at)
+        .filter(habit_data_collected::hab_id.eq_any(&source_ids))
+        .distinct()
+        .load::<NaiveDate>(conn)?;
+
+    diesel::delete(habit_data_collected::table.filter(habit_data_collected::hab_id.eq(hab_id)))
+        .execute(conn)?;
+
+    compute_derived_habits_data(conn, &[hab_id], &dates)
+}
+
+// Check the sources of a derivation against the habits of their owner, to be run in the
+// transaction writing it. Derivation changes of a user go one at a time, so concurrent ones can't
+// chain derived habits the other didn't see
+pub fn check_derivation_sources(
+    conn: &mut PgConnection,
+    user_id: &str,
+    hab_id: Uuid,
+    data: &HabitDerivationSchema,
+) -> Result<(), Error> {
+    diesel::sql_query("SELECT pg_advisory_xact_lock(hashtext('habit_derivation'), hashtext($1))")
+        .bind::<diesel::sql_types::Text, _>(user_id)
+        .execute(conn)?;
+
+    let sources = habit::table
+        .select(Habit::as_select())
+        .filter(habit::hab_id.eq_any(&data.sources))
+        .filter(habit::usr_id.eq(user_id))
+        .load::<Habit>(conn)?;
+
+    if sources.len() != data.sources.len() {
+        return Err(Error::BadRequest(
+            "Derivation sources must belong to the user".to_string(),
+        ));
+    }
+
+    // Quorums are judged day by day, against the daily goal of each source
+    if data.kind == HabDerivationEnum::quorum
+        && sources
+            .iter()
+            .any(|source| source.hab_freq_type != HabFreqTypeEnum::daily)
+    {
+        return Err(Error::BadRequest(
+            "Quorum derivation sources must be daily habits".to_string(),
+        ));
+    }
+
+    let derived_sources = habit_derivation::table
+        .filter(habit_derivation::hab_id.eq_any(&data.sources))
+        .count()
+        .get_result::<i64>(conn)?;
+
+    if derived_sources > 0 {
+        return Err(Error::BadRequest(
+            "Derivation sources can't be derived habits".to_string(),
+        ));
+    }
+
+    let derived_from = habit_derivation_source::table
+        .filter(habit_derivation_source::hab_src_id.eq(hab_id))
+        .count()
+        .get_result::<i64>(conn)?;
+
+    if derived_from > 0 {
+        return Err(Error::BadRequest(
+            "Habits other habits are derived from can't be derived".to_string(),
+        ));
+    }
+
+    let checklist_items = habit_checklist_item::table
+        .filter(habit_checklist_item::hab_id.eq(hab_id))
+        .count()
+        .get_result::<i64>(conn)?;
+
+    if checklist_items > 0 {
+        return Err(Error::BadRequest(
+            "Habits with a checklist can't be derived".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+impl DBManager {
+    // Get which of some habits are derived
+    pub fn get_derived_habit_ids(&self, habit_ids: &[Uuid]) -> Result<Vec<Uuid>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_derivation::table
+            .select(habit_derivation::hab_id)
+            .filter(habit_derivation::hab_id.eq_any(habit_ids))
+            .load::<Uuid>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Derived habits are computed, their data can't be written directly
+    pub fn is_habit_derived(&self, hab_id: Uuid) -> Result<bool, Error> {
+        let derived_ids = self.get_derived_habit_ids(&[hab_id]);
+
+        if derived_ids.is_err() {
+            return Err(derived_ids.err().unwrap());
+        }
+
+        Ok(!derived_ids.unwrap().is_empty())
+    }
+
+    // Get the definition of a derived habit, none for logged habits
+    pub fn get_habit_derivation(
+        &self,
+        hab_id: Uuid,
+    ) -> Result<Option<HabitDerivationWithSources>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let derivation = habit_derivation::table
+            .select(HabitDerivation::as_select())
+            .find(hab_id)
+            .first::<HabitDerivation>(&mut conn)
+            .optional();
+
+        if derivation.is_err() {
+            return Err(Error::QueryError(derivation.err().unwrap()));
+        }
+
+        let derivation = derivation.unwrap();
+
+        if derivation.is_none() {
+            return Ok(None);
+        }
+
+        let derivation = derivation.unwrap();
+
+        let sources = HabitDerivationSource::belonging_to(&derivation)
+            .select(habit_derivation_source::hab_src_id)
+            .load::<Uuid>(&mut conn);
+
+        if sources.is_err() {
+            return Err(Error::QueryError(sources.err().unwrap()));
+        }
+
+        Ok(Some(HabitDerivationWithSources {
+            derivation,
+            sources: sources.unwrap(),
+        }))
+    }
+
+    // Set how a habit is derived, replacing its data with the computed one, or turn it back into
+    // a logged habit keeping the data computed so far
+    pub fn set_habit_derivation(
+        &self,
+        user_id: String,
+        habit: &Habit,
+        data: Option<HabitDerivationSchema>,
+    ) -> Result<Option<HabitDerivationWithSources>, Error> {
+        if let Some(data) = &data {
+            let result = check_habit_derivation(habit, data);
+
+            if result.is_err() {
+                return Err(result.err().unwrap());
+            }
+        }
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let hab_id = habit.hab_id;
+
+        let search = conn.unwrap().transaction(|conn| {
+            if let Some(data) = &data {
+                check_derivation_sources(conn, &user_id, hab_id, data)?;
+            }
+
+            diesel::delete(habit_derivation::table.find(hab_id)).execute(conn)?;
+
+            if data.is_none() {
+                return Ok::<(), Error>(());
+            }
+
+            let (derivation, sources) = build_habit_derivation(
+                hab_id,
+                data.as_ref().unwrap(),
+                chrono::Local::now().naive_local(),
+            );
+
+            diesel::insert_into(habit_derivation::table)
+                .values(&derivation)
+                .execute(conn)?;
+
+            diesel::insert_into(habit_derivation_source::table)
+                .values(&sources)
+                .execute(conn)?;
+
+            rebuild_derived_habit_data(conn, hab_id)?;
+
+            Ok(())
+        });
+
+        if search.is_err() {
+            return Err(search.err().unwrap());
+        }
+
+        self.get_habit_derivation(hab_id)
+    }
+}
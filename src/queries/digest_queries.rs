@@ -0,0 +1,135 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::digest_api_models::DigestPreferenceUpdateSchema, database::UserDigestPreference,
+    },
+    schema::*,
+};
+
+use chrono::Datelike;
+use diesel::{prelude::*, upsert::excluded};
+
+impl DBManager {
+    // Get the digest settings of a user, users that never set them are opted out
+    pub fn get_digest_preference(&self, user_id: String) -> Result<UserDigestPreference, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = user_digest_preference::table
+            .select(UserDigestPreference::as_select())
+            .find(&user_id)
+            .first::<UserDigestPreference>(&mut conn.unwrap())
+            .optional();
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap().unwrap_or(UserDigestPreference {
+            usr_id: user_id,
+            usr_dig_enabled: false,
+            usr_dig_weekday: 1,
+            usr_dig_last_sent_on: None,
+            usr_dig_updated_at: chrono::Utc::now().naive_utc(),
+        }))
+    }
+
+    // Create or replace the digest settings of a user
+    pub fn update_digest_preference(
+        &self,
+        user_id: String,
+        data: DigestPreferenceUpdateSchema,
+    ) -> Result<UserDigestPreference, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let preference = (
+            user_digest_preference::usr_id.eq(user_id),
+            user_digest_preference::usr_dig_enabled.eq(data.enabled),
+            user_digest_preference::usr_dig_weekday.eq(data.weekday),
+            user_digest_preference::usr_dig_updated_at.eq(chrono::Utc::now().naive_utc()),
+        );
+
+        let result = diesel::insert_into(user_digest_preference::table)
+            .values(preference)
+            .on_conflict(user_digest_preference::usr_id)
+            .do_update()
+            .set((
+                user_digest_preference::usr_dig_enabled
+                    .eq(excluded(user_digest_preference::usr_dig_enabled)),
+                user_digest_preference::usr_dig_weekday
+                    .eq(excluded(user_digest_preference::usr_dig_weekday)),
+                user_digest_preference::usr_dig_updated_at
+                    .eq(excluded(user_digest_preference::usr_dig_updated_at)),
+            ))
+            .returning(UserDigestPreference::as_returning())
+            .get_result::<UserDigestPreference>(&mut conn.unwrap());
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        Ok(result.unwrap())
+    }
+
+    // Users opted in whose digest is due at the given date and wasn't sent yet
+    pub fn get_due_digest_users(&self, as_of: chrono::NaiveDate) -> Result<Vec<String>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let weekday = as_of.weekday().number_from_monday() as i16;
+
+        let search = user_digest_preference::table
+            .select(user_digest_preference::usr_id)
+            .filter(user_digest_preference::usr_dig_enabled.eq(true))
+            .filter(user_digest_preference::usr_dig_weekday.eq(weekday))
+            .filter(
+                user_digest_preference::usr_dig_last_sent_on
+                    .is_null()
+                    .or(user_digest_preference::usr_dig_last_sent_on.lt(as_of)),
+            )
+            .order_by(user_digest_preference::usr_id.asc())
+            .load::<String>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Record the digests of the given users as sent
+    pub fn mark_digests_sent(
+        &self,
+        user_ids: &[String],
+        as_of: chrono::NaiveDate,
+    ) -> Result<usize, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let result = diesel::update(
+            user_digest_preference::table.filter(user_digest_preference::usr_id.eq_any(user_ids)),
+        )
+        .set(user_digest_preference::usr_dig_last_sent_on.eq(as_of))
+        .execute(&mut conn.unwrap());
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        Ok(result.unwrap())
+    }
+}
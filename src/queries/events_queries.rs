@@ -7,11 +7,16 @@ use crate::{
     },
     schema::*,
     utils::{
+        periods::{build_calendar_periods, build_heatmap_days},
+        queries::{get_goal_version_join_sql, AGGREGATED_AMOUNT_SQL},
         time::{DateRange, MAXIMUM_DATE, MINIMUM_DATE},
-        DEFAULT_QUERY_LIMIT, HABIT_CREATION_DATE_AS_REFERENCE,
+        DEFAULT_QUERY_LIMIT, HABIT_CREATION_DATE_AS_REFERENCE, MAX_CALENDAR_DAYS, MAX_PERIOD_DAYS,
     },
 };
-use diesel::prelude::*;
+use diesel::{
+    prelude::*,
+    sql_types::{Array, Date, Nullable, Text},
+};
 use uuid::Uuid;
 
 impl DBManager {
@@ -44,8 +49,10 @@ impl DBManager {
 
         let start_date = start_date.unwrap_or(chrono::Local::now().naive_local().date());
 
-        // By default, end date is 7 days from start date (so a week)
-        let end_date = end_date.unwrap_or(start_date + chrono::Duration::days(7));
+        // By default, end date is 7 days from start date (so a week). No events after the habit ends
+        let end_date = end_date
+            .unwrap_or(start_date + chrono::Duration::days(7))
+            .min(habit.hab_end_date.unwrap_or(MAXIMUM_DATE.unwrap()));
 
         let mut vec = Vec::new();
 
@@ -53,10 +60,13 @@ impl DBManager {
             end_date,
             habit.hab_freq_type,
             Some(start_date),
-            Some(match HABIT_CREATION_DATE_AS_REFERENCE {
-                true => habit.hab_created_at.date(),
-                false => start_date,
-            }),
+            Some(
+                match (habit.hab_start_date, HABIT_CREATION_DATE_AS_REFERENCE) {
+                    (Some(habit_start_date), _) => habit_start_date,
+                    (None, true) => habit.hab_created_at.date(),
+                    (None, false) => start_date,
+                },
+            ),
         );
 
         for date_ocurrence in data_range {
@@ -85,89 +95,281 @@ impl DBManager {
         habit_id: Option<Uuid>,
         start_date: Option<chrono::NaiveDate>,
         end_date: Option<chrono::NaiveDate>,
+        group_by: Option<CalendarGrouping>,
     ) -> Result<Vec<CalendarEvent>, Error> {
+        if habit_id.is_none() && user_id.is_none() {
+            return Err(Error::BadRequest("Missing habit_id or user_id".to_string()));
+        }
+
+        let group_by = group_by.unwrap_or(CalendarGrouping::Day);
+
         let conn = self.get_read_connection();
 
         if conn.is_err() {
             return Err(conn.err().unwrap());
         }
 
-        let mut query = habit_data_collected::table
-            .inner_join(habit::table)
-            .select((HabitDataCollected::as_select(), Habit::as_select()))
-            .filter(
-                habit_data_collected::hab_dat_collected_at
-                    .ge(start_date.unwrap_or(MINIMUM_DATE.unwrap())),
-            )
+        // Each habit is aggregated on its own first, split where its goal version changes the way
+        // it aggregates, then added up. Y/N habits count each of their records, relative frequency
+        // is 0 when there is no data at all
+        let version_join = get_goal_version_join_sql("hd.hab_dat_collected_at");
+        let query = diesel::sql_query(format!(
+            "SELECT grouped.date, grouped.data, \
+                COALESCE(grouped.data / NULLIF(SUM(grouped.data) OVER (), 0), 0) \
+                    AS relative_frequency \
+             FROM ( \
+                SELECT by_habit.date, SUM(by_habit.data) AS data \
+                FROM ( \
+                    SELECT date_trunc($1, hd.hab_dat_collected_at)::date AS date, \
+                        {AGGREGATED_AMOUNT_SQL} AS data \
+                    FROM habit_data_collected hd \
+                    INNER JOIN habit h ON h.hab_id = hd.hab_id \
+                    {version_join} \
+                    WHERE hd.hab_dat_collected_at >= $2 \
+                        AND hd.hab_dat_collected_at <= $3 \
+                        AND (h.hab_id = $4 OR ($4 IS NULL AND h.usr_id = $5)) \
+                    GROUP BY 1, h.hab_id, v.hab_ver_aggregation, v.hab_ver_is_yn \
+                ) AS by_habit \
+                GROUP BY 1 \
+             ) AS grouped \
+             ORDER BY grouped.date ASC",
+        ))
+        .bind::<Text, _>(group_by.as_date_trunc_field())
+        .bind::<Date, _>(start_date.unwrap_or(MINIMUM_DATE.unwrap()))
+        .bind::<Date, _>(end_date.unwrap_or(MAXIMUM_DATE.unwrap()))
+        .bind::<Nullable<diesel::sql_types::Uuid>, _>(habit_id)
+        .bind::<Nullable<Text>, _>(user_id);
+
+        // Execute query
+        let data = query.load::<CalendarEvent>(&mut conn.unwrap());
+
+        if data.is_err() {
+            return Err(Error::QueryError(data.err().unwrap()));
+        }
+
+        Ok(data.unwrap())
+    }
+
+    // Expected periods of habits within two dates, merged with the data collected in them
+    pub fn get_habits_calendar_periods(
+        &self,
+        user_id: Option<String>,
+        habit_id: Option<Uuid>,
+        start_date: Option<chrono::NaiveDate>,
+        end_date: Option<chrono::NaiveDate>,
+    ) -> Result<Vec<CalendarPeriod>, Error> {
+        let current_date = chrono::Local::now().naive_local().date();
+
+        // By default, show the last month
+        let end_date = end_date.unwrap_or(current_date);
+        let start_date = start_date.unwrap_or(end_date - chrono::Duration::days(30));
+
+        if end_date < start_date {
+            return Err(Error::BadRequest(
+                "End date must not be before start date".to_string(),
+            ));
+        }
+
+        if (end_date - start_date).num_days() > MAX_CALENDAR_DAYS {
+            return Err(Error::BadRequest(format!(
+                "Date range must not exceed {} days",
+                MAX_CALENDAR_DAYS
+            )));
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let mut query = habit::table.select(Habit::as_select()).into_boxed();
+
+        if let Some(habit_id) = habit_id {
+            query = query.filter(habit::hab_id.eq(habit_id));
+        } else if let Some(user_id) = user_id {
+            query = query.filter(habit::usr_id.eq(user_id));
+        } else {
+            return Err(Error::BadRequest("Missing habit_id or user_id".to_string()));
+        }
+
+        let habits = query.load::<Habit>(&mut conn);
+
+        if habits.is_err() {
+            return Err(Error::QueryError(habits.err().unwrap()));
+        }
+
+        let habits = habits.unwrap();
+
+        // Periods overlapping the range may start up to two months before it
+        let habits_data = HabitDataCollected::belonging_to(&habits)
+            .select(HabitDataCollected::as_select())
             .filter(
                 habit_data_collected::hab_dat_collected_at
-                    .le(end_date.unwrap_or(MAXIMUM_DATE.unwrap())),
+                    .ge(start_date - chrono::Duration::days(MAX_PERIOD_DAYS)),
             )
-            .into_boxed();
+            .filter(habit_data_collected::hab_dat_collected_at.le(end_date))
+            .load::<HabitDataCollected>(&mut conn);
 
-        if habit_id.is_some() {
-            query = query.filter(habit::hab_id.eq(habit_id.unwrap()));
-        } else if user_id.is_some() {
-            query = query.filter(habit::usr_id.eq(user_id.unwrap()));
-        } else {
-            return Err(Error::BadRequest("Missing habit_id or user_id".to_string()));
+        if habits_data.is_err() {
+            return Err(Error::QueryError(habits_data.err().unwrap()));
         }
 
-        query = query.order(habit_data_collected::hab_dat_collected_at.asc());
+        let habits_data = habits_data.unwrap().grouped_by(&habits);
 
-        // Execute query
-        let data = query.load::<(HabitDataCollected, Habit)>(&mut conn.unwrap());
+        let versions = self.get_habits_versions(&habits);
 
-        if data.is_err() {
-            return Err(Error::QueryError(data.err().unwrap()));
+        if versions.is_err() {
+            return Err(versions.err().unwrap());
         }
 
-        let data = data.unwrap();
+        let mut periods: Vec<CalendarPeriod> = versions
+            .unwrap()
+            .iter()
+            .zip(habits_data.iter())
+            .flat_map(|(versions, data)| {
+                build_calendar_periods(versions, data, start_date, end_date, current_date)
+            })
+            .collect();
 
-        // Group data by date
-        let mut data_by_date: Vec<CalendarEvent> = Vec::new();
+        periods.sort_by_key(|period| period.start_date);
 
-        for (habit_data, habit) in data {
-            let mut found = false;
+        Ok(periods)
+    }
 
-            for data_by_date_item in &mut data_by_date {
-                if data_by_date_item.date == habit_data.hab_dat_collected_at {
-                    data_by_date_item.data += match habit.hab_is_yn {
-                        true => bigdecimal::BigDecimal::from(1),
-                        false => habit_data.hab_dat_amount.clone(),
-                    };
+    // Daily intensity of habits (a single one, a category's or all of a user's) over a year
+    pub fn get_habits_heatmap(
+        &self,
+        user_id: Option<String>,
+        habit_id: Option<Uuid>,
+        category_id: Option<Uuid>,
+        year: i32,
+    ) -> Result<(chrono::NaiveDate, chrono::NaiveDate, Vec<HeatmapDay>), Error> {
+        let start_date = chrono::NaiveDate::from_ymd_opt(year, 1, 1);
+        let end_date = chrono::NaiveDate::from_ymd_opt(year, 12, 31);
+
+        if start_date.is_none()
+            || end_date.is_none()
+            || start_date < MINIMUM_DATE
+            || end_date > MAXIMUM_DATE
+        {
+            return Err(Error::BadRequest(format!("Invalid year {}", year)));
+        }
 
-                    found = true;
-                    break;
-                }
-            }
+        self.get_habits_heatmap_between(
+            user_id,
+            habit_id,
+            category_id,
+            start_date.unwrap(),
+            end_date.unwrap(),
+        )
+    }
 
-            if !found {
-                let calendar_event = CalendarEvent {
-                    date: habit_data.hab_dat_collected_at,
-                    data: match habit.hab_is_yn {
-                        true => bigdecimal::BigDecimal::from(1),
-                        false => habit_data.hab_dat_amount,
-                    },
-                    relative_frequency: bigdecimal::BigDecimal::from(0),
-                };
-
-                data_by_date.push(calendar_event);
+    // Heatmap of habits over any date range, callers keep the range within bounds
+    pub fn get_habits_heatmap_between(
+        &self,
+        user_id: Option<String>,
+        habit_id: Option<Uuid>,
+        category_id: Option<Uuid>,
+        start_date: chrono::NaiveDate,
+        end_date: chrono::NaiveDate,
+    ) -> Result<(chrono::NaiveDate, chrono::NaiveDate, Vec<HeatmapDay>), Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let mut query = habit::table.select(Habit::as_select()).into_boxed();
+
+        if let Some(habit_id) = habit_id {
+            query = query.filter(habit::hab_id.eq(habit_id));
+        } else if let Some(user_id) = user_id {
+            query = query.filter(habit::usr_id.eq(user_id));
+
+            if let Some(category_id) = category_id {
+                query = query.filter(habit::cat_id.eq(category_id));
             }
+        } else {
+            return Err(Error::BadRequest("Missing habit_id or user_id".to_string()));
+        }
+
+        let habits = query.load::<Habit>(&mut conn);
+
+        if habits.is_err() {
+            return Err(Error::QueryError(habits.err().unwrap()));
+        }
+
+        let habits = habits.unwrap();
+        let habit_ids: Vec<Uuid> = habits.iter().map(|habit| habit.hab_id).collect();
+
+        let daily_amounts = self.get_habits_daily_amounts(&habit_ids, start_date, end_date);
+
+        if daily_amounts.is_err() {
+            return Err(daily_amounts.err().unwrap());
+        }
+
+        let versions = self.get_habits_versions(&habits);
+
+        if versions.is_err() {
+            return Err(versions.err().unwrap());
         }
 
-        // Find relative frequency
-        let mut total_data = bigdecimal::BigDecimal::from(0);
+        let current_date = chrono::Local::now().naive_local().date();
+        let days = build_heatmap_days(
+            &versions.unwrap(),
+            &daily_amounts.unwrap(),
+            start_date,
+            end_date,
+            current_date,
+        );
+
+        Ok((start_date, end_date, days))
+    }
 
-        for data_by_date_item in &data_by_date {
-            total_data += data_by_date_item.data.clone();
+    // Data of habits summarized by day and habit between two dates
+    pub fn get_habits_daily_amounts(
+        &self,
+        habit_ids: &[Uuid],
+        start_date: chrono::NaiveDate,
+        end_date: chrono::NaiveDate,
+    ) -> Result<Vec<HabitDailyAmount>, Error> {
+        if habit_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
         }
 
-        for data_by_date_item in &mut data_by_date {
-            data_by_date_item.relative_frequency =
-                data_by_date_item.data.clone() / total_data.clone();
+        // Y/N habits count once per record
+        let version_join = get_goal_version_join_sql("hd.hab_dat_collected_at");
+        let daily_amounts = diesel::sql_query(format!(
+            "SELECT h.hab_id, hd.hab_dat_collected_at AS date, \
+                {AGGREGATED_AMOUNT_SQL} AS amount, COUNT(*) AS records \
+             FROM habit_data_collected hd \
+             INNER JOIN habit h ON h.hab_id = hd.hab_id \
+             {version_join} \
+             WHERE hd.hab_id = ANY($1) \
+                AND hd.hab_dat_collected_at >= $2 \
+                AND hd.hab_dat_collected_at <= $3 \
+             GROUP BY 1, 2",
+        ))
+        .bind::<Array<diesel::sql_types::Uuid>, _>(habit_ids)
+        .bind::<Date, _>(start_date)
+        .bind::<Date, _>(end_date)
+        .load::<HabitDailyAmount>(&mut conn.unwrap());
+
+        if daily_amounts.is_err() {
+            return Err(Error::QueryError(daily_amounts.err().unwrap()));
         }
 
-        Ok(data_by_date)
+        Ok(daily_amounts.unwrap())
     }
 }
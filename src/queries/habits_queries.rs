@@ -2,16 +2,21 @@ use crate::{
     db::DBManager,
     error::Error,
     models::api::habit_api_models::*,
-    models::database::Habit,
+    models::database::{Habit, HabitPeriod},
+    queries::derivations_queries::{check_derivation_sources, rebuild_derived_habit_data},
     schema::*,
     utils::{
-        time::{get_next_closure_date, DateRange},
-        DEFAULT_QUERY_LIMIT, HABIT_CREATION_DATE_AS_REFERENCE, MAX_QUERY_LIMIT,
+        checklists::build_checklist_items,
+        derivations::{build_habit_derivation, check_habit_derivation},
+        periods::{get_goal_version, HabitClosure},
+        queries::build_new_habit,
+        time::{DateRange, MAXIMUM_DATE},
+        DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT,
     },
 };
 
 use chrono::NaiveDate;
-use diesel::prelude::*;
+use diesel::{pg::upsert::excluded, prelude::*};
 
 use uuid::Uuid;
 
@@ -47,38 +52,34 @@ impl DBManager {
         let current_datetime = chrono::Local::now().naive_local();
         let current_date = current_datetime.date();
 
-        let closure_date: NaiveDate;
-
-        if HABIT_CREATION_DATE_AS_REFERENCE {
-            closure_date = current_date;
-        } else {
-            closure_date = DateRange::get_next_closest_date(
-                data.frequency_type,
-                // Change if habit start should be another (usually a week later from current date)
-                Some(current_date),
-                // Change to None when reference date should be another (usually a constant)
-                None,
-            );
-        }
-
-        let habit = Habit {
-            hab_id: Uuid::new_v4(),
-            hab_name: data.name,
-            hab_description: data.description,
-            hab_created_at: current_datetime,
-            hab_updated_at: current_datetime,
-            hab_is_favorite: data.is_favorite,
-            hab_is_yn: data.is_yn,
-            hab_color: data.color,
-            hab_units: data.units,
-            hab_goal: data.goal,
-            hab_freq_type: data.frequency_type,
-
-            hab_next_closure_date: closure_date,
-            hab_location: data.location,
-
-            usr_id: user_id,
-            cat_id: data.category,
+        let checklist = data.checklist.clone().unwrap_or_default();
+        let derivation = data.derivation.clone();
+
+        let habit = build_new_habit(user_id.clone(), data, current_datetime);
+
+        if habit.is_err() {
+            return Err(habit.err().unwrap());
+        }
+
+        let habit = habit.unwrap();
+
+        // Derived habits get their data computed from their sources' history
+        let derivation = match derivation {
+            Some(_) if !checklist.is_empty() => {
+                return Err(Error::BadRequest(
+                    "Habits with a checklist can't be derived".to_string(),
+                ));
+            }
+            Some(derivation) => {
+                let result = check_habit_derivation(&habit, &derivation);
+
+                if result.is_err() {
+                    return Err(result.err().unwrap());
+                }
+
+                Some(derivation)
+            }
+            None => None,
         };
 
         let conn = self.get_write_connection();
@@ -87,13 +88,46 @@ impl DBManager {
             return Err(conn.err().unwrap());
         }
 
-        let search = diesel::insert_into(habit::table)
-            .values(&habit)
-            .execute(&mut conn.unwrap())
-            .map(|_| habit.hab_id);
+        // Habit starts with its first goal version
+        let version = get_goal_version(&habit, current_date);
+
+        let items = build_checklist_items(habit.hab_id, &checklist, current_datetime);
+
+        let search = conn.unwrap().transaction(|conn| {
+            diesel::insert_into(habit::table)
+                .values(&habit)
+                .execute(conn)?;
+
+            diesel::insert_into(habit_goal_version::table)
+                .values(&version)
+                .execute(conn)?;
+
+            diesel::insert_into(habit_checklist_item::table)
+                .values(&items)
+                .execute(conn)?;
+
+            if let Some(data) = &derivation {
+                check_derivation_sources(conn, &user_id, habit.hab_id, data)?;
+
+                let (derivation, sources) =
+                    build_habit_derivation(habit.hab_id, data, current_datetime);
+
+                diesel::insert_into(habit_derivation::table)
+                    .values(&derivation)
+                    .execute(conn)?;
+
+                diesel::insert_into(habit_derivation_source::table)
+                    .values(&sources)
+                    .execute(conn)?;
+
+                rebuild_derived_habit_data(conn, habit.hab_id)?;
+            }
+
+            Ok::<Uuid, Error>(habit.hab_id)
+        });
 
         if search.is_err() {
-            return Err(Error::QueryError(search.err().unwrap()));
+            return Err(search.err().unwrap());
         }
 
         Ok(search.unwrap())
@@ -107,9 +141,22 @@ impl DBManager {
             return Err(conn.err().unwrap());
         }
 
-        let search = diesel::delete(habit::table.filter(habit::hab_id.eq(id)))
-            .execute(&mut conn.unwrap())
-            .map(|_| id);
+        // Habits derived from this one lose it as a source
+        let search = conn.unwrap().transaction(|conn| {
+            let derived_ids = habit_derivation_source::table
+                .select(habit_derivation_source::hab_id)
+                .filter(habit_derivation_source::hab_src_id.eq(id))
+                .order_by(habit_derivation_source::hab_id.asc())
+                .load::<Uuid>(conn)?;
+
+            diesel::delete(habit::table.filter(habit::hab_id.eq(id))).execute(conn)?;
+
+            for derived_id in derived_ids {
+                rebuild_derived_habit_data(conn, derived_id)?;
+            }
+
+            Ok::<Uuid, diesel::result::Error>(id)
+        });
 
         if search.is_err() {
             return Err(Error::QueryError(search.err().unwrap()));
@@ -119,23 +166,195 @@ impl DBManager {
     }
 
     // Update an habit
-    pub fn update_habit(&self, id: Uuid, data: HabitUpdateSchema) -> Result<Uuid, Error> {
+    pub fn update_habit(
+        &self,
+        id: Uuid,
+        data: HabitUpdateSchema,
+    ) -> Result<Option<InFlightPeriod>, Error> {
+        let habit = self.get_habit_by_id(id);
+
+        if habit.is_err() {
+            return Err(habit.err().unwrap());
+        }
+
+        let habit = habit.unwrap();
+
+        // Goal changes apply from the period in progress on, earlier periods keep their own version
+        let current_datetime = chrono::Local::now().naive_local();
+        let current_date = current_datetime.date();
+        let is_frequency_change = data
+            .frequency_type
+            .is_some_and(|frequency_type| frequency_type != habit.hab_freq_type);
+        let is_goal_change = is_frequency_change
+            || data
+                .goal
+                .as_ref()
+                .is_some_and(|goal| goal != &habit.hab_goal)
+            || data
+                .goal_direction
+                .is_some_and(|direction| direction != habit.hab_goal_direction)
+            || data
+                .goal_tolerance
+                .as_ref()
+                .is_some_and(|tolerance| tolerance != &habit.hab_goal_tolerance)
+            || data
+                .units
+                .as_ref()
+                .is_some_and(|units| units != &habit.hab_units)
+            || data
+                .aggregation
+                .is_some_and(|aggregation| aggregation != habit.hab_aggregation)
+            || data.is_yn.is_some_and(|is_yn| is_yn != habit.hab_is_yn);
+
+        // Leaving the challenge is the way to follow another goal
+        if is_goal_change {
+            let in_challenge = self.is_habit_in_challenge(id);
+
+            if in_challenge.is_err() {
+                return Err(in_challenge.err().unwrap());
+            }
+
+            if in_challenge.unwrap() {
+                return Err(Error::BadRequest(
+                    "Challenge habits keep the goal of their challenge".to_string(),
+                ));
+            }
+        }
+
+        // Derived habits keep the kind their derivation computes
+        if data.is_yn.is_some_and(|is_yn| is_yn != habit.hab_is_yn) {
+            let is_derived = self.is_habit_derived(id);
+
+            if is_derived.is_err() {
+                return Err(is_derived.err().unwrap());
+            }
+
+            if is_derived.unwrap() {
+                return Err(Error::BadRequest(
+                    "Derived habits can't switch between Y/N and amounts".to_string(),
+                ));
+            }
+        }
+
+        let mut periods: Vec<HabitPeriod> = Vec::new();
+        let mut in_flight_period: Option<InFlightPeriod> = None;
+
+        // Other changes apply to the period in progress, a new frequency starts its own recurrence
+        // today instead, so the period in progress ends early and is judged against the previous
+        // definition prorated to the days it lasted
+        if is_frequency_change {
+            let frequency_type = data.frequency_type.unwrap_or(habit.hab_freq_type);
+            let interrupted =
+                self.build_interrupted_periods(&habit, current_date, current_datetime);
+
+            if interrupted.is_err() {
+                return Err(interrupted.err().unwrap());
+            }
+
+            let (closed_periods, closed_period) = interrupted.unwrap();
+
+            periods = closed_periods;
+            periods.extend(closed_period.clone());
+
+            // New recurrence starts today, or when the habit starts if it hasn't yet
+            let reference_date = current_date.max(habit.hab_start_date.unwrap_or(current_date));
+            let last_date = habit.hab_end_date.unwrap_or(MAXIMUM_DATE.unwrap());
+
+            in_flight_period = Some(InFlightPeriod {
+                treatment: match closed_period.is_some() {
+                    true => PeriodTreatment::Closed,
+                    false => PeriodTreatment::Restarted,
+                },
+                closed_period,
+                next_closure_date: DateRange::get_next_closest_date(
+                    frequency_type,
+                    Some(reference_date + chrono::Duration::days(1)),
+                    Some(reference_date),
+                )
+                .min(last_date + chrono::Duration::days(1)),
+            });
+        }
+
         let conn = self.get_write_connection();
 
         if conn.is_err() {
             return Err(conn.err().unwrap());
         }
 
-        let search = diesel::update(habit::table.filter(habit::hab_id.eq(id)))
-            .set(&data)
-            .execute(&mut conn.unwrap())
-            .map(|_| id);
+        let search = conn.unwrap().transaction(|conn| {
+            // Periods were worked out from the closure date read above, they only hold while the
+            // closure job hasn't moved it in the meantime
+            if let Some(in_flight_period) = &in_flight_period {
+                let updated = diesel::update(habit::table)
+                    .set(habit::hab_next_closure_date.eq(in_flight_period.next_closure_date))
+                    .filter(habit::hab_id.eq(id))
+                    .filter(habit::hab_next_closure_date.eq(habit.hab_next_closure_date))
+                    .execute(conn)?;
+
+                if updated == 0 {
+                    return Err(diesel::result::Error::RollbackTransaction);
+                }
+
+                diesel::insert_into(habit_period::table)
+                    .values(&periods)
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+            }
+
+            diesel::update(habit::table.filter(habit::hab_id.eq(id)))
+                .set(&data)
+                .execute(conn)?;
+
+            if is_goal_change {
+                let habit = habit::table
+                    .select(Habit::as_select())
+                    .find(id)
+                    .first(conn)?;
+
+                let version = get_goal_version(&habit, current_date);
+
+                // Several changes within the same day make up a single version
+                diesel::insert_into(habit_goal_version::table)
+                    .values(&version)
+                    .on_conflict((
+                        habit_goal_version::hab_id,
+                        habit_goal_version::hab_ver_valid_from,
+                    ))
+                    .do_update()
+                    .set((
+                        habit_goal_version::hab_ver_goal
+                            .eq(excluded(habit_goal_version::hab_ver_goal)),
+                        habit_goal_version::hab_ver_goal_direction
+                            .eq(excluded(habit_goal_version::hab_ver_goal_direction)),
+                        habit_goal_version::hab_ver_goal_tolerance
+                            .eq(excluded(habit_goal_version::hab_ver_goal_tolerance)),
+                        habit_goal_version::hab_ver_freq_type
+                            .eq(excluded(habit_goal_version::hab_ver_freq_type)),
+                        habit_goal_version::hab_ver_units
+                            .eq(excluded(habit_goal_version::hab_ver_units)),
+                        habit_goal_version::hab_ver_aggregation
+                            .eq(excluded(habit_goal_version::hab_ver_aggregation)),
+                        habit_goal_version::hab_ver_is_yn
+                            .eq(excluded(habit_goal_version::hab_ver_is_yn)),
+                    ))
+                    .execute(conn)?;
+            }
+
+            Ok::<(), diesel::result::Error>(())
+        });
+
+        // The closure job got there first, the periods above would be stale
+        if let Err(diesel::result::Error::RollbackTransaction) = search {
+            return Err(Error::BadRequest(
+                "Habit periods were being closed, try again".to_string(),
+            ));
+        }
 
         if search.is_err() {
             return Err(Error::QueryError(search.err().unwrap()));
         }
 
-        Ok(search.unwrap())
+        Ok(in_flight_period)
     }
 
     // Get all of user habits
@@ -262,46 +481,137 @@ impl DBManager {
         Ok(search.unwrap())
     }
 
-    // Get pending habits and update their closure date
-    pub fn get_update_pending_habits(&self) -> Result<Vec<Habit>, Error> {
-        let current_datetime = chrono::Local::now().naive_local();
-        let current_date = current_datetime.date();
+    // Get habits whose period closes on or before the given date
+    pub fn get_pending_habits(&self, as_of: NaiveDate) -> Result<Vec<Habit>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit::table
+            .select(Habit::as_select())
+            .filter(habit::hab_next_closure_date.le(as_of))
+            .load::<Habit>(&mut conn.unwrap());
 
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Get habits whose current period is still open at the given date
+    pub fn get_habits_in_progress(&self, as_of: NaiveDate) -> Result<Vec<Habit>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit::table
+            .select(Habit::as_select())
+            .filter(habit::hab_next_closure_date.gt(as_of))
+            .load::<Habit>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Get habits that ended before the given date and haven't had their summary sent since
+    pub fn get_finished_habits(&self, as_of: NaiveDate) -> Result<Vec<Habit>, Error> {
         let conn = self.get_read_connection();
 
         if conn.is_err() {
             return Err(conn.err().unwrap());
         }
 
-        // Get habits to update
         let search = habit::table
             .select(Habit::as_select())
-            .filter(habit::hab_next_closure_date.le(current_date))
+            .filter(habit::hab_end_date.lt(as_of))
+            .filter(habit::hab_summary_sent_on.is_null())
+            .order_by(habit::hab_end_date.asc())
             .load::<Habit>(&mut conn.unwrap());
 
         if search.is_err() {
             return Err(Error::QueryError(search.err().unwrap()));
         }
 
+        Ok(search.unwrap())
+    }
+
+    // Record the summaries of the given habits as sent
+    pub fn mark_summaries_sent(
+        &self,
+        habit_ids: &[Uuid],
+        as_of: NaiveDate,
+    ) -> Result<usize, Error> {
         let conn = self.get_write_connection();
 
         if conn.is_err() {
             return Err(conn.err().unwrap());
         }
 
-        // Update all habits that are pending
-        let update = diesel::update(habit::table)
-            .set(habit::hab_next_closure_date.eq(get_next_closure_date(
-                habit::hab_freq_type,
-                habit::hab_next_closure_date,
-            )))
-            .filter(habit::hab_next_closure_date.le(current_date))
-            .get_result::<Habit>(&mut conn.unwrap());
+        let result = diesel::update(habit::table.filter(habit::hab_id.eq_any(habit_ids)))
+            .set(habit::hab_summary_sent_on.eq(as_of))
+            .execute(&mut conn.unwrap());
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        Ok(result.unwrap())
+    }
+
+    // Record finished periods and move pending habits to their next closure date
+    pub fn close_habit_periods(
+        &self,
+        periods: &[HabitPeriod],
+        closures: &[HabitClosure],
+    ) -> Result<usize, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let update = conn.unwrap().transaction(|conn| {
+            let mut closed_ids: Vec<Uuid> = Vec::new();
+
+            // Habits whose closure date changed in the meantime are left untouched
+            for closure in closures {
+                let updated = diesel::update(habit::table)
+                    .set(habit::hab_next_closure_date.eq(closure.next_closure_date))
+                    .filter(habit::hab_id.eq(closure.hab_id))
+                    .filter(habit::hab_next_closure_date.eq(closure.prev_closure_date))
+                    .execute(conn)?;
+
+                if updated > 0 {
+                    closed_ids.push(closure.hab_id);
+                }
+            }
+
+            // Periods computed from a stale closure date aren't saved either
+            let closed_periods: Vec<&HabitPeriod> = periods
+                .iter()
+                .filter(|period| closed_ids.contains(&period.hab_id))
+                .collect();
+
+            diesel::insert_into(habit_period::table)
+                .values(closed_periods)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            Ok::<usize, diesel::result::Error>(closed_ids.len())
+        });
 
         if update.is_err() {
             return Err(Error::QueryError(update.err().unwrap()));
         }
 
-        Ok(search.unwrap())
+        Ok(update.unwrap())
     }
 }
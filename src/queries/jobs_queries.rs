@@ -0,0 +1,186 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::database::JobRun,
+    schema::*,
+    utils::{DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT},
+};
+
+use diesel::{
+    prelude::*,
+    r2d2::{ConnectionManager, PooledConnection},
+    sql_types::{Bool, Text},
+};
+
+use uuid::Uuid;
+
+#[derive(QueryableByName)]
+struct AdvisoryLock {
+    #[diesel(sql_type = Bool)]
+    locked: bool,
+}
+
+// Session lock held by a run of a job, released along with it when dropped. Scheduled and
+// manual runs take the same lock so they never overlap
+pub struct JobLock {
+    conn: PooledConnection<ConnectionManager<PgConnection>>,
+    job_name: String,
+}
+
+impl Drop for JobLock {
+    fn drop(&mut self) {
+        let result = diesel::sql_query(
+            "SELECT pg_advisory_unlock(hashtext('job_run'), hashtext($1)) AS locked",
+        )
+        .bind::<Text, _>(&self.job_name)
+        .get_result::<AdvisoryLock>(&mut self.conn);
+
+        if result.is_err() {
+            println!(
+                "[JOBS] Error releasing job {} lock: {:?}",
+                self.job_name,
+                result.err()
+            );
+        }
+    }
+}
+
+impl DBManager {
+    // Try to lock a job for a run, returns None while another run of the job holds it
+    pub fn lock_job(&self, job_name: &str) -> Result<Option<JobLock>, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let result = diesel::sql_query(
+            "SELECT pg_try_advisory_lock(hashtext('job_run'), hashtext($1)) AS locked",
+        )
+        .bind::<Text, _>(job_name)
+        .get_result::<AdvisoryLock>(&mut conn);
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        match result.unwrap().locked {
+            true => Ok(Some(JobLock {
+                conn,
+                job_name: job_name.to_string(),
+            })),
+            false => Ok(None),
+        }
+    }
+
+    // Try to claim a job tick, returns None when another replica already claimed it
+    pub fn claim_job_run(
+        &self,
+        job_name: &str,
+        tick: chrono::NaiveDateTime,
+        replica: &str,
+    ) -> Result<Option<JobRun>, Error> {
+        let job_run = JobRun {
+            job_run_id: Uuid::new_v4(),
+            job_run_name: job_name.to_string(),
+            job_run_tick: tick,
+            job_run_replica: replica.to_string(),
+            job_run_started_at: chrono::Utc::now().naive_utc(),
+            job_run_finished_at: None,
+            job_run_habits_processed: 0,
+            job_run_error: None,
+        };
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        // Unique (name, tick) constraint makes the insert act as a lease
+        let result = diesel::insert_into(job_run::table)
+            .values(&job_run)
+            .on_conflict_do_nothing()
+            .execute(&mut conn.unwrap());
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        if result.unwrap() == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(job_run))
+    }
+
+    // Save the outcome of a claimed job run
+    pub fn finish_job_run(
+        &self,
+        id: Uuid,
+        habits_processed: i32,
+        error: Option<String>,
+    ) -> Result<Uuid, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let result = diesel::update(job_run::table.filter(job_run::job_run_id.eq(id)))
+            .set((
+                job_run::job_run_finished_at.eq(chrono::Utc::now().naive_utc()),
+                job_run::job_run_habits_processed.eq(habits_processed),
+                job_run::job_run_error.eq(error),
+            ))
+            .execute(&mut conn.unwrap())
+            .map(|_| id);
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        Ok(result.unwrap())
+    }
+
+    // Get job runs history (most recent first)
+    pub fn get_job_runs(
+        &self,
+        job_name: Option<String>,
+        page: Option<i64>,
+        per_page: Option<i64>,
+    ) -> Result<Vec<JobRun>, Error> {
+        let page = page.unwrap_or(1);
+        let mut per_page = per_page.unwrap_or(DEFAULT_QUERY_LIMIT);
+
+        if per_page > MAX_QUERY_LIMIT {
+            per_page = MAX_QUERY_LIMIT;
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut query = job_run::table.select(JobRun::as_select()).into_boxed();
+
+        if let Some(job_name) = job_name {
+            query = query.filter(job_run::job_run_name.eq(job_name));
+        }
+
+        let search = query
+            .order_by(job_run::job_run_started_at.desc())
+            .limit(per_page)
+            .offset((page - 1) * per_page)
+            .load::<JobRun>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+}
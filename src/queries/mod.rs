@@ -1,4 +1,17 @@
 pub mod categories_queries;
+pub mod challenges_queries;
+pub mod checklists_queries;
+pub mod dashboard_queries;
 pub mod data_queries;
+pub mod dependencies_queries;
+pub mod derivations_queries;
+pub mod digest_queries;
 pub mod events_queries;
 pub mod habits_queries;
+pub mod jobs_queries;
+pub mod periods_queries;
+pub mod reports_queries;
+pub mod routines_queries;
+pub mod shares_queries;
+pub mod stats_queries;
+pub mod versions_queries;
@@ -0,0 +1,398 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::period_api_models::{HabitStreak, PeriodAmount},
+        database::{Habit, HabitDataCollected, HabitPeriod},
+    },
+    schema::*,
+    utils::{
+        periods::{
+            aggregate_period_amount, get_habit_version_at, get_prorated_version, is_goal_met,
+            HabitClosure,
+        },
+        queries::{get_goal_version_join_sql, AGGREGATED_AMOUNT_SQL},
+        time::{get_next_closure_date, DateRange, MAXIMUM_DATE, MINIMUM_DATE},
+        DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT,
+    },
+};
+
+use diesel::{
+    prelude::*,
+    sql_types::{Array, Date, Uuid as SqlUuid},
+};
+
+use uuid::Uuid;
+
+impl DBManager {
+    // Get closed periods of a habit
+    pub fn get_habit_periods(
+        &self,
+        id: Uuid,
+        start_date: Option<chrono::NaiveDate>,
+        end_date: Option<chrono::NaiveDate>,
+        page: Option<i64>,
+        per_page: Option<i64>,
+    ) -> Result<Vec<HabitPeriod>, Error> {
+        let page = page.unwrap_or(1);
+        let mut per_page = per_page.unwrap_or(DEFAULT_QUERY_LIMIT);
+
+        if per_page > MAX_QUERY_LIMIT {
+            per_page = MAX_QUERY_LIMIT;
+        }
+
+        let start_date = start_date.unwrap_or(MINIMUM_DATE.unwrap());
+        let end_date: chrono::NaiveDate = end_date.unwrap_or(MAXIMUM_DATE.unwrap());
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_period::table
+            .select(HabitPeriod::as_select())
+            .filter(habit_period::hab_id.eq(id))
+            .filter(habit_period::hab_per_end_date.ge(start_date))
+            .filter(habit_period::hab_per_start_date.le(end_date))
+            .limit(per_page)
+            .offset((page - 1) * per_page)
+            .order_by(habit_period::hab_per_start_date.desc())
+            .load::<HabitPeriod>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // First day of the period each habit is going through, as far as recorded periods go. A period
+    // starts right after the last recorded one, or a full frequency step before closure
+    fn get_pending_start_dates(
+        &self,
+        conn: &mut PgConnection,
+        habits: &[Habit],
+    ) -> Result<Vec<chrono::NaiveDate>, Error> {
+        let last_periods = HabitPeriod::belonging_to(habits)
+            .select(HabitPeriod::as_select())
+            .order_by(habit_period::hab_per_end_date.desc())
+            .load::<HabitPeriod>(conn);
+
+        if last_periods.is_err() {
+            return Err(Error::QueryError(last_periods.err().unwrap()));
+        }
+
+        let last_periods = last_periods.unwrap().grouped_by(habits);
+
+        let mut start_dates: Vec<chrono::NaiveDate> = Vec::new();
+
+        for (habit, periods) in habits.iter().zip(last_periods.iter()) {
+            let mut start_date = match periods.first() {
+                Some(period) => period.hab_per_end_date + chrono::Duration::days(1),
+                None => DateRange::get_period_start_date(
+                    habit.hab_freq_type,
+                    habit.hab_next_closure_date,
+                ),
+            };
+
+            // Habit didn't exist before its creation date, nor before its scheduled start
+            let first_date = habit.hab_start_date.unwrap_or(habit.hab_created_at.date());
+
+            if start_date < first_date {
+                start_date = first_date;
+            }
+
+            start_dates.push(start_date);
+        }
+
+        Ok(start_dates)
+    }
+
+    // Build the records of every period of each habit closing on or before the given date,
+    // along with the closure date each habit moves to
+    pub fn build_closing_periods(
+        &self,
+        habits: &[Habit],
+        as_of: chrono::NaiveDate,
+        closed_at: chrono::NaiveDateTime,
+    ) -> Result<(Vec<HabitPeriod>, Vec<HabitClosure>), Error> {
+        if habits.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let start_dates = self.get_pending_start_dates(&mut conn, habits);
+
+        if start_dates.is_err() {
+            return Err(start_dates.err().unwrap());
+        }
+
+        let start_dates = start_dates.unwrap();
+
+        let min_date = start_dates
+            .iter()
+            .min()
+            .copied()
+            .unwrap_or(MINIMUM_DATE.unwrap());
+
+        let habits_data = HabitDataCollected::belonging_to(habits)
+            .select(HabitDataCollected::as_select())
+            .filter(habit_data_collected::hab_dat_collected_at.ge(min_date))
+            .load::<HabitDataCollected>(&mut conn);
+
+        if habits_data.is_err() {
+            return Err(Error::QueryError(habits_data.err().unwrap()));
+        }
+
+        let habits_data = habits_data.unwrap().grouped_by(habits);
+
+        // Closed periods are judged against the goal version in effect when they ended
+        let versions = self.get_habits_versions(habits);
+
+        if versions.is_err() {
+            return Err(versions.err().unwrap());
+        }
+
+        let versions = versions.unwrap();
+
+        let mut periods: Vec<HabitPeriod> = Vec::new();
+        let mut closures: Vec<HabitClosure> = Vec::new();
+
+        for (((habit, data), versions), mut start_date) in habits
+            .iter()
+            .zip(habits_data.iter())
+            .zip(versions.iter())
+            .zip(start_dates)
+        {
+            let mut closure_date = habit.hab_next_closure_date;
+            let last_date = habit.hab_end_date.unwrap_or(MAXIMUM_DATE.unwrap());
+
+            // Catch up with every period that closed since the last run
+            while closure_date <= as_of && closure_date < MAXIMUM_DATE.unwrap() {
+                let end_date = (closure_date - chrono::Duration::days(1)).min(last_date);
+
+                // Nothing to record when the period ended before the habit was created
+                if start_date <= end_date {
+                    let period_data: Vec<&HabitDataCollected> = data
+                        .iter()
+                        .filter(|item| {
+                            item.hab_dat_collected_at >= start_date
+                                && item.hab_dat_collected_at <= end_date
+                        })
+                        .collect();
+
+                    let version = get_habit_version_at(versions, end_date);
+                    let amount = aggregate_period_amount(version, &period_data);
+
+                    periods.push(HabitPeriod {
+                        hab_per_id: Uuid::new_v4(),
+                        hab_per_start_date: start_date,
+                        hab_per_end_date: end_date,
+                        hab_per_is_met: is_goal_met(version, &amount),
+                        hab_per_amount: amount,
+                        hab_per_goal: version.hab_goal.clone(),
+                        hab_per_closed_at: closed_at,
+                        hab_id: habit.hab_id,
+                    });
+
+                    start_date = closure_date;
+                }
+
+                // Last period is cut short at the end of the habit, nothing closes afterwards
+                closure_date = match end_date >= last_date {
+                    true => MAXIMUM_DATE.unwrap(),
+                    false => get_next_closure_date(habit.hab_freq_type, closure_date)
+                        .min(last_date + chrono::Duration::days(1)),
+                };
+            }
+
+            closures.push(HabitClosure {
+                hab_id: habit.hab_id,
+                prev_closure_date: habit.hab_next_closure_date,
+                next_closure_date: closure_date,
+            });
+        }
+
+        Ok((periods, closures))
+    }
+
+    // Cut the period a habit is going through short right before the given date, along with the
+    // records of every period closing until then. The cut period is judged against a goal prorated
+    // to the days it lasted, and left out when it would start on that date
+    pub fn build_interrupted_periods(
+        &self,
+        habit: &Habit,
+        cut_date: chrono::NaiveDate,
+        closed_at: chrono::NaiveDateTime,
+    ) -> Result<(Vec<HabitPeriod>, Option<HabitPeriod>), Error> {
+        let habits = std::slice::from_ref(habit);
+        let periods = self.build_closing_periods(habits, cut_date, closed_at);
+
+        if periods.is_err() {
+            return Err(periods.err().unwrap());
+        }
+
+        let (periods, closures) = periods.unwrap();
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let start_date = match periods.last() {
+            Some(period) => period.hab_per_end_date + chrono::Duration::days(1),
+            None => {
+                let start_dates = self.get_pending_start_dates(&mut conn, habits);
+
+                if start_dates.is_err() {
+                    return Err(start_dates.err().unwrap());
+                }
+
+                start_dates.unwrap()[0]
+            }
+        };
+
+        let end_date = (cut_date - chrono::Duration::days(1))
+            .min(habit.hab_end_date.unwrap_or(MAXIMUM_DATE.unwrap()));
+
+        if start_date > end_date {
+            return Ok((periods, None));
+        }
+
+        let data = HabitDataCollected::belonging_to(habit)
+            .select(HabitDataCollected::as_select())
+            .filter(habit_data_collected::hab_dat_collected_at.ge(start_date))
+            .filter(habit_data_collected::hab_dat_collected_at.le(end_date))
+            .load::<HabitDataCollected>(&mut conn);
+
+        if data.is_err() {
+            return Err(Error::QueryError(data.err().unwrap()));
+        }
+
+        let data = data.unwrap();
+        let data: Vec<&HabitDataCollected> = data.iter().collect();
+
+        let versions = self.get_habits_versions(habits);
+
+        if versions.is_err() {
+            return Err(versions.err().unwrap());
+        }
+
+        let versions = versions.unwrap();
+        let version = get_prorated_version(
+            get_habit_version_at(&versions[0], end_date),
+            (end_date - start_date).num_days() + 1,
+            (closures[0].next_closure_date - start_date).num_days(),
+        );
+        let amount = aggregate_period_amount(&version, &data);
+
+        let period = HabitPeriod {
+            hab_per_id: Uuid::new_v4(),
+            hab_per_start_date: start_date,
+            hab_per_end_date: end_date,
+            hab_per_is_met: is_goal_met(&version, &amount),
+            hab_per_amount: amount,
+            hab_per_goal: version.hab_goal.clone(),
+            hab_per_closed_at: closed_at,
+            hab_id: habit.hab_id,
+        };
+
+        Ok((periods, Some(period)))
+    }
+
+    // Aggregate the data of a habit within each of the given periods (bounds are inclusive)
+    pub fn get_habit_period_amounts(
+        &self,
+        habit: &Habit,
+        bounds: &[(chrono::NaiveDate, chrono::NaiveDate)],
+    ) -> Result<Vec<PeriodAmount>, Error> {
+        if bounds.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let (start_dates, end_dates): (Vec<chrono::NaiveDate>, Vec<chrono::NaiveDate>) =
+            bounds.iter().copied().unzip();
+
+        // Periods are aggregated as a whole with the version in effect when they end. Y/N habits
+        // count the days logged, periods without data amount to 0
+        let version_join = get_goal_version_join_sql("p.end_date");
+        let query = diesel::sql_query(format!(
+            "SELECT p.start_date, COALESCE({AGGREGATED_AMOUNT_SQL}, 0) AS amount \
+             FROM unnest($1, $2) AS p(start_date, end_date) \
+             INNER JOIN habit h ON h.hab_id = $3 \
+             LEFT JOIN habit_data_collected hd ON hd.hab_id = h.hab_id \
+                AND hd.hab_dat_collected_at >= p.start_date \
+                AND hd.hab_dat_collected_at <= p.end_date \
+             {version_join} \
+             GROUP BY p.start_date, h.hab_id \
+             ORDER BY p.start_date ASC",
+        ))
+        .bind::<Array<Date>, _>(start_dates)
+        .bind::<Array<Date>, _>(end_dates)
+        .bind::<SqlUuid, _>(habit.hab_id);
+
+        let amounts = query.load::<PeriodAmount>(&mut conn.unwrap());
+
+        if amounts.is_err() {
+            return Err(Error::QueryError(amounts.err().unwrap()));
+        }
+
+        Ok(amounts.unwrap())
+    }
+
+    // Current streak of each habit from its closed periods history, habits without any met
+    // period are left out
+    pub fn get_habits_streaks(&self, habit_ids: &[Uuid]) -> Result<Vec<HabitStreak>, Error> {
+        if habit_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        // Streak is made of the met periods after the last missed one
+        let query = diesel::sql_query(
+            "SELECT p.hab_id, COUNT(*) AS streak \
+             FROM habit_period p \
+             WHERE p.hab_id = ANY($1) \
+                AND p.hab_per_is_met \
+                AND p.hab_per_start_date > COALESCE( \
+                    ( \
+                        SELECT MAX(q.hab_per_start_date) FROM habit_period q \
+                        WHERE q.hab_id = p.hab_id AND NOT q.hab_per_is_met \
+                    ), \
+                    '-infinity'::date \
+                ) \
+             GROUP BY p.hab_id",
+        )
+        .bind::<Array<SqlUuid>, _>(habit_ids);
+
+        let streaks = query.load::<HabitStreak>(&mut conn.unwrap());
+
+        if streaks.is_err() {
+            return Err(Error::QueryError(streaks.err().unwrap()));
+        }
+
+        Ok(streaks.unwrap())
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::report_api_models::{Review, ReviewPeriod},
+        database::Habit,
+    },
+    schema::*,
+    utils::{
+        reports::{build_review, get_review_window},
+        MAX_PERIOD_DAYS,
+    },
+};
+
+use diesel::prelude::*;
+use uuid::Uuid;
+
+impl DBManager {
+    // Compare the week or month containing a date with the previous one, for every user habit
+    pub fn get_user_review(
+        &self,
+        user_id: String,
+        period: ReviewPeriod,
+        date: chrono::NaiveDate,
+        current_date: chrono::NaiveDate,
+    ) -> Result<Review, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let habits = habit::table
+            .select(Habit::as_select())
+            .filter(habit::usr_id.eq(user_id))
+            .order_by(habit::hab_name.asc())
+            .load::<Habit>(&mut conn.unwrap());
+
+        if habits.is_err() {
+            return Err(Error::QueryError(habits.err().unwrap()));
+        }
+
+        let habits = habits.unwrap();
+        let habit_ids: Vec<Uuid> = habits.iter().map(|habit| habit.hab_id).collect();
+
+        // Periods ending before the current window may start up to two periods before it
+        let (start_date, end_date) = get_review_window(period, date);
+        let daily_amounts = self.get_habits_daily_amounts(
+            &habit_ids,
+            start_date - chrono::Duration::days(2 * MAX_PERIOD_DAYS),
+            end_date,
+        );
+
+        if daily_amounts.is_err() {
+            return Err(daily_amounts.err().unwrap());
+        }
+
+        let versions = self.get_habits_versions(&habits);
+
+        if versions.is_err() {
+            return Err(versions.err().unwrap());
+        }
+
+        Ok(build_review(
+            &versions.unwrap(),
+            &daily_amounts.unwrap(),
+            period,
+            date,
+            current_date,
+        ))
+    }
+}
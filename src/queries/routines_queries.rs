@@ -0,0 +1,453 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{
+        api::{data_api_models::HabitDataCreateSchema, routine_api_models::*},
+        database::{Habit, HabitDataCollected, Routine, RoutineHabit},
+    },
+    schema::*,
+    utils::{periods::is_habit_active, routines::build_routine_progress, ROUTINE_HISTORY_DAYS},
+};
+
+use diesel::prelude::*;
+
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+// Rows placing each habit at its index within a routine
+fn build_routine_habits(rou_id: Uuid, habits: &[Uuid]) -> Vec<RoutineHabit> {
+    habits
+        .iter()
+        .enumerate()
+        .map(|(position, hab_id)| RoutineHabit {
+            rou_id,
+            hab_id: *hab_id,
+            rou_hab_position: position as i32,
+        })
+        .collect()
+}
+
+impl DBManager {
+    // Check if routine is accessible by user
+    pub fn is_routine_accessible_by_user(
+        &self,
+        user_id: String,
+        routine_id: Uuid,
+    ) -> Result<bool, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = routine::table
+            .select(routine::usr_id)
+            .filter(routine::rou_id.eq(routine_id))
+            .first::<String>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap() == user_id)
+    }
+
+    // Routines group habits of their owner, each at most once
+    fn check_routine_habits(&self, user_id: &str, habits: &[Uuid]) -> Result<(), Error> {
+        let mut unique = habits.to_vec();
+        unique.sort();
+        unique.dedup();
+
+        if unique.len() != habits.len() {
+            return Err(Error::BadRequest(
+                "A habit can only appear once in a routine".to_string(),
+            ));
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let owned = habit::table
+            .filter(habit::hab_id.eq_any(habits))
+            .filter(habit::usr_id.eq(user_id))
+            .count()
+            .get_result::<i64>(&mut conn.unwrap());
+
+        if owned.is_err() {
+            return Err(Error::QueryError(owned.err().unwrap()));
+        }
+
+        if owned.unwrap() != habits.len() as i64 {
+            return Err(Error::BadRequest(
+                "Routine habits must belong to the user".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Add a routine with its habits
+    pub fn add_routine(&self, user_id: String, data: RoutineCreateSchema) -> Result<Uuid, Error> {
+        let result = self.check_routine_habits(&user_id, &data.habits);
+
+        if result.is_err() {
+            return Err(result.err().unwrap());
+        }
+
+        let routine = Routine {
+            rou_id: Uuid::new_v4(),
+            rou_name: data.name,
+            rou_description: data.description,
+            rou_created_at: chrono::Local::now().naive_local(),
+            usr_id: user_id,
+        };
+
+        let routine_habits = build_routine_habits(routine.rou_id, &data.habits);
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = conn.unwrap().transaction(|conn| {
+            diesel::insert_into(routine::table)
+                .values(&routine)
+                .execute(conn)?;
+
+            diesel::insert_into(routine_habit::table)
+                .values(&routine_habits)
+                .execute(conn)?;
+
+            Ok::<Uuid, diesel::result::Error>(routine.rou_id)
+        });
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Get every routine of a user along with its habits
+    pub fn get_routines(&self, user_id: String) -> Result<Vec<RoutineWithHabits>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        let routines = routine::table
+            .select(Routine::as_select())
+            .filter(routine::usr_id.eq(user_id))
+            .order_by(routine::rou_created_at.asc())
+            .load::<Routine>(&mut conn);
+
+        if routines.is_err() {
+            return Err(Error::QueryError(routines.err().unwrap()));
+        }
+
+        let routines = routines.unwrap();
+
+        let routine_habits = RoutineHabit::belonging_to(&routines)
+            .select(RoutineHabit::as_select())
+            .order_by(routine_habit::rou_hab_position.asc())
+            .load::<RoutineHabit>(&mut conn);
+
+        if routine_habits.is_err() {
+            return Err(Error::QueryError(routine_habits.err().unwrap()));
+        }
+
+        let routine_habits = routine_habits.unwrap().grouped_by(&routines);
+
+        Ok(routines
+            .into_iter()
+            .zip(routine_habits)
+            .map(|(routine, habits)| RoutineWithHabits {
+                routine,
+                habits: habits.iter().map(|item| item.hab_id).collect(),
+            })
+            .collect())
+    }
+
+    // Get a routine along with its habits
+    pub fn get_routine(&self, id: Uuid) -> Result<RoutineWithHabits, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = routine::table
+            .select(Routine::as_select())
+            .find(id)
+            .first::<Routine>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        let habits = self.get_routine_habits(id);
+
+        if habits.is_err() {
+            return Err(habits.err().unwrap());
+        }
+
+        Ok(RoutineWithHabits {
+            routine: search.unwrap(),
+            habits: habits.unwrap().iter().map(|habit| habit.hab_id).collect(),
+        })
+    }
+
+    // Get the habits of a routine, in order
+    pub fn get_routine_habits(&self, id: Uuid) -> Result<Vec<Habit>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = routine_habit::table
+            .inner_join(habit::table)
+            .select(Habit::as_select())
+            .filter(routine_habit::rou_id.eq(id))
+            .order_by(routine_habit::rou_hab_position.asc())
+            .load::<Habit>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Update a routine, a new list of habits replaces the previous one and its order
+    pub fn update_routine(
+        &self,
+        id: Uuid,
+        user_id: String,
+        data: RoutineUpdateSchema,
+    ) -> Result<Uuid, Error> {
+        if let Some(habits) = &data.habits {
+            let result = self.check_routine_habits(&user_id, habits);
+
+            if result.is_err() {
+                return Err(result.err().unwrap());
+            }
+        }
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = conn.unwrap().transaction(|conn| {
+            if let Some(name) = &data.name {
+                diesel::update(routine::table.find(id))
+                    .set(routine::rou_name.eq(name))
+                    .execute(conn)?;
+            }
+
+            if let Some(description) = &data.description {
+                diesel::update(routine::table.find(id))
+                    .set(routine::rou_description.eq(description))
+                    .execute(conn)?;
+            }
+
+            if let Some(habits) = &data.habits {
+                diesel::delete(routine_habit::table.filter(routine_habit::rou_id.eq(id)))
+                    .execute(conn)?;
+
+                diesel::insert_into(routine_habit::table)
+                    .values(&build_routine_habits(id, habits))
+                    .execute(conn)?;
+            }
+
+            Ok::<Uuid, diesel::result::Error>(id)
+        });
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Delete a routine, its habits are kept
+    pub fn delete_routine(&self, id: Uuid) -> Result<Uuid, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = diesel::delete(routine::table.filter(routine::rou_id.eq(id)))
+            .execute(&mut conn.unwrap())
+            .map(|_| id);
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Log every habit of a routine running on the day, in a single transaction. Habits are
+    // logged once unless given an amount
+    pub fn complete_routine(
+        &self,
+        id: Uuid,
+        data: RoutineCompleteSchema,
+    ) -> Result<Vec<HabitDataCollected>, Error> {
+        let habits = self.get_routine_habits(id);
+
+        if habits.is_err() {
+            return Err(habits.err().unwrap());
+        }
+
+        let habits = habits.unwrap();
+
+        let amounts = data.amounts.unwrap_or_default();
+
+        if amounts
+            .iter()
+            .any(|item| !habits.iter().any(|habit| habit.hab_id == item.habit_id))
+        {
+            return Err(Error::BadRequest(
+                "Amounts can only be given for habits of the routine".to_string(),
+            ));
+        }
+
+        let collected_at = data
+            .collected_at
+            .unwrap_or_else(|| chrono::Utc::now().naive_utc().date());
+
+        let habit_ids: Vec<Uuid> = habits.iter().map(|habit| habit.hab_id).collect();
+
+        // Derived habits of the routine follow from the others
+        let derived_ids = self.get_derived_habit_ids(&habit_ids);
+
+        if derived_ids.is_err() {
+            return Err(derived_ids.err().unwrap());
+        }
+
+        let derived_ids = derived_ids.unwrap();
+
+        if amounts
+            .iter()
+            .any(|item| derived_ids.contains(&item.habit_id))
+        {
+            return Err(Error::BadRequest(
+                "Derived habits can't be logged directly".to_string(),
+            ));
+        }
+
+        // And habits with a checklist follow from their items
+        let checklist_ids = self.get_checklist_habit_ids(&habit_ids);
+
+        if checklist_ids.is_err() {
+            return Err(checklist_ids.err().unwrap());
+        }
+
+        let checklist_ids = checklist_ids.unwrap();
+
+        if amounts
+            .iter()
+            .any(|item| checklist_ids.contains(&item.habit_id))
+        {
+            return Err(Error::BadRequest(
+                "Habits with a checklist are logged by checking their items".to_string(),
+            ));
+        }
+
+        let habits_data: Vec<HabitDataCreateSchema> = habits
+            .iter()
+            .filter(|habit| !derived_ids.contains(&habit.hab_id))
+            .filter(|habit| !checklist_ids.contains(&habit.hab_id))
+            .filter(|habit| is_habit_active(habit, collected_at))
+            .map(|habit| HabitDataCreateSchema {
+                amount: amounts
+                    .iter()
+                    .find(|item| item.habit_id == habit.hab_id)
+                    .map(|item| item.amount.clone())
+                    .unwrap_or(BigDecimal::from(1)),
+                collected_at: Some(collected_at),
+                habit_id: habit.hab_id,
+                mood: data.mood,
+            })
+            .collect();
+
+        if habits_data.is_empty() {
+            return Err(Error::BadRequest(
+                "No habit of the routine is running on that day".to_string(),
+            ));
+        }
+
+        // Same as logging each habit on its own, dates can't go back before their last data
+        if data.collected_at.is_some() {
+            let last_date = self.get_last_habits_data_date(
+                &habits_data
+                    .iter()
+                    .map(|item| item.habit_id)
+                    .collect::<Vec<Uuid>>(),
+            );
+
+            if last_date.is_err() {
+                return Err(last_date.err().unwrap());
+            }
+
+            if last_date
+                .unwrap()
+                .is_some_and(|last_date| last_date > collected_at)
+            {
+                return Err(Error::BadRequest(
+                    "Requested date is before the last data of a habit of the routine".to_string(),
+                ));
+            }
+        }
+
+        self.add_habits_data(habits_data)
+    }
+
+    // Progress of a routine on a day, along with its streaks
+    pub fn get_routine_progress(
+        &self,
+        id: Uuid,
+        date: Option<NaiveDate>,
+    ) -> Result<RoutineProgress, Error> {
+        let date = date.unwrap_or_else(|| chrono::Local::now().naive_local().date());
+
+        let habits = self.get_routine_habits(id);
+
+        if habits.is_err() {
+            return Err(habits.err().unwrap());
+        }
+
+        let habits = habits.unwrap();
+        let habit_ids: Vec<Uuid> = habits.iter().map(|habit| habit.hab_id).collect();
+
+        let daily_amounts = self.get_habits_daily_amounts(
+            &habit_ids,
+            date - chrono::Duration::days(ROUTINE_HISTORY_DAYS),
+            date,
+        );
+
+        if daily_amounts.is_err() {
+            return Err(daily_amounts.err().unwrap());
+        }
+
+        Ok(build_routine_progress(
+            id,
+            &habits,
+            &daily_amounts.unwrap(),
+            date,
+        ))
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{db::DBManager, error::Error, models::database::HabitShare, schema::*};
+
+use diesel::prelude::*;
+
+use uuid::Uuid;
+
+impl DBManager {
+    // Create a new public token for a habit
+    pub fn add_habit_share(&self, hab_id: Uuid) -> Result<HabitShare, Error> {
+        let share = HabitShare {
+            hab_sha_token: Uuid::new_v4().simple().to_string(),
+            hab_sha_created_at: chrono::Utc::now().naive_utc(),
+            hab_id,
+        };
+
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let result = diesel::insert_into(habit_share::table)
+            .values(&share)
+            .execute(&mut conn.unwrap());
+
+        if result.is_err() {
+            return Err(Error::QueryError(result.err().unwrap()));
+        }
+
+        Ok(share)
+    }
+
+    // Get every public token of a habit
+    pub fn get_habit_shares(&self, hab_id: Uuid) -> Result<Vec<HabitShare>, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_share::table
+            .select(HabitShare::as_select())
+            .filter(habit_share::hab_id.eq(hab_id))
+            .order_by(habit_share::hab_sha_created_at.desc())
+            .load::<HabitShare>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Get a public token, NotFound when it doesn't exist (or was revoked)
+    pub fn get_habit_share(&self, token: String) -> Result<HabitShare, Error> {
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search = habit_share::table
+            .select(HabitShare::as_select())
+            .find(token)
+            .first::<HabitShare>(&mut conn.unwrap());
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+
+    // Revoke a public token
+    pub fn delete_habit_share(&self, token: String) -> Result<String, Error> {
+        let conn = self.get_write_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let search =
+            diesel::delete(habit_share::table.filter(habit_share::hab_sha_token.eq(&token)))
+                .execute(&mut conn.unwrap())
+                .map(|_| token);
+
+        if search.is_err() {
+            return Err(Error::QueryError(search.err().unwrap()));
+        }
+
+        Ok(search.unwrap())
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::{api::stats_api_models::*, database::Habit},
+    utils::{
+        forecast::build_period_forecast,
+        periods::{get_habit_reference_date, get_versioned_period_bounds, is_goal_met},
+        queries::{get_goal_version_join_sql, AGGREGATED_AMOUNT_SQL},
+        DEFAULT_MIN_OVERLAP, FORECAST_HISTORY_DAYS, MAX_CALENDAR_DAYS,
+    },
+};
+
+use chrono::Weekday;
+use diesel::{
+    prelude::*,
+    sql_types::{BigInt, Date, Text},
+};
+use uuid::Uuid;
+
+impl DBManager {
+    // Summarize a habit between two dates (by default, since the habit started until today)
+    pub fn get_habit_stats(
+        &self,
+        id: Uuid,
+        start_date: Option<chrono::NaiveDate>,
+        end_date: Option<chrono::NaiveDate>,
+    ) -> Result<HabitStats, Error> {
+        let habit = self.get_habit_by_id(id);
+
+        if habit.is_err() {
+            return Err(habit.err().unwrap());
+        }
+
+        let habit = habit.unwrap();
+
+        let current_date = chrono::Local::now().naive_local().date();
+        // Finished habits are looked at up to their end by default, and from their start as far as
+        // the longest range allowed
+        let end_date =
+            end_date.unwrap_or(current_date.min(habit.hab_end_date.unwrap_or(current_date)));
+        let start_date = start_date.unwrap_or(
+            get_habit_reference_date(&habit)
+                .max(end_date - chrono::Duration::days(MAX_CALENDAR_DAYS))
+                .min(end_date),
+        );
+
+        if end_date < start_date {
+            return Err(Error::BadRequest(
+                "End date must not be before start date".to_string(),
+            ));
+        }
+
+        if (end_date - start_date).num_days() > MAX_CALENDAR_DAYS {
+            return Err(Error::BadRequest(format!(
+                "Date range must not exceed {} days",
+                MAX_CALENDAR_DAYS
+            )));
+        }
+
+        let versions = self.get_habits_versions(std::slice::from_ref(&habit));
+
+        if versions.is_err() {
+            return Err(versions.err().unwrap());
+        }
+
+        let versions = versions.unwrap().pop().unwrap_or(vec![habit.clone()]);
+
+        // Completion only accounts for periods that are already over, each one judged against
+        // the goal in effect at the time
+        let (bounds, period_versions): (Vec<(chrono::NaiveDate, chrono::NaiveDate)>, Vec<Habit>) =
+            get_versioned_period_bounds(&versions, start_date, end_date)
+                .into_iter()
+                .filter(|(_, period_end, _)| period_end < &current_date)
+                .map(|(period_start, period_end, version)| ((period_start, period_end), version))
+                .unzip();
+
+        let amounts = self.get_habit_period_amounts(&habit, &bounds);
+
+        if amounts.is_err() {
+            return Err(amounts.err().unwrap());
+        }
+
+        let amounts = amounts.unwrap();
+
+        let periods_count = amounts.len() as i64;
+        let periods_met = amounts
+            .iter()
+            .zip(period_versions)
+            .filter(|(period, version)| is_goal_met(version, &period.amount))
+            .count() as i64;
+
+        let completion_rate = match periods_count {
+            0 => None,
+            _ => Some(periods_met as f64 / periods_count as f64),
+        };
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        // Days are aggregated first so several records on the same day count as one value
+        let version_join = get_goal_version_join_sql("hd.hab_dat_collected_at");
+        let query = diesel::sql_query(format!(
+            "WITH daily AS ( \
+                SELECT hd.hab_dat_collected_at AS date, {AGGREGATED_AMOUNT_SQL} AS amount \
+                FROM habit_data_collected hd \
+                INNER JOIN habit h ON h.hab_id = hd.hab_id \
+                {version_join} \
+                WHERE hd.hab_id = $1 \
+                    AND hd.hab_dat_collected_at >= $2 \
+                    AND hd.hab_dat_collected_at <= $3 \
+                GROUP BY 1, h.hab_id \
+             ), \
+             filled AS ( \
+                SELECT days.date, COALESCE(daily.amount, 0) AS amount \
+                FROM generate_series($2, $3, INTERVAL '1 day') AS days(date) \
+                LEFT JOIN daily ON daily.date = days.date::date \
+             ) \
+             SELECT COUNT(*) AS days_logged, \
+                COALESCE(SUM(amount), 0) AS total_amount, \
+                ROUND(AVG(amount), 2) AS mean_amount, \
+                ROUND(percentile_cont(0.5) WITHIN GROUP (ORDER BY amount)::numeric, 2) \
+                    AS median_amount, \
+                MIN(amount) AS min_amount, \
+                MAX(amount) AS max_amount, \
+                ( \
+                    SELECT EXTRACT(ISODOW FROM date)::int4 FROM daily \
+                    GROUP BY 1 ORDER BY SUM(amount) DESC, 1 ASC LIMIT 1 \
+                ) AS best_weekday, \
+                (SELECT regr_slope(amount::float8, (date::date - $2)::float8) FROM filled) \
+                    AS trend_slope \
+             FROM daily",
+        ))
+        .bind::<diesel::sql_types::Uuid, _>(id)
+        .bind::<Date, _>(start_date)
+        .bind::<Date, _>(end_date);
+
+        let summary = query.get_result::<HabitDataSummary>(&mut conn.unwrap());
+
+        if summary.is_err() {
+            return Err(Error::QueryError(summary.err().unwrap()));
+        }
+
+        let summary = summary.unwrap();
+
+        // PostgreSQL numbers weekdays from 1 (Monday) to 7 (Sunday)
+        let best_weekday = summary
+            .best_weekday
+            .and_then(|weekday| Weekday::try_from((weekday - 1) as u8).ok());
+
+        Ok(HabitStats {
+            hab_id: id,
+            start_date,
+            end_date,
+            periods_count,
+            periods_met,
+            completion_rate,
+            days_logged: summary.days_logged,
+            total_amount: summary.total_amount,
+            mean_amount: summary.mean_amount,
+            median_amount: summary.median_amount,
+            min_amount: summary.min_amount,
+            max_amount: summary.max_amount,
+            best_weekday,
+            trend_slope: summary.trend_slope,
+        })
+    }
+
+    // Pairwise correlation of the daily values of a user's habits (and with mood when asked)
+    pub fn get_habits_correlations(
+        &self,
+        user_id: String,
+        start_date: Option<chrono::NaiveDate>,
+        end_date: Option<chrono::NaiveDate>,
+        min_overlap: Option<i64>,
+        include_mood: bool,
+    ) -> Result<CorrelationAnalysis, Error> {
+        // By default, look at the last three months
+        let end_date = end_date.unwrap_or(chrono::Local::now().naive_local().date());
+        let start_date = start_date.unwrap_or(end_date - chrono::Duration::days(90));
+        let min_overlap = min_overlap.unwrap_or(DEFAULT_MIN_OVERLAP);
+
+        if end_date < start_date {
+            return Err(Error::BadRequest(
+                "End date must not be before start date".to_string(),
+            ));
+        }
+
+        if (end_date - start_date).num_days() > MAX_CALENDAR_DAYS {
+            return Err(Error::BadRequest(format!(
+                "Date range must not exceed {} days",
+                MAX_CALENDAR_DAYS
+            )));
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let mut conn = conn.unwrap();
+
+        // Every habit gets a value for each day since its creation, zero when nothing was logged
+        let version_join = get_goal_version_join_sql("hd.hab_dat_collected_at");
+        let series = format!(
+            "WITH daily AS ( \
+                SELECT h.hab_id, hd.hab_dat_collected_at AS date, \
+                    {AGGREGATED_AMOUNT_SQL} AS amount \
+                FROM habit_data_collected hd \
+                INNER JOIN habit h ON h.hab_id = hd.hab_id \
+                {version_join} \
+                WHERE h.usr_id = $1 \
+                    AND hd.hab_dat_collected_at >= $2 \
+                    AND hd.hab_dat_collected_at <= $3 \
+                GROUP BY 1, 2 \
+             ), \
+             series AS ( \
+                SELECT h.hab_id, days.date::date AS date, \
+                    COALESCE(daily.amount, 0)::float8 AS value, \
+                    daily.amount IS NOT NULL AS logged \
+                FROM habit h \
+                CROSS JOIN generate_series($2, $3, INTERVAL '1 day') AS days(date) \
+                LEFT JOIN daily ON daily.hab_id = h.hab_id AND daily.date = days.date::date \
+                WHERE h.usr_id = $1 \
+                    AND days.date::date >= COALESCE(h.hab_start_date, h.hab_created_at::date) \
+                    AND days.date::date <= COALESCE(h.hab_end_date, days.date::date) \
+             )"
+        );
+
+        let correlations = diesel::sql_query(format!(
+            "{} \
+             SELECT a.hab_id AS hab_id_a, b.hab_id AS hab_id_b, \
+                corr(a.value, b.value) AS correlation, \
+                COUNT(*) AS sample_size, \
+                COUNT(*) FILTER (WHERE a.logged AND b.logged) AS overlap \
+             FROM series a \
+             INNER JOIN series b ON b.date = a.date AND a.hab_id < b.hab_id \
+             GROUP BY 1, 2 \
+             HAVING COUNT(*) FILTER (WHERE a.logged AND b.logged) >= $4 \
+             ORDER BY abs(corr(a.value, b.value)) DESC NULLS LAST",
+            series
+        ))
+        .bind::<Text, _>(&user_id)
+        .bind::<Date, _>(start_date)
+        .bind::<Date, _>(end_date)
+        .bind::<BigInt, _>(min_overlap)
+        .load::<HabitCorrelation>(&mut conn);
+
+        if correlations.is_err() {
+            return Err(Error::QueryError(correlations.err().unwrap()));
+        }
+
+        let mut analysis = CorrelationAnalysis {
+            start_date,
+            end_date,
+            habits: correlations.unwrap(),
+            mood: None,
+        };
+
+        if !include_mood {
+            return Ok(analysis);
+        }
+
+        // Only days with a mood recorded take part, mood isn't zero filled
+        let mood = diesel::sql_query(format!(
+            "{}, \
+             mood AS ( \
+                SELECT hd.hab_dat_collected_at AS date, AVG(hd.hab_dat_mood)::float8 AS mood \
+                FROM habit_data_collected hd \
+                INNER JOIN habit h ON h.hab_id = hd.hab_id \
+                WHERE h.usr_id = $1 \
+                    AND hd.hab_dat_mood IS NOT NULL \
+                    AND hd.hab_dat_collected_at >= $2 \
+                    AND hd.hab_dat_collected_at <= $3 \
+                GROUP BY 1 \
+             ) \
+             SELECT series.hab_id, corr(series.value, mood.mood) AS correlation, \
+                COUNT(*) AS sample_size, \
+                COUNT(*) FILTER (WHERE series.logged) AS overlap \
+             FROM series \
+             INNER JOIN mood ON mood.date = series.date \
+             GROUP BY 1 \
+             HAVING COUNT(*) FILTER (WHERE series.logged) >= $4 \
+             ORDER BY abs(corr(series.value, mood.mood)) DESC NULLS LAST",
+            series
+        ))
+        .bind::<Text, _>(&user_id)
+        .bind::<Date, _>(start_date)
+        .bind::<Date, _>(end_date)
+        .bind::<BigInt, _>(min_overlap)
+        .load::<MoodCorrelation>(&mut conn);
+
+        if mood.is_err() {
+            return Err(Error::QueryError(mood.err().unwrap()));
+        }
+
+        analysis.mood = Some(mood.unwrap());
+
+        Ok(analysis)
+    }
+
+    // Completion of a habit with an end date, from its start to its end
+    pub fn get_habit_summary(
+        &self,
+        habit: &Habit,
+        current_date: chrono::NaiveDate,
+    ) -> Result<HabitSummary, Error> {
+        if habit.hab_end_date.is_none() {
+            return Err(Error::BadRequest("Habit has no end date".to_string()));
+        }
+
+        let start_date = get_habit_reference_date(habit);
+        let end_date = habit.hab_end_date.unwrap();
+
+        let stats = self.get_habit_stats(habit.hab_id, Some(start_date), Some(end_date));
+
+        if stats.is_err() {
+            return Err(stats.err().unwrap());
+        }
+
+        Ok(HabitSummary {
+            hab_id: habit.hab_id,
+            hab_name: habit.hab_name.clone(),
+            start_date,
+            end_date,
+            days: (end_date - start_date).num_days() + 1,
+            is_finished: end_date < current_date,
+            stats: stats.unwrap(),
+        })
+    }
+
+    // Forecast the current period of habits, habits that haven't started yet are left out
+    pub fn get_habits_forecasts(
+        &self,
+        habits: &[Habit],
+        current_date: chrono::NaiveDate,
+    ) -> Result<Vec<PeriodForecast>, Error> {
+        let versions = self.get_habits_versions(habits);
+
+        if versions.is_err() {
+            return Err(versions.err().unwrap());
+        }
+
+        let versions = versions.unwrap();
+
+        let start_date = versions
+            .iter()
+            .filter_map(|versions| {
+                get_versioned_period_bounds(versions, current_date, current_date)
+                    .first()
+                    .map(|(period_start, _, _)| *period_start)
+            })
+            .min();
+
+        // No habit has a current period
+        if start_date.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let habit_ids: Vec<Uuid> = habits.iter().map(|habit| habit.hab_id).collect();
+
+        // Past weeks are needed to know how each weekday usually goes
+        let daily_amounts = self.get_habits_daily_amounts(
+            &habit_ids,
+            start_date.unwrap() - chrono::Duration::days(FORECAST_HISTORY_DAYS),
+            current_date,
+        );
+
+        if daily_amounts.is_err() {
+            return Err(daily_amounts.err().unwrap());
+        }
+
+        let daily_amounts = daily_amounts.unwrap();
+
+        Ok(versions
+            .iter()
+            .filter_map(|versions| build_period_forecast(versions, &daily_amounts, current_date))
+            .collect())
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{
+    db::DBManager,
+    error::Error,
+    models::database::{Habit, HabitGoalVersion},
+    schema::*,
+    utils::periods::get_habit_versions,
+};
+
+use diesel::prelude::*;
+
+impl DBManager {
+    // Every goal version of each habit (oldest first), in the same order as the habits
+    pub fn get_habits_versions(&self, habits: &[Habit]) -> Result<Vec<Vec<Habit>>, Error> {
+        if habits.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.get_read_connection();
+
+        if conn.is_err() {
+            return Err(conn.err().unwrap());
+        }
+
+        let versions = HabitGoalVersion::belonging_to(habits)
+            .select(HabitGoalVersion::as_select())
+            .order_by(habit_goal_version::hab_ver_valid_from.asc())
+            .load::<HabitGoalVersion>(&mut conn.unwrap());
+
+        if versions.is_err() {
+            return Err(Error::QueryError(versions.err().unwrap()));
+        }
+
+        let versions = versions.unwrap().grouped_by(habits);
+
+        Ok(habits
+            .iter()
+            .zip(versions.iter())
+            .map(|(habit, versions)| get_habit_versions(habit, versions))
+            .collect())
+    }
+}
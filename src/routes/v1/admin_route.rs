@@ -0,0 +1,44 @@
+use crate::{
+    db::PostgresPool,
+    handlers::admin_handler,
+    models::api::{
+        jobs_api_models::{JobRunParams, JobTriggerParams},
+        RangeParams,
+    },
+    utils::{with_authenticator, with_db_manager},
+};
+
+use warp::filters::BoxedFilter;
+use warp::Filter;
+use warp::Reply;
+
+pub fn get_routes(
+    pool_write: Option<PostgresPool>,
+    pool_read: Option<PostgresPool>,
+) -> BoxedFilter<(impl Reply,)> {
+    let base_jobs_route = warp::path("admin").and(warp::path("jobs"));
+
+    // Background jobs history
+    let get_job_runs = base_jobs_route
+        .and(warp::get())
+        .and(warp::path("runs"))
+        .and(warp::path::end())
+        .and(warp::query::<JobRunParams>())
+        .and(warp::query::<RangeParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(admin_handler::get_job_runs_handler);
+
+    // Manually trigger (or dry-run) a job
+    let trigger_job = base_jobs_route
+        .and(warp::post())
+        .and(warp::path::param::<String>())
+        .and(warp::path("run"))
+        .and(warp::path::end())
+        .and(warp::query::<JobTriggerParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(admin_handler::trigger_job_handler);
+
+    get_job_runs.or(trigger_job).boxed()
+}
@@ -0,0 +1,93 @@
+use crate::{
+    db::PostgresPool,
+    handlers::challenge_handler,
+    utils::{with_authenticator, with_db_manager},
+};
+
+use warp::filters::BoxedFilter;
+use warp::Filter;
+use warp::Reply;
+
+use uuid::Uuid;
+
+pub fn get_routes(
+    pool_write: Option<PostgresPool>,
+    pool_read: Option<PostgresPool>,
+) -> BoxedFilter<(impl Reply,)> {
+    let base_challenges_route = warp::path("challenges");
+
+    let create_challenge = base_challenges_route
+        .and(warp::post())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(challenge_handler::create_challenge_handler);
+
+    let get_challenge = base_challenges_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(challenge_handler::get_challenge_handler);
+
+    // Owner invites other users to private challenges
+    let create_challenge_invitation = base_challenges_route
+        .and(warp::post())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("invitations"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(challenge_handler::create_challenge_invitation_handler);
+
+    // Taking part in a challenge
+    let join_challenge = base_challenges_route
+        .and(warp::post())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("join"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(challenge_handler::join_challenge_handler);
+
+    let leave_challenge = base_challenges_route
+        .and(warp::post())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("leave"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(challenge_handler::leave_challenge_handler);
+
+    let update_challenge_privacy = base_challenges_route
+        .and(warp::patch())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("privacy"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(challenge_handler::update_challenge_privacy_handler);
+
+    let get_challenge_leaderboard = base_challenges_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("leaderboard"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(challenge_handler::get_challenge_leaderboard_handler);
+
+    create_challenge
+        .or(get_challenge)
+        .or(create_challenge_invitation)
+        .or(join_challenge)
+        .or(leave_challenge)
+        .or(update_challenge_privacy)
+        .or(get_challenge_leaderboard)
+        .boxed()
+}
@@ -0,0 +1,26 @@
+use crate::{
+    db::PostgresPool,
+    handlers::dashboard_handler,
+    utils::{with_authenticator, with_db_manager},
+};
+
+use warp::filters::BoxedFilter;
+use warp::Filter;
+use warp::Reply;
+
+pub fn get_routes(
+    pool_write: Option<PostgresPool>,
+    pool_read: Option<PostgresPool>,
+) -> BoxedFilter<(impl Reply,)> {
+    let base_dashboard_route = warp::path("dashboard");
+
+    // Current period summary of every user habit
+    let get_dashboard = base_dashboard_route
+        .and(warp::get())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(dashboard_handler::get_dashboard_handler);
+
+    get_dashboard.boxed()
+}
@@ -0,0 +1,34 @@
+use crate::{
+    db::PostgresPool,
+    handlers::digest_handler,
+    utils::{with_authenticator, with_db_manager},
+};
+
+use warp::filters::BoxedFilter;
+use warp::Filter;
+use warp::Reply;
+
+pub fn get_routes(
+    pool_write: Option<PostgresPool>,
+    pool_read: Option<PostgresPool>,
+) -> BoxedFilter<(impl Reply,)> {
+    let base_digest_route = warp::path("digest").and(warp::path("preferences"));
+
+    // Weekly email digest opt-in and send day
+    let get_digest_preference = base_digest_route
+        .and(warp::get())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(digest_handler::get_digest_preference_handler);
+
+    let update_digest_preference = base_digest_route
+        .and(warp::put())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(digest_handler::update_digest_preference_handler);
+
+    get_digest_preference.or(update_digest_preference).boxed()
+}
@@ -1,7 +1,10 @@
 use crate::{
     db::PostgresPool,
     handlers::events_handler,
-    models::api::{DateParams, RangeParams},
+    models::api::{
+        events_api_models::{CalendarGroupParams, HeatmapParams},
+        DateParams, RangeParams,
+    },
     utils::{with_authenticator, with_db_manager},
 };
 
@@ -33,18 +36,72 @@ pub fn get_routes(
     let get_calendar_events_by_habit = base_calendar_route
         .and(warp::path("habit"))
         .and(warp::path::param::<uuid::Uuid>())
+        .and(warp::query::<CalendarGroupParams>())
         .and(with_db_manager(pool_write.clone(), pool_read.clone()))
         .and(with_authenticator())
         .and_then(events_handler::get_data_by_habit_handler);
 
     let get_calendar_events_by_user = base_calendar_route
         .and(warp::path::end())
+        .and(warp::query::<CalendarGroupParams>())
         .and(with_db_manager(pool_write.clone(), pool_read.clone()))
         .and(with_authenticator())
         .and_then(events_handler::get_data_by_user_handler);
 
+    // Expected periods merged with collected data
+    let base_calendar_status_route = base_calendar_route.and(warp::path("status"));
+
+    let get_calendar_periods_by_habit = base_calendar_status_route
+        .and(warp::path("habit"))
+        .and(warp::path::param::<uuid::Uuid>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(events_handler::get_calendar_periods_by_habit_handler);
+
+    let get_calendar_periods_by_user = base_calendar_status_route
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(events_handler::get_calendar_periods_by_user_handler);
+
+    // Daily intensity over a year
+    let base_heatmap_route = base_events_route
+        .and(warp::get())
+        .and(warp::path("calendar"))
+        .and(warp::path("heatmap"));
+
+    let get_heatmap_by_habit = base_heatmap_route
+        .and(warp::path("habit"))
+        .and(warp::path::param::<uuid::Uuid>())
+        .and(warp::query::<HeatmapParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(events_handler::get_heatmap_by_habit_handler);
+
+    let get_heatmap_by_category = base_heatmap_route
+        .and(warp::path("category"))
+        .and(warp::path::param::<uuid::Uuid>())
+        .map(Some)
+        .and(warp::query::<HeatmapParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(events_handler::get_heatmap_by_user_handler);
+
+    let get_heatmap_by_user = base_heatmap_route
+        .and(warp::path::end())
+        .and(warp::any().map(|| None))
+        .and(warp::query::<HeatmapParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(events_handler::get_heatmap_by_user_handler);
+
     get_next_end_event_by_habit
         .or(get_calendar_events_by_habit)
         .or(get_calendar_events_by_user)
+        .or(get_calendar_periods_by_habit)
+        .or(get_calendar_periods_by_user)
+        .or(get_heatmap_by_habit)
+        .or(get_heatmap_by_category)
+        .or(get_heatmap_by_user)
         .boxed()
 }
@@ -1,7 +1,10 @@
 use crate::{
     db::PostgresPool,
-    handlers::habit_handler,
-    models::api::{DataIncludeParams, DateParams, RangeParams},
+    handlers::{habit_handler, stats_handler},
+    models::api::{
+        habit_api_models::HabitChecklistParams, stats_api_models::CorrelationParams,
+        DataIncludeParams, DateParams, RangeParams,
+    },
     utils::{with_authenticator, with_db_manager},
 };
 
@@ -99,6 +102,121 @@ pub fn get_routes(
         }))
         .and_then(habit_handler::get_habit_by_id_handler);
 
+    // Getting habit statistics
+    let get_habit_stats = base_habit_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("stats"))
+        .and(warp::path::end())
+        .and(warp::query::<DateParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(stats_handler::get_habit_stats_handler);
+
+    // Current period progress along with its forecast
+    let get_habit_progress = base_habit_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("progress"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(stats_handler::get_habit_progress_handler);
+
+    // Completion of a challenge over its whole run
+    let get_habit_summary = base_habit_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("summary"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(stats_handler::get_habit_summary_handler);
+
+    // Checklist sub-items and their daily checks
+    let get_habit_checklist = base_habit_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("checklist"))
+        .and(warp::path::end())
+        .and(warp::query::<HabitChecklistParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(habit_handler::get_habit_checklist_handler);
+
+    let update_habit_checklist = base_habit_route
+        .and(warp::put())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("checklist"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(habit_handler::update_habit_checklist_handler);
+
+    let set_habit_checklist_checks = base_habit_route
+        .and(warp::put())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("checklist"))
+        .and(warp::path("checks"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(habit_handler::set_habit_checklist_checks_handler);
+
+    // Habit stacking, the trigger habit prompts the habit once done
+    let set_habit_trigger = base_habit_route
+        .and(warp::put())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("trigger"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(habit_handler::set_habit_trigger_handler);
+
+    let get_habit_chain = base_habit_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("chain"))
+        .and(warp::path::end())
+        .and(warp::query::<DateParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(habit_handler::get_habit_chain_handler);
+
+    // Habits computed from other habits
+    let get_habit_derivation = base_habit_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("derivation"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(habit_handler::get_habit_derivation_handler);
+
+    let set_habit_derivation = base_habit_route
+        .and(warp::put())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("derivation"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(habit_handler::set_habit_derivation_handler);
+
+    // Comparing daily values of every user habit
+    let get_habits_correlations = base_habit_route
+        .and(warp::get())
+        .and(warp::path("correlations"))
+        .and(warp::path::end())
+        .and(warp::query::<DateParams>())
+        .and(warp::query::<CorrelationParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(stats_handler::get_habits_correlations_handler);
+
     create_habit
         .or(update_habit)
         .or(delete_habit)
@@ -107,5 +225,16 @@ pub fn get_routes(
         .or(get_habit_by_category)
         .or(get_habit_by_id)
         .or(get_habit_by_id_data)
+        .or(get_habit_stats)
+        .or(get_habit_progress)
+        .or(get_habit_summary)
+        .or(get_habits_correlations)
+        .or(get_habit_checklist)
+        .or(update_habit_checklist)
+        .or(set_habit_checklist_checks)
+        .or(set_habit_trigger)
+        .or(get_habit_chain)
+        .or(get_habit_derivation)
+        .or(set_habit_derivation)
         .boxed()
 }
@@ -1,8 +1,16 @@
+pub mod admin_route;
 pub mod category_route;
+pub mod challenges_route;
+pub mod dashboard_route;
+pub mod digest_route;
 pub mod events_route;
 pub mod habit_data_route;
 pub mod habits_route;
 pub mod ownership_route;
+pub mod periods_route;
+pub mod reports_route;
+pub mod routines_route;
+pub mod share_route;
 
 use crate::db::PostgresPool;
 use warp::filters::BoxedFilter;
@@ -35,5 +43,37 @@ pub fn get_routes(
         pool_write.clone(),
         pool_read.clone(),
     )))
+    .or(v1.and(periods_route::get_routes(
+        pool_write.clone(),
+        pool_read.clone(),
+    )))
+    .or(v1.and(admin_route::get_routes(
+        pool_write.clone(),
+        pool_read.clone(),
+    )))
+    .or(v1.and(dashboard_route::get_routes(
+        pool_write.clone(),
+        pool_read.clone(),
+    )))
+    .or(v1.and(share_route::get_routes(
+        pool_write.clone(),
+        pool_read.clone(),
+    )))
+    .or(v1.and(reports_route::get_routes(
+        pool_write.clone(),
+        pool_read.clone(),
+    )))
+    .or(v1.and(digest_route::get_routes(
+        pool_write.clone(),
+        pool_read.clone(),
+    )))
+    .or(v1.and(challenges_route::get_routes(
+        pool_write.clone(),
+        pool_read.clone(),
+    )))
+    .or(v1.and(routines_route::get_routes(
+        pool_write.clone(),
+        pool_read.clone(),
+    )))
     .boxed()
 }
@@ -0,0 +1,32 @@
+use crate::{
+    db::PostgresPool,
+    handlers::period_handler,
+    models::api::{DateParams, RangeParams},
+    utils::{with_authenticator, with_db_manager},
+};
+
+use warp::filters::BoxedFilter;
+use warp::Filter;
+use warp::Reply;
+
+use uuid::Uuid;
+
+pub fn get_routes(
+    pool_write: Option<PostgresPool>,
+    pool_read: Option<PostgresPool>,
+) -> BoxedFilter<(impl Reply,)> {
+    let base_periods_route = warp::path("periods");
+
+    // Querying closed periods history
+    let get_periods_by_habit = base_periods_route
+        .and(warp::get())
+        .and(warp::path("habit"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::query::<DateParams>())
+        .and(warp::query::<RangeParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(period_handler::get_periods_by_habit_handler);
+
+    get_periods_by_habit.boxed()
+}
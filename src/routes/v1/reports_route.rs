@@ -0,0 +1,29 @@
+use crate::{
+    db::PostgresPool,
+    handlers::report_handler,
+    models::api::report_api_models::ReviewParams,
+    utils::{with_authenticator, with_db_manager},
+};
+
+use warp::filters::BoxedFilter;
+use warp::Filter;
+use warp::Reply;
+
+pub fn get_routes(
+    pool_write: Option<PostgresPool>,
+    pool_read: Option<PostgresPool>,
+) -> BoxedFilter<(impl Reply,)> {
+    let base_reports_route = warp::path("reports");
+
+    // Current week / month compared with the previous one
+    let get_review = base_reports_route
+        .and(warp::get())
+        .and(warp::path("review"))
+        .and(warp::path::end())
+        .and(warp::query::<ReviewParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(report_handler::get_review_handler);
+
+    get_review.boxed()
+}
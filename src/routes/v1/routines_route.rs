@@ -0,0 +1,89 @@
+use crate::{
+    db::PostgresPool,
+    handlers::routine_handler,
+    models::api::routine_api_models::RoutineProgressParams,
+    utils::{with_authenticator, with_db_manager},
+};
+
+use warp::filters::BoxedFilter;
+use warp::Filter;
+use warp::Reply;
+
+use uuid::Uuid;
+
+pub fn get_routes(
+    pool_write: Option<PostgresPool>,
+    pool_read: Option<PostgresPool>,
+) -> BoxedFilter<(impl Reply,)> {
+    let base_routines_route = warp::path("routines");
+
+    let create_routine = base_routines_route
+        .and(warp::post())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(routine_handler::create_routine_handler);
+
+    let get_routines = base_routines_route
+        .and(warp::get())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(routine_handler::get_routines_handler);
+
+    let get_routine = base_routines_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(routine_handler::get_routine_handler);
+
+    let update_routine = base_routines_route
+        .and(warp::patch())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(routine_handler::update_routine_handler);
+
+    let delete_routine = base_routines_route
+        .and(warp::delete())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(routine_handler::delete_routine_handler);
+
+    // Logging every habit of the routine at once
+    let complete_routine = base_routines_route
+        .and(warp::post())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("complete"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and(warp::body::json())
+        .and_then(routine_handler::complete_routine_handler);
+
+    let get_routine_progress = base_routines_route
+        .and(warp::get())
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("progress"))
+        .and(warp::path::end())
+        .and(warp::query::<RoutineProgressParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(routine_handler::get_routine_progress_handler);
+
+    create_routine
+        .or(get_routines)
+        .or(get_routine)
+        .or(update_routine)
+        .or(delete_routine)
+        .or(complete_routine)
+        .or(get_routine_progress)
+        .boxed()
+}
@@ -0,0 +1,72 @@
+use crate::{
+    db::PostgresPool,
+    handlers::share_handler,
+    models::api::{share_api_models::ChartParams, DateParams},
+    utils::{with_authenticator, with_db_manager},
+};
+
+use warp::filters::BoxedFilter;
+use warp::Filter;
+use warp::Reply;
+
+use uuid::Uuid;
+
+pub fn get_routes(
+    pool_write: Option<PostgresPool>,
+    pool_read: Option<PostgresPool>,
+) -> BoxedFilter<(impl Reply,)> {
+    let base_share_route = warp::path("share");
+
+    // Managing share tokens of a habit
+    let create_habit_share = base_share_route
+        .and(warp::post())
+        .and(warp::path("habit"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(share_handler::create_habit_share_handler);
+
+    let get_habit_shares = base_share_route
+        .and(warp::get())
+        .and(warp::path("habit"))
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(share_handler::get_habit_shares_handler);
+
+    let delete_habit_share = base_share_route
+        .and(warp::delete())
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and(with_authenticator())
+        .and_then(share_handler::delete_habit_share_handler);
+
+    // Public embeds, the token is the only credential
+    let get_share_badge = base_share_route
+        .and(warp::get())
+        .and(warp::path::param::<String>())
+        .and(warp::path("badge.svg"))
+        .and(warp::path::end())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and_then(share_handler::get_share_badge_handler);
+
+    let get_share_chart = base_share_route
+        .and(warp::get())
+        .and(warp::path::param::<String>())
+        .and(warp::path("chart.svg"))
+        .and(warp::path::end())
+        .and(warp::query::<DateParams>())
+        .and(warp::query::<ChartParams>())
+        .and(with_db_manager(pool_write.clone(), pool_read.clone()))
+        .and_then(share_handler::get_share_chart_handler);
+
+    create_habit_share
+        .or(get_habit_shares)
+        .or(delete_habit_share)
+        .or(get_share_badge)
+        .or(get_share_chart)
+        .boxed()
+}
@@ -4,6 +4,26 @@ pub mod sql_types {
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "hab_freq_type_enum"))]
     pub struct HabFreqTypeEnum;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "hab_goal_direction_enum"))]
+    pub struct HabGoalDirectionEnum;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "hab_aggregation_enum"))]
+    pub struct HabAggregationEnum;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "chl_par_privacy_enum"))]
+    pub struct ChlParPrivacyEnum;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "hab_checklist_mode_enum"))]
+    pub struct HabChecklistModeEnum;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "hab_derivation_enum"))]
+    pub struct HabDerivationEnum;
 }
 
 diesel::table! {
@@ -17,6 +37,62 @@ diesel::table! {
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::HabFreqTypeEnum;
+    use super::sql_types::HabGoalDirectionEnum;
+    use super::sql_types::HabAggregationEnum;
+
+    challenge (chl_id) {
+        chl_id -> Uuid,
+        #[max_length = 255]
+        chl_name -> Varchar,
+        #[max_length = 255]
+        chl_description -> Varchar,
+        chl_created_at -> Timestamp,
+        chl_is_yn -> Bool,
+        #[max_length = 10]
+        chl_units -> Varchar,
+        chl_goal -> Numeric,
+        chl_goal_direction -> HabGoalDirectionEnum,
+        chl_freq_type -> HabFreqTypeEnum,
+        chl_aggregation -> HabAggregationEnum,
+        chl_start_date -> Date,
+        chl_end_date -> Date,
+        chl_is_public -> Bool,
+        #[max_length = 24]
+        usr_id -> Varchar,
+    }
+}
+
+diesel::table! {
+    challenge_invitation (chl_inv_id) {
+        chl_inv_id -> Uuid,
+        chl_inv_created_at -> Timestamp,
+        chl_id -> Uuid,
+        #[max_length = 24]
+        usr_id -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ChlParPrivacyEnum;
+
+    challenge_participant (chl_par_id) {
+        chl_par_id -> Uuid,
+        chl_par_joined_at -> Timestamp,
+        chl_par_privacy -> ChlParPrivacyEnum,
+        chl_id -> Uuid,
+        #[max_length = 24]
+        usr_id -> Varchar,
+        hab_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::HabFreqTypeEnum;
+    use super::sql_types::HabGoalDirectionEnum;
+    use super::sql_types::HabAggregationEnum;
+    use super::sql_types::HabChecklistModeEnum;
 
     habit (hab_id) {
         hab_id -> Uuid,
@@ -40,6 +116,32 @@ diesel::table! {
         cat_id -> Uuid,
         #[max_length = 256]
         hab_location -> Nullable<Varchar>,
+        hab_goal_direction -> HabGoalDirectionEnum,
+        hab_goal_tolerance -> Numeric,
+        hab_aggregation -> HabAggregationEnum,
+        hab_start_date -> Nullable<Date>,
+        hab_end_date -> Nullable<Date>,
+        hab_checklist_mode -> HabChecklistModeEnum,
+        hab_summary_sent_on -> Nullable<Date>,
+    }
+}
+
+diesel::table! {
+    habit_checklist_check (hab_chk_id, hab_chk_chk_date) {
+        hab_chk_id -> Uuid,
+        hab_chk_chk_date -> Date,
+        hab_chk_chk_checked_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    habit_checklist_item (hab_chk_id) {
+        hab_chk_id -> Uuid,
+        #[max_length = 255]
+        hab_chk_name -> Varchar,
+        hab_chk_position -> Int4,
+        hab_chk_created_at -> Timestamp,
+        hab_id -> Uuid,
     }
 }
 
@@ -49,14 +151,160 @@ diesel::table! {
         hab_dat_amount -> Numeric,
         hab_dat_collected_at -> Date,
         hab_id -> Uuid,
+        hab_dat_mood -> Nullable<Int2>,
+        hab_dat_created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    habit_dependency (hab_id) {
+        hab_id -> Uuid,
+        hab_dep_trigger_id -> Uuid,
+        hab_dep_created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::HabDerivationEnum;
+
+    habit_derivation (hab_id) {
+        hab_id -> Uuid,
+        hab_der_kind -> HabDerivationEnum,
+        hab_der_quorum -> Nullable<Int4>,
+        hab_der_created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    habit_derivation_source (hab_id, hab_src_id) {
+        hab_id -> Uuid,
+        hab_src_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::HabFreqTypeEnum;
+    use super::sql_types::HabGoalDirectionEnum;
+    use super::sql_types::HabAggregationEnum;
+
+    habit_goal_version (hab_ver_id) {
+        hab_ver_id -> Uuid,
+        hab_ver_valid_from -> Date,
+        hab_ver_goal -> Numeric,
+        hab_ver_goal_direction -> HabGoalDirectionEnum,
+        hab_ver_goal_tolerance -> Numeric,
+        hab_ver_freq_type -> HabFreqTypeEnum,
+        #[max_length = 10]
+        hab_ver_units -> Varchar,
+        hab_id -> Uuid,
+        hab_ver_aggregation -> HabAggregationEnum,
+        hab_ver_is_yn -> Bool,
+    }
+}
+
+diesel::table! {
+    habit_period (hab_per_id) {
+        hab_per_id -> Uuid,
+        hab_per_start_date -> Date,
+        hab_per_end_date -> Date,
+        hab_per_amount -> Numeric,
+        hab_per_goal -> Numeric,
+        hab_per_is_met -> Bool,
+        hab_per_closed_at -> Timestamp,
+        hab_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    habit_share (hab_sha_token) {
+        #[max_length = 64]
+        hab_sha_token -> Varchar,
+        hab_sha_created_at -> Timestamp,
+        hab_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    job_run (job_run_id) {
+        job_run_id -> Uuid,
+        #[max_length = 64]
+        job_run_name -> Varchar,
+        job_run_tick -> Timestamp,
+        #[max_length = 255]
+        job_run_replica -> Varchar,
+        job_run_started_at -> Timestamp,
+        job_run_finished_at -> Nullable<Timestamp>,
+        job_run_habits_processed -> Int4,
+        job_run_error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    routine (rou_id) {
+        rou_id -> Uuid,
+        #[max_length = 255]
+        rou_name -> Varchar,
+        #[max_length = 255]
+        rou_description -> Varchar,
+        rou_created_at -> Timestamp,
+        #[max_length = 24]
+        usr_id -> Varchar,
+    }
+}
+
+diesel::table! {
+    routine_habit (rou_id, hab_id) {
+        rou_id -> Uuid,
+        hab_id -> Uuid,
+        rou_hab_position -> Int4,
+    }
+}
+
+diesel::table! {
+    user_digest_preference (usr_id) {
+        #[max_length = 24]
+        usr_id -> Varchar,
+        usr_dig_enabled -> Bool,
+        usr_dig_weekday -> Int2,
+        usr_dig_last_sent_on -> Nullable<Date>,
+        usr_dig_updated_at -> Timestamp,
     }
 }
 
+diesel::joinable!(challenge_invitation -> challenge (chl_id));
+diesel::joinable!(challenge_participant -> challenge (chl_id));
+diesel::joinable!(challenge_participant -> habit (hab_id));
 diesel::joinable!(habit -> category (cat_id));
+diesel::joinable!(habit_checklist_check -> habit_checklist_item (hab_chk_id));
+diesel::joinable!(habit_checklist_item -> habit (hab_id));
 diesel::joinable!(habit_data_collected -> habit (hab_id));
+diesel::joinable!(habit_derivation -> habit (hab_id));
+diesel::joinable!(habit_derivation_source -> habit_derivation (hab_id));
+diesel::joinable!(habit_goal_version -> habit (hab_id));
+diesel::joinable!(habit_period -> habit (hab_id));
+diesel::joinable!(habit_share -> habit (hab_id));
+diesel::joinable!(routine_habit -> habit (hab_id));
+diesel::joinable!(routine_habit -> routine (rou_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     category,
+    challenge,
+    challenge_invitation,
+    challenge_participant,
     habit,
+    habit_checklist_check,
+    habit_checklist_item,
     habit_data_collected,
+    habit_dependency,
+    habit_derivation,
+    habit_derivation_source,
+    habit_goal_version,
+    habit_period,
+    habit_share,
+    job_run,
+    routine,
+    routine_habit,
+    user_digest_preference,
 );
@@ -71,6 +71,14 @@ pub fn seed_database(pool_write: PostgresPool) -> Result<(), String> {
             color: Faker.fake::<String>().chars().take(6).collect::<String>(),
             category: categories_ids[(i % categories_ids.len() as i32) as usize].clone(),
             location: Some(Faker.fake::<String>()),
+            goal_direction: None,
+            goal_tolerance: None,
+            aggregation: None,
+            start_date: None,
+            end_date: None,
+            checklist: None,
+            checklist_mode: None,
+            derivation: None,
         };
 
         let habit_id = manager.add_habit(user_id, habit);
@@ -101,6 +109,7 @@ pub fn seed_database(pool_write: PostgresPool) -> Result<(), String> {
                     + chrono::Duration::days((i - BASE_QUANTITY * 100 - 10) as i64),
             ),
             habit_id: habits_ids[(i % habits_ids.len() as i32) as usize].clone(),
+            mood: Some((1..6).fake::<i16>()),
         };
 
         let data_id = manager.add_habit_data(data);
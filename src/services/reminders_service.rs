@@ -1,9 +1,22 @@
 use ::reqwest::Client;
+use bigdecimal::ToPrimitive;
 use chrono::NaiveDate;
 
 use graphql_client::{reqwest::post_graphql, GraphQLQuery};
-
-use crate::models::database::Habit;
+use serde_derive::Serialize;
+
+use crate::{
+    models::{
+        api::{
+            report_api_models::Review,
+            stats_api_models::{HabitSummary, PeriodForecast},
+        },
+        database::{HabGoalDirectionEnum, Habit},
+    },
+    utils::{
+        periods::is_habit_active, reports::render_review_markdown, FORECAST_REMINDER_PROBABILITY,
+    },
+};
 
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -16,24 +29,181 @@ struct NotifyReminder;
 
 use notify_reminder::{NotificationQueueInsert, Variables as NotifyReminderVariables};
 
-pub async fn enqueue_reminders_service(habits: Vec<Habit>) -> Result<(), reqwest::Error> {
-    // Comunicate with gateway to enqueue reminders of habits
-    let gateway_url = std::env::var("GATEWAY_URL").unwrap_or("http://localhost:4000".to_string());
-    let client = Client::new();
+// Notification to be enqueued through the gateway
+#[derive(Debug, Serialize, Clone)]
+pub struct ReminderNotification {
+    pub title: String,
+    pub body: String,
+    pub init_date: NaiveDate,
+    pub user_id: String,
+    pub should_email: bool,
+}
 
-    let mut notifications: Vec<NotificationQueueInsert> = Vec::new();
+// Reminders sent when a habit restarts its period, limit habits have nothing to be reminded of
+// and neither have habits that aren't running
+pub fn build_reminder_notifications(
+    habits: &[Habit],
+    current_date: NaiveDate,
+) -> Vec<ReminderNotification> {
+    habits
+        .iter()
+        .filter(|habit| habit.hab_goal_direction != HabGoalDirectionEnum::at_most)
+        .filter(|habit| is_habit_active(habit, current_date))
+        .map(|habit| ReminderNotification {
+            title: format!("Reminder for habit {}", habit.hab_name),
+            body: "Your habit just restarted its period! Remember to do it today!".to_string(),
+            init_date: current_date,
+            user_id: habit.usr_id.clone(),
+            should_email: false,
+        })
+        .collect()
+}
 
-    let current_date: NaiveDate = chrono::Local::now().naive_local().date();
+// Reminders for habits unlikely to meet their goal, telling how much is still needed
+pub fn build_forecast_notifications(
+    habits: &[Habit],
+    forecasts: &[PeriodForecast],
+    current_date: NaiveDate,
+) -> Vec<ReminderNotification> {
+    forecasts
+        .iter()
+        .filter(|forecast| {
+            matches!(forecast.probability, Some(probability) if probability < FORECAST_REMINDER_PROBABILITY)
+                && forecast.remaining_days > 0
+        })
+        .filter_map(|forecast| {
+            let habit = habits.iter().find(|habit| habit.hab_id == forecast.hab_id)?;
+            let remaining = forecast.remaining_amount.to_f64().unwrap_or(0.0);
+
+            let is_last_day = forecast.period_end_date == current_date;
+
+            // Limits warn about what's left instead of asking for more
+            let (title, body) = match (habit.hab_goal_direction, is_last_day) {
+                (HabGoalDirectionEnum::at_most, _) if remaining <= 0.0 => (
+                    format!("Careful with {}", habit.hab_name),
+                    format!(
+                        "You reached your limit of {} {} until {}",
+                        habit.hab_goal, habit.hab_units, forecast.period_end_date
+                    ),
+                ),
+                (HabGoalDirectionEnum::at_most, _) => (
+                    format!("Careful with {}", habit.hab_name),
+                    format!(
+                        "Only {} {} left until {}",
+                        remaining, habit.hab_units, forecast.period_end_date
+                    ),
+                ),
+                (_, true) => (
+                    format!("Keep up with {}", habit.hab_name),
+                    format!("You need {} more {} today!", remaining, habit.hab_units),
+                ),
+                (_, false) => (
+                    format!("Keep up with {}", habit.hab_name),
+                    format!(
+                        "You need {} more {} by {} (about {:.1} a day)",
+                        remaining, habit.hab_units, forecast.period_end_date, forecast.needed_per_day
+                    ),
+                ),
+            };
+
+            Some(ReminderNotification {
+                title,
+                body,
+                init_date: current_date,
+                user_id: habit.usr_id.clone(),
+                should_email: false,
+            })
+        })
+        .collect()
+}
 
-    for habit in habits {
-        notifications.push(NotificationQueueInsert {
-            title: format!("Reminder for habit {}", habit.hab_name),
-            body: format!("Your habit just restarted its period! Remember to do it today!"),
-            init_date: current_date.to_string(),
-            user_id: habit.usr_id,
+// Completion summary of a challenge that just ended
+pub fn build_challenge_notification(
+    habit: &Habit,
+    summary: &HabitSummary,
+    current_date: NaiveDate,
+) -> ReminderNotification {
+    let completion = match summary.stats.completion_rate {
+        Some(rate) => format!(
+            "You met your goal in {} of {} periods ({:.0}%)",
+            summary.stats.periods_met,
+            summary.stats.periods_count,
+            rate * 100.0
+        ),
+        None => "There were no periods to complete".to_string(),
+    };
+
+    ReminderNotification {
+        title: format!("Challenge finished: {}", habit.hab_name),
+        body: format!(
+            "{} over {} days, from {} to {}, with {} {} in total",
+            completion,
+            summary.days,
+            summary.start_date,
+            summary.end_date,
+            summary.stats.total_amount.to_f64().unwrap_or(0.0),
+            habit.hab_units
+        ),
+        init_date: current_date,
+        user_id: habit.usr_id.clone(),
+        should_email: false,
+    }
+}
+
+// Prompts for the habits stacked on a habit that was just done
+pub fn build_dependency_notifications(
+    trigger: &Habit,
+    dependents: &[Habit],
+    current_date: NaiveDate,
+) -> Vec<ReminderNotification> {
+    dependents
+        .iter()
+        .map(|habit| ReminderNotification {
+            title: format!("Next up: {}", habit.hab_name),
+            body: format!(
+                "You just did {}, now is a good time for {}!",
+                trigger.hab_name, habit.hab_name
+            ),
+            init_date: current_date,
+            user_id: habit.usr_id.clone(),
             should_email: false,
-        });
+        })
+        .collect()
+}
+
+// Weekly digest, the only notification sent by email
+pub fn build_digest_notification(
+    user_id: String,
+    review: &Review,
+    current_date: NaiveDate,
+) -> ReminderNotification {
+    ReminderNotification {
+        title: format!(
+            "Your weekly review ({} to {})",
+            review.current_start_date, review.current_end_date
+        ),
+        body: render_review_markdown(review),
+        init_date: current_date,
+        user_id,
+        should_email: true,
     }
+}
+
+pub async fn enqueue_reminders_service(reminders: Vec<ReminderNotification>) -> Result<(), String> {
+    // Comunicate with gateway to enqueue reminders of habits
+    let gateway_url = std::env::var("GATEWAY_URL").unwrap_or("http://localhost:4000".to_string());
+    let client = Client::new();
+
+    let notifications: Vec<NotificationQueueInsert> = reminders
+        .into_iter()
+        .map(|reminder| NotificationQueueInsert {
+            title: reminder.title,
+            body: reminder.body,
+            init_date: reminder.init_date.to_string(),
+            user_id: reminder.user_id,
+            should_email: reminder.should_email,
+        })
+        .collect();
 
     let variables = NotifyReminderVariables {
         input: notifications,
@@ -41,29 +211,29 @@ pub async fn enqueue_reminders_service(habits: Vec<Habit>) -> Result<(), reqwest
 
     let response_body = post_graphql::<NotifyReminder, _>(&client, gateway_url, variables).await;
 
+    // Callers only record notifications as sent once the gateway took them
     if response_body.is_err() {
-        println!(
+        return Err(format!(
             "Error sending reminder notifications: {:?}",
-            response_body.err()
-        );
-        return Ok(());
+            response_body.err().unwrap()
+        ));
     }
 
     let response_body = response_body.unwrap();
 
     if let Some(errors) = response_body.errors {
-        for error in errors {
-            println!("Error sending reminder notifications: {:?}", error);
-        }
-
-        return Ok(());
+        return Err(format!(
+            "Error sending reminder notifications: {}",
+            errors
+                .iter()
+                .map(|error| error.message.clone())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
     }
 
-    let response_data = response_body.data;
-
-    if response_data.is_none() {
-        println!("Error sending reminder notifications: No data returned");
-        return Ok(());
+    if response_body.data.is_none() {
+        return Err("Error sending reminder notifications: No data returned".to_string());
     }
 
     Ok(())
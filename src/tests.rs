@@ -64,3 +64,2113 @@ async fn test_habit_query() {
 
     assert_eq!(value.status(), 401);
 }
+
+#[tokio::test]
+async fn test_habit_periods_query() {
+    let value = test::request()
+        .method("GET")
+        .path("/api/v1/periods/habit/4cf3e092-7d38-4c59-af9e-fbbf546299af")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 401);
+}
+
+#[tokio::test]
+async fn test_habit_stats_query() {
+    let value = test::request()
+        .method("GET")
+        .path("/api/v1/habits/4cf3e092-7d38-4c59-af9e-fbbf546299af/stats")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 401);
+}
+
+#[tokio::test]
+async fn test_dashboard_query() {
+    let value = test::request()
+        .method("GET")
+        .path("/api/v1/dashboard")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 401);
+}
+
+#[tokio::test]
+async fn test_digest_preference_wrong_weekday() {
+    let value = test::request()
+        .method("PUT")
+        .path("/api/v1/digest/preferences")
+        .json(&serde_json::json!({
+            "enabled": true,
+            "weekday": 8,
+        }))
+        .header("user_id", "test_user")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 400);
+}
+
+#[tokio::test]
+async fn test_habits_correlations_wrong_range() {
+    let value = test::request()
+        .method("GET")
+        .path("/api/v1/habits/correlations?start_date=2026-01-01&end_date=2027-06-01")
+        .header("user_id", "test_user")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 400);
+}
+
+#[tokio::test]
+async fn test_job_runs_query() {
+    set_test_admin_token();
+
+    // User ids don't make administrators, whatever they are
+    for (header, value) in [("user_id", "admin"), ("admin_token", "wrong_token")] {
+        let value = test::request()
+            .method("GET")
+            .path("/api/v1/admin/jobs/runs")
+            .header(header, value)
+            .reply(&crate::routes::get_routes(
+                Some(crate::db::create_pool_write().unwrap()),
+                None,
+            ))
+            .await;
+
+        assert_eq!(value.status(), 401);
+    }
+
+    let value = test::request()
+        .method("GET")
+        .path("/api/v1/admin/jobs/runs")
+        .header("admin_token", "test_admin_token")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 200);
+}
+
+// Administration requests carry the token shared with the gateway
+#[cfg(test)]
+fn set_test_admin_token() {
+    std::env::set_var("ADMIN_TOKEN", "test_admin_token");
+}
+
+#[tokio::test]
+async fn test_unknown_job_trigger() {
+    set_test_admin_token();
+
+    let value = test::request()
+        .method("POST")
+        .path("/api/v1/admin/jobs/unknown_job/run?dry_run=true")
+        .header("admin_token", "test_admin_token")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 400);
+}
+
+#[tokio::test]
+async fn test_future_job_trigger() {
+    set_test_admin_token();
+
+    let value = test::request()
+        .method("POST")
+        .path("/api/v1/admin/jobs/reminders_update/run?as_of=2999-01-01")
+        .header("admin_token", "test_admin_token")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 400);
+}
+
+#[test]
+fn test_job_lock() {
+    let manager = crate::db::DBManager::new(Some(crate::db::create_pool_write().unwrap()), None);
+
+    let job_lock = manager.lock_job("test_job_lock").unwrap();
+    assert!(job_lock.is_some());
+
+    // Runs of the same job exclude each other until the lock is dropped
+    assert!(manager.lock_job("test_job_lock").unwrap().is_none());
+    assert!(manager.lock_job("test_other_job_lock").unwrap().is_some());
+
+    drop(job_lock);
+    assert!(manager.lock_job("test_job_lock").unwrap().is_some());
+}
+
+#[test]
+fn test_scheduled_tick() {
+    use crate::jobs::get_scheduled_tick;
+    use chrono::TimeZone;
+
+    let at = |hour: u32, minute: u32, second: u32, milli: u32| {
+        chrono::Utc
+            .with_ymd_and_hms(2026, 1, 2, hour, minute, second)
+            .unwrap()
+            + chrono::Duration::milliseconds(milli as i64)
+    };
+
+    let tick = at(9, 0, 0, 0).naive_utc();
+
+    // Replicas firing on either side of the minute claim the same tick
+    assert_eq!(
+        get_scheduled_tick("0 0 9 * * *", at(8, 59, 59, 900)),
+        Some(tick)
+    );
+    assert_eq!(
+        get_scheduled_tick("0 0 9 * * *", at(9, 0, 0, 0)),
+        Some(tick)
+    );
+    assert_eq!(
+        get_scheduled_tick("0 0 9 * * *", at(9, 0, 1, 200)),
+        Some(tick)
+    );
+    assert_eq!(get_scheduled_tick("not a schedule", at(9, 0, 0, 0)), None);
+}
+
+#[tokio::test]
+async fn test_closure_date_parity_with_database() {
+    use crate::models::database::HabFreqTypeEnum;
+    use diesel::prelude::*;
+    use diesel::sql_types::Date;
+
+    #[derive(QueryableByName)]
+    struct ClosureDates {
+        #[diesel(sql_type = Date)]
+        prev_closure_date: chrono::NaiveDate,
+
+        #[diesel(sql_type = Date)]
+        next_closure_date: chrono::NaiveDate,
+    }
+
+    let mut conn = crate::db::create_pool_write().unwrap().get().unwrap();
+
+    let frequency_types = [
+        HabFreqTypeEnum::daily,
+        HabFreqTypeEnum::daily2,
+        HabFreqTypeEnum::weekly,
+        HabFreqTypeEnum::weekly2,
+        HabFreqTypeEnum::monthly,
+        HabFreqTypeEnum::monthly2,
+    ];
+
+    for frequency_type in frequency_types {
+        // Every day of several years, leap years and month ends included
+        let dates = diesel::sql_query(
+            "SELECT day::date AS prev_closure_date, \
+                get_next_closure_date($1, day::date) AS next_closure_date \
+             FROM generate_series('2019-01-01'::date, '2028-12-31'::date, '1 day') AS day",
+        )
+        .bind::<crate::schema::sql_types::HabFreqTypeEnum, _>(frequency_type)
+        .load::<ClosureDates>(&mut conn)
+        .unwrap();
+
+        assert_eq!(dates.len(), 3653);
+
+        for date in dates {
+            assert_eq!(
+                crate::utils::time::get_next_closure_date(frequency_type, date.prev_closure_date),
+                date.next_closure_date,
+                "{:?} closure after {}",
+                frequency_type,
+                date.prev_closure_date
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn test_next_closest_date_is_first_occurrence_on_or_after_start(
+        frequency_index in 0usize..6,
+        reference_offset in 0i64..15000,
+        start_offset in -400i64..4000,
+    ) {
+        use crate::utils::time::{get_next_closure_date, DateRange, MINIMUM_DATE};
+
+        let frequency_type = FREQUENCY_TYPES[frequency_index];
+        let reference_date = MINIMUM_DATE.unwrap() + chrono::Duration::days(reference_offset);
+        let start_date = reference_date + chrono::Duration::days(start_offset);
+
+        let closest_date = DateRange::get_next_closest_date(
+            frequency_type,
+            Some(start_date),
+            Some(reference_date),
+        );
+
+        // Walk the recurrence from its reference date up to the closest date
+        let mut previous_occurrence = None;
+        let mut occurrence = reference_date;
+
+        while occurrence < closest_date {
+            previous_occurrence = Some(occurrence);
+            occurrence = get_next_closure_date(frequency_type, occurrence);
+        }
+
+        // It is a true occurrence, on or after start date
+        proptest::prop_assert_eq!(occurrence, closest_date);
+        proptest::prop_assert!(closest_date >= start_date);
+
+        // And no occurrence was skipped between start date and it
+        if let Some(previous_occurrence) = previous_occurrence {
+            proptest::prop_assert!(previous_occurrence < start_date);
+        }
+    }
+
+    #[test]
+    fn test_date_range_yields_every_occurrence_in_range(
+        frequency_index in 0usize..6,
+        reference_offset in 0i64..15000,
+        start_offset in -400i64..4000,
+        range_days in 0i64..800,
+    ) {
+        use crate::utils::time::{get_next_closure_date, DateRange, MINIMUM_DATE};
+
+        let frequency_type = FREQUENCY_TYPES[frequency_index];
+        let reference_date = MINIMUM_DATE.unwrap() + chrono::Duration::days(reference_offset);
+        let start_date = reference_date + chrono::Duration::days(start_offset);
+        let end_date = start_date + chrono::Duration::days(range_days);
+
+        let dates: Vec<chrono::NaiveDate> =
+            DateRange::new(end_date, frequency_type, Some(start_date), Some(reference_date))
+                .collect();
+
+        // Every occurrence of the recurrence falling within the range, in order
+        let mut expected_dates = Vec::new();
+        let mut occurrence = reference_date;
+
+        while occurrence <= end_date {
+            if occurrence >= start_date {
+                expected_dates.push(occurrence);
+            }
+
+            occurrence = get_next_closure_date(frequency_type, occurrence);
+        }
+
+        proptest::prop_assert_eq!(dates, expected_dates);
+    }
+}
+
+#[cfg(test)]
+const FREQUENCY_TYPES: [crate::models::database::HabFreqTypeEnum; 6] = [
+    crate::models::database::HabFreqTypeEnum::daily,
+    crate::models::database::HabFreqTypeEnum::daily2,
+    crate::models::database::HabFreqTypeEnum::weekly,
+    crate::models::database::HabFreqTypeEnum::weekly2,
+    crate::models::database::HabFreqTypeEnum::monthly,
+    crate::models::database::HabFreqTypeEnum::monthly2,
+];
+
+#[test]
+fn test_next_closest_date_never_panics_near_maximum_date() {
+    use crate::utils::time::{DateRange, MAXIMUM_DATE};
+
+    for frequency_type in FREQUENCY_TYPES {
+        let reference_date = chrono::NaiveDate::MAX - chrono::Duration::days(20);
+
+        let closest_date = DateRange::get_next_closest_date(
+            frequency_type,
+            Some(chrono::NaiveDate::MAX),
+            Some(MAXIMUM_DATE.unwrap()),
+        );
+
+        assert!(closest_date >= MAXIMUM_DATE.unwrap());
+
+        // Stepping past the last representable date ends the iteration instead of panicking
+        let dates: Vec<chrono::NaiveDate> = DateRange::new(
+            chrono::NaiveDate::MAX,
+            frequency_type,
+            Some(reference_date),
+            Some(reference_date),
+        )
+        .collect();
+
+        assert!(!dates.is_empty());
+        assert_eq!(dates[0], reference_date);
+    }
+}
+
+#[cfg(test)]
+fn build_test_habit(
+    frequency_type: crate::models::database::HabFreqTypeEnum,
+    created_at: chrono::NaiveDate,
+    goal: i64,
+    is_yn: bool,
+) -> crate::models::database::Habit {
+    crate::models::database::Habit {
+        hab_id: uuid::Uuid::new_v4(),
+        hab_name: "Test habit".to_string(),
+        hab_description: "Test habit".to_string(),
+        hab_created_at: created_at.and_hms_opt(8, 0, 0).unwrap(),
+        hab_updated_at: created_at.and_hms_opt(8, 0, 0).unwrap(),
+        hab_is_favorite: false,
+        hab_is_yn: is_yn,
+        hab_color: "ffffff".to_string(),
+        hab_units: "times".to_string(),
+        hab_goal: bigdecimal::BigDecimal::from(goal),
+        hab_freq_type: frequency_type,
+        hab_next_closure_date: created_at,
+        usr_id: "test_user".to_string(),
+        cat_id: uuid::Uuid::new_v4(),
+        hab_location: None,
+        hab_goal_direction: crate::models::database::HabGoalDirectionEnum::at_least,
+        hab_goal_tolerance: bigdecimal::BigDecimal::from(0),
+        hab_aggregation: crate::models::database::HabAggregationEnum::sum,
+        hab_start_date: None,
+        hab_end_date: None,
+        hab_checklist_mode: crate::models::database::HabChecklistModeEnum::count,
+    }
+}
+
+// User of a single database test, everything it owns is deleted when it goes out of scope so
+// that failing tests don't leave rows behind either
+#[cfg(test)]
+struct TestUser {
+    usr_id: String,
+    category: uuid::Uuid,
+    manager: crate::db::DBManager,
+}
+
+#[cfg(test)]
+impl TestUser {
+    fn new() -> Self {
+        let manager =
+            crate::db::DBManager::new(Some(crate::db::create_pool_write().unwrap()), None);
+
+        // User ids are at most 24 characters long
+        let usr_id = format!("test_{}", &uuid::Uuid::new_v4().simple().to_string()[..19]);
+        let category = manager
+            .add_category(
+                crate::models::api::category_api_models::CategoryCreateSchema {
+                    name: format!("Test {}", usr_id),
+                },
+            )
+            .unwrap();
+
+        TestUser {
+            usr_id,
+            category,
+            manager,
+        }
+    }
+
+    fn insert_habit(
+        &self,
+        mut habit: crate::models::database::Habit,
+    ) -> crate::models::database::Habit {
+        use diesel::prelude::*;
+
+        habit.usr_id = self.usr_id.clone();
+        habit.cat_id = self.category;
+
+        diesel::insert_into(crate::schema::habit::table)
+            .values(&habit)
+            .execute(&mut self.manager.get_write_connection().unwrap())
+            .unwrap();
+
+        habit
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestUser {
+    fn drop(&mut self) {
+        use crate::schema::{category, challenge, habit, routine};
+        use diesel::prelude::*;
+
+        let Ok(mut conn) = self.manager.get_write_connection() else {
+            return;
+        };
+
+        // Habit data, periods, shares and the like go along with their habit
+        let _ = diesel::delete(routine::table.filter(routine::usr_id.eq(&self.usr_id)))
+            .execute(&mut conn);
+        let _ =
+            diesel::delete(habit::table.filter(habit::usr_id.eq(&self.usr_id))).execute(&mut conn);
+        let _ = diesel::delete(challenge::table.filter(challenge::usr_id.eq(&self.usr_id)))
+            .execute(&mut conn);
+        let _ = diesel::delete(category::table.find(self.category)).execute(&mut conn);
+    }
+}
+
+#[cfg(test)]
+fn build_test_data(
+    habit: &crate::models::database::Habit,
+    days: &[(u32, i64)],
+) -> Vec<crate::models::database::HabitDataCollected> {
+    days.iter()
+        .map(
+            |(day, amount)| crate::models::database::HabitDataCollected {
+                hab_dat_id: uuid::Uuid::new_v4(),
+                hab_dat_amount: bigdecimal::BigDecimal::from(*amount),
+                hab_dat_collected_at: chrono::NaiveDate::from_ymd_opt(2026, 1, *day).unwrap(),
+                hab_id: habit.hab_id,
+                hab_dat_mood: None,
+                hab_dat_created_at: chrono::NaiveDate::from_ymd_opt(2026, 1, *day)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+            },
+        )
+        .collect()
+}
+
+#[test]
+fn test_calendar_periods_status() {
+    use crate::models::api::events_api_models::PeriodStatus;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    let habit = build_test_habit(
+        crate::models::database::HabFreqTypeEnum::weekly,
+        date(5),
+        3,
+        false,
+    );
+    let data = build_test_data(&habit, &[(5, 2), (9, 1), (20, 1)]);
+
+    let periods =
+        crate::utils::periods::build_calendar_periods(&[habit], &data, date(1), date(31), date(21));
+
+    let statuses: Vec<(chrono::NaiveDate, chrono::NaiveDate, PeriodStatus)> = periods
+        .iter()
+        .map(|period| (period.start_date, period.end_date, period.status))
+        .collect();
+
+    assert_eq!(
+        statuses,
+        vec![
+            (date(1), date(4), PeriodStatus::Paused),
+            (date(5), date(11), PeriodStatus::Done),
+            (date(12), date(18), PeriodStatus::Missed),
+            (date(19), date(25), PeriodStatus::Partial),
+            (
+                date(26),
+                chrono::NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+                PeriodStatus::Upcoming
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_limit_habit_periods_status() {
+    use crate::models::api::events_api_models::PeriodStatus;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    let mut habit = build_test_habit(
+        crate::models::database::HabFreqTypeEnum::weekly,
+        date(5),
+        2,
+        false,
+    );
+    habit.hab_goal_direction = crate::models::database::HabGoalDirectionEnum::at_most;
+
+    let data = build_test_data(&habit, &[(5, 2), (9, 1), (20, 1)]);
+
+    let periods = crate::utils::periods::build_calendar_periods(
+        std::slice::from_ref(&habit),
+        &data,
+        date(5),
+        date(25),
+        date(21),
+    );
+
+    let statuses: Vec<PeriodStatus> = periods.iter().map(|period| period.status).collect();
+
+    // Going over the limit fails, staying under it only succeeds once the period is over
+    assert_eq!(
+        statuses,
+        vec![
+            PeriodStatus::Missed,
+            PeriodStatus::Done,
+            PeriodStatus::Partial
+        ]
+    );
+
+    let notifications =
+        crate::services::reminders_service::build_reminder_notifications(&[habit], date(19));
+
+    assert!(notifications.is_empty());
+}
+
+#[test]
+fn test_period_aggregation_modes() {
+    use crate::models::{api::events_api_models::HabitDailyAmount, database::HabAggregationEnum};
+    use crate::utils::periods::{aggregate_daily_amounts, aggregate_period_amount};
+
+    let mut habit = build_test_habit(
+        crate::models::database::HabFreqTypeEnum::weekly,
+        chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(),
+        70,
+        false,
+    );
+
+    let data = build_test_data(&habit, &[(9, 71), (5, 70), (6, 72), (6, 71)]);
+    let data: Vec<&crate::models::database::HabitDataCollected> = data.iter().collect();
+
+    let expected = [
+        (HabAggregationEnum::sum, 284),
+        (HabAggregationEnum::last, 71),
+        (HabAggregationEnum::average, 71),
+        (HabAggregationEnum::min, 70),
+        (HabAggregationEnum::max, 72),
+    ];
+
+    for (aggregation, amount) in expected {
+        habit.hab_aggregation = aggregation;
+
+        assert_eq!(
+            aggregate_period_amount(&habit, &data),
+            bigdecimal::BigDecimal::from(amount)
+        );
+    }
+
+    // Same day records go by when they were logged, whatever order they come in
+    habit.hab_aggregation = HabAggregationEnum::last;
+
+    let mut data = build_test_data(&habit, &[(6, 75), (6, 74)]);
+    data[0].hab_dat_created_at += chrono::Duration::minutes(5);
+    let data: Vec<&crate::models::database::HabitDataCollected> = data.iter().rev().collect();
+
+    assert_eq!(
+        aggregate_period_amount(&habit, &data),
+        bigdecimal::BigDecimal::from(75)
+    );
+
+    // Daily averages are weighted by their records
+    habit.hab_aggregation = HabAggregationEnum::average;
+
+    let daily_amounts: Vec<HabitDailyAmount> = [(5, 70, 2), (6, 73, 1)]
+        .iter()
+        .map(|(day, amount, records)| HabitDailyAmount {
+            hab_id: habit.hab_id,
+            date: chrono::NaiveDate::from_ymd_opt(2026, 1, *day).unwrap(),
+            amount: bigdecimal::BigDecimal::from(*amount),
+            records: *records,
+        })
+        .collect();
+    let daily_amounts: Vec<&HabitDailyAmount> = daily_amounts.iter().collect();
+
+    assert_eq!(
+        aggregate_daily_amounts(&habit, &daily_amounts),
+        bigdecimal::BigDecimal::from(71)
+    );
+}
+
+#[test]
+fn test_versioned_period_bounds() {
+    use crate::models::database::HabFreqTypeEnum;
+    use crate::utils::periods::{
+        get_goal_version, get_habit_versions, get_versioned_period_bounds,
+    };
+    use bigdecimal::ToPrimitive;
+    use chrono::Datelike;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Three times a week from January 5th, then once a day from January 14th
+    let mut habit = build_test_habit(HabFreqTypeEnum::weekly, date(5), 3, false);
+    let first = get_goal_version(&habit, date(5));
+
+    habit.hab_freq_type = HabFreqTypeEnum::daily;
+    habit.hab_goal = bigdecimal::BigDecimal::from(1);
+    habit.hab_aggregation = crate::models::database::HabAggregationEnum::max;
+
+    let second = get_goal_version(&habit, date(14));
+    let versions = get_habit_versions(&habit, &[second, first]);
+
+    // Earlier periods keep counting their amounts the way they used to
+    assert_eq!(
+        versions[0].hab_aggregation,
+        crate::models::database::HabAggregationEnum::sum
+    );
+
+    let bounds: Vec<(u32, u32, f64)> = get_versioned_period_bounds(&versions, date(5), date(15))
+        .iter()
+        .map(|(start, end, version)| {
+            (
+                start.day(),
+                end.day(),
+                version.hab_goal.to_f64().unwrap_or(0.0),
+            )
+        })
+        .collect();
+
+    // Week cut short by the new frequency only asks for its share of the goal
+    assert_eq!(
+        bounds,
+        vec![(5, 11, 3.0), (12, 13, 0.86), (14, 14, 1.0), (15, 15, 1.0)]
+    );
+
+    // Changes keeping the frequency don't cut the week, it ends under the latest goal
+    let mut habit = build_test_habit(HabFreqTypeEnum::weekly, date(5), 3, false);
+    let first = get_goal_version(&habit, date(5));
+
+    habit.hab_goal = bigdecimal::BigDecimal::from(4);
+
+    let second = get_goal_version(&habit, date(14));
+    let versions = get_habit_versions(&habit, &[first, second]);
+
+    let bounds: Vec<(u32, u32, f64)> = get_versioned_period_bounds(&versions, date(5), date(15))
+        .iter()
+        .map(|(start, end, version)| {
+            (
+                start.day(),
+                end.day(),
+                version.hab_goal.to_f64().unwrap_or(0.0),
+            )
+        })
+        .collect();
+
+    assert_eq!(bounds, vec![(5, 11, 3.0), (12, 18, 4.0)]);
+}
+
+#[test]
+fn test_frequency_change_closure_date() {
+    use crate::models::database::HabFreqTypeEnum;
+    use crate::utils::periods::{
+        get_goal_version, get_habit_versions, get_versioned_period_bounds,
+    };
+    use crate::utils::time::DateRange;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Closure date set on a frequency change ends the first period of the new version
+    for frequency_type in FREQUENCY_TYPES {
+        if frequency_type == HabFreqTypeEnum::weekly {
+            continue;
+        }
+
+        let mut habit = build_test_habit(HabFreqTypeEnum::weekly, date(5), 3, false);
+        let first = get_goal_version(&habit, date(5));
+
+        habit.hab_freq_type = frequency_type;
+
+        let second = get_goal_version(&habit, date(14));
+        let versions = get_habit_versions(&habit, &[first, second]);
+
+        let bounds = get_versioned_period_bounds(&versions, date(13), date(14));
+        let closure_date =
+            DateRange::get_next_closest_date(frequency_type, Some(date(15)), Some(date(14)));
+
+        assert_eq!(bounds.len(), 2);
+        assert_eq!((bounds[0].0, bounds[0].1), (date(12), date(13)));
+        assert_eq!(bounds[1].0, date(14));
+        assert_eq!(bounds[1].1 + chrono::Duration::days(1), closure_date);
+    }
+}
+
+#[test]
+fn test_challenge_periods() {
+    use crate::models::api::events_api_models::PeriodStatus;
+    use crate::utils::periods::{build_calendar_periods, is_habit_active};
+    use chrono::Datelike;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Two weeks challenge created on January 1st, scheduled from Wednesday 7th to the 20th
+    let mut habit = build_test_habit(
+        crate::models::database::HabFreqTypeEnum::weekly,
+        date(1),
+        3,
+        false,
+    );
+    habit.hab_start_date = Some(date(7));
+    habit.hab_end_date = Some(date(20));
+
+    let data = build_test_data(&habit, &[(8, 3), (15, 1), (22, 5)]);
+
+    let periods = build_calendar_periods(
+        std::slice::from_ref(&habit),
+        &data,
+        date(1),
+        date(31),
+        date(25),
+    );
+
+    let statuses: Vec<(u32, u32, PeriodStatus)> = periods
+        .iter()
+        .map(|period| {
+            (
+                period.start_date.day(),
+                period.end_date.day(),
+                period.status,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        statuses,
+        vec![
+            (1, 6, PeriodStatus::Paused),
+            (7, 13, PeriodStatus::Done),
+            (14, 20, PeriodStatus::Partial),
+            (21, 31, PeriodStatus::Paused),
+        ]
+    );
+
+    assert!(!is_habit_active(&habit, date(6)));
+    assert!(is_habit_active(&habit, date(20)));
+    assert!(!is_habit_active(&habit, date(21)));
+
+    // No reminders once the challenge is over
+    let notifications =
+        crate::services::reminders_service::build_reminder_notifications(&[habit], date(21));
+
+    assert!(notifications.is_empty());
+}
+
+#[tokio::test]
+async fn test_calendar_wrong_grouping() {
+    let value = test::request()
+        .method("GET")
+        .path("/api/v1/events/calendar?group_by=year")
+        .header("user_id", "test_user")
+        .reply(&crate::routes::get_routes(
+            Some(crate::db::create_pool_write().unwrap()),
+            None,
+        ))
+        .await;
+
+    assert_eq!(value.status(), 400);
+}
+
+#[test]
+fn test_heatmap_days() {
+    use crate::models::api::events_api_models::{HabitDailyAmount, HeatmapDayStatus};
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    let habit = build_test_habit(
+        crate::models::database::HabFreqTypeEnum::daily,
+        date(2),
+        4,
+        false,
+    );
+    let daily_amounts: Vec<HabitDailyAmount> = [(2, 2), (3, 8)]
+        .iter()
+        .map(|(day, amount)| HabitDailyAmount {
+            hab_id: habit.hab_id,
+            date: date(*day),
+            amount: bigdecimal::BigDecimal::from(*amount),
+            records: 1,
+        })
+        .collect();
+
+    let days = crate::utils::periods::build_heatmap_days(
+        &[vec![habit]],
+        &daily_amounts,
+        date(1),
+        date(5),
+        date(4),
+    );
+
+    let days: Vec<(f64, HeatmapDayStatus)> =
+        days.iter().map(|day| (day.value, day.status)).collect();
+
+    assert_eq!(
+        days,
+        vec![
+            (0.0, HeatmapDayStatus::Paused),
+            (0.5, HeatmapDayStatus::Active),
+            (1.0, HeatmapDayStatus::Active),
+            (0.0, HeatmapDayStatus::Active),
+            (0.0, HeatmapDayStatus::Upcoming),
+        ]
+    );
+}
+
+#[test]
+fn test_period_forecast() {
+    use crate::models::api::events_api_models::HabitDailyAmount;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    let habit = build_test_habit(
+        crate::models::database::HabFreqTypeEnum::weekly,
+        date(5),
+        3,
+        false,
+    );
+
+    // Mondays and Wednesdays in past weeks, only Monday so far in the current one
+    let daily_amounts: Vec<HabitDailyAmount> = [5, 7, 12, 14, 19]
+        .iter()
+        .map(|day| HabitDailyAmount {
+            hab_id: habit.hab_id,
+            date: date(*day),
+            amount: bigdecimal::BigDecimal::from(1),
+            records: 1,
+        })
+        .collect();
+
+    let forecast = crate::utils::forecast::build_period_forecast(
+        std::slice::from_ref(&habit),
+        &daily_amounts,
+        date(21),
+    )
+    .unwrap();
+
+    assert_eq!(forecast.period_start_date, date(19));
+    assert_eq!(forecast.period_end_date, date(25));
+    assert_eq!(forecast.remaining_amount, bigdecimal::BigDecimal::from(2));
+    assert_eq!(forecast.remaining_days, 5);
+    assert_eq!(forecast.needed_per_day, 0.4);
+    assert_eq!(forecast.expected_amount, 2.75);
+
+    // Past weeks always went the same, the uneven pace of this one still leaves some chance
+    assert!(forecast
+        .probability
+        .is_some_and(|probability| (probability - 0.33).abs() < 0.01));
+
+    let notifications = crate::services::reminders_service::build_forecast_notifications(
+        &[habit],
+        &[forecast],
+        date(21),
+    );
+
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(
+        notifications[0].body,
+        "You need 2 more times by 2026-01-25 (about 0.4 a day)"
+    );
+}
+
+#[test]
+fn test_svg_badge_escapes_habit_name() {
+    let svg = crate::utils::svg::render_badge(
+        "Read <b>&</b> write",
+        "streak 3 | 75%",
+        &crate::utils::svg::get_svg_color("not a color"),
+    );
+
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains("Read &lt;b&gt;&amp;&lt;/b&gt; write"));
+    assert!(!svg.contains("<b>"));
+    assert!(svg.contains("fill=\"#4c1\""));
+}
+
+#[test]
+fn test_weekly_review() {
+    use crate::models::api::{
+        events_api_models::HabitDailyAmount,
+        report_api_models::{ReviewPeriod, ReviewStreakChange},
+    };
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Weeks of January 5th and 12th 2026 (Monday to Sunday)
+    let habit = build_test_habit(
+        crate::models::database::HabFreqTypeEnum::daily,
+        date(1),
+        1,
+        false,
+    );
+    let daily_amounts: Vec<HabitDailyAmount> = [5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17]
+        .iter()
+        .map(|day| HabitDailyAmount {
+            hab_id: habit.hab_id,
+            date: date(*day),
+            amount: bigdecimal::BigDecimal::from(1),
+            records: 1,
+        })
+        .collect();
+
+    let review = crate::utils::reports::build_review(
+        &[vec![habit]],
+        &daily_amounts,
+        ReviewPeriod::Week,
+        date(14),
+        date(19),
+    );
+
+    assert_eq!(review.current_start_date, date(12));
+    assert_eq!(review.previous_end_date, date(11));
+
+    let habit_review = &review.habits[0];
+
+    assert_eq!(habit_review.current_total, bigdecimal::BigDecimal::from(6));
+    assert_eq!(habit_review.previous_total, bigdecimal::BigDecimal::from(7));
+    assert_eq!(habit_review.previous_completion, Some(1.0));
+    assert_eq!(habit_review.current_completion, Some(6.0 / 7.0));
+    assert_eq!(habit_review.streak, ReviewStreakChange::Broken);
+}
+
+#[test]
+fn test_challenge_leaderboard() {
+    use crate::models::{
+        api::events_api_models::HabitDailyAmount,
+        database::{
+            Challenge, ChallengeParticipant, ChlParPrivacyEnum, HabAggregationEnum,
+            HabFreqTypeEnum, HabGoalDirectionEnum,
+        },
+    };
+    use crate::utils::challenges::build_leaderboard;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Two weeks (January 5th to 18th 2026), at least 3 times a week
+    let challenge = Challenge {
+        chl_id: uuid::Uuid::new_v4(),
+        chl_name: "Test challenge".to_string(),
+        chl_description: "Test challenge".to_string(),
+        chl_created_at: date(1).and_hms_opt(8, 0, 0).unwrap(),
+        chl_is_yn: false,
+        chl_units: "times".to_string(),
+        chl_goal: bigdecimal::BigDecimal::from(3),
+        chl_goal_direction: HabGoalDirectionEnum::at_least,
+        chl_freq_type: HabFreqTypeEnum::weekly,
+        chl_aggregation: HabAggregationEnum::sum,
+        chl_start_date: date(5),
+        chl_end_date: date(18),
+        chl_is_public: false,
+        usr_id: "alice".to_string(),
+    };
+
+    // Amounts logged on the first and second week
+    let players = [
+        ("alice", ChlParPrivacyEnum::public, 3, 3),
+        ("bob", ChlParPrivacyEnum::anonymous, 5, 1),
+        ("carol", ChlParPrivacyEnum::hidden, 4, 0),
+        ("dave", ChlParPrivacyEnum::public, 3, 3),
+    ];
+
+    let mut participants: Vec<ChallengeParticipant> = Vec::new();
+    let mut habits: Vec<Vec<crate::models::database::Habit>> = Vec::new();
+    let mut daily_amounts: Vec<HabitDailyAmount> = Vec::new();
+
+    for (user, privacy, first_week, second_week) in players {
+        let mut habit = build_test_habit(HabFreqTypeEnum::weekly, date(1), 3, false);
+        habit.hab_start_date = Some(date(5));
+        habit.hab_end_date = Some(date(18));
+
+        for (day, amount) in [(6, first_week), (13, second_week)] {
+            daily_amounts.push(HabitDailyAmount {
+                hab_id: habit.hab_id,
+                date: date(day),
+                amount: bigdecimal::BigDecimal::from(amount),
+                records: 1,
+            });
+        }
+
+        participants.push(ChallengeParticipant {
+            chl_par_id: uuid::Uuid::new_v4(),
+            chl_par_joined_at: date(1).and_hms_opt(8, 0, 0).unwrap(),
+            chl_par_privacy: privacy,
+            chl_id: challenge.chl_id,
+            usr_id: user.to_string(),
+            hab_id: habit.hab_id,
+        });
+        habits.push(vec![habit]);
+    }
+
+    let leaderboard = build_leaderboard(
+        &challenge,
+        &participants,
+        &habits,
+        &daily_amounts,
+        "dave",
+        date(19),
+    );
+
+    let entries: Vec<(i64, Option<&str>, i64)> = leaderboard
+        .entries
+        .iter()
+        .map(|entry| (entry.rank, entry.usr_id.as_deref(), entry.periods_met))
+        .collect();
+
+    // Ties share their rank, hidden participants still take their place
+    assert_eq!(leaderboard.participants_count, 4);
+    assert_eq!(
+        entries,
+        vec![(1, Some("alice"), 2), (1, Some("dave"), 2), (3, None, 1)]
+    );
+
+    // Hidden participants see themselves
+    let leaderboard = build_leaderboard(
+        &challenge,
+        &participants,
+        &habits,
+        &daily_amounts,
+        "carol",
+        date(19),
+    );
+    let carol = leaderboard.entries.last().unwrap();
+
+    assert_eq!(leaderboard.entries.len(), 4);
+    assert!(carol.is_requester);
+    assert_eq!(carol.rank, 4);
+    assert_eq!(carol.usr_id.as_deref(), Some("carol"));
+    assert_eq!(carol.completion_rate, Some(0.5));
+
+    // Only periods already over count
+    let leaderboard = build_leaderboard(
+        &challenge,
+        &participants,
+        &habits,
+        &daily_amounts,
+        "dave",
+        date(14),
+    );
+
+    assert!(leaderboard
+        .entries
+        .iter()
+        .all(|entry| entry.periods_count == 1));
+}
+
+#[test]
+fn test_routine_progress() {
+    use crate::models::{api::events_api_models::HabitDailyAmount, database::HabFreqTypeEnum};
+    use crate::utils::routines::build_routine_progress;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Meditate and stretch every day, journal only until the 3rd
+    let meditate = build_test_habit(HabFreqTypeEnum::daily, date(1), 1, true);
+    let stretch = build_test_habit(HabFreqTypeEnum::daily, date(1), 1, true);
+    let mut journal = build_test_habit(HabFreqTypeEnum::daily, date(1), 1, true);
+    journal.hab_end_date = Some(date(3));
+
+    let logged = |habit: &crate::models::database::Habit, days: &[u32]| -> Vec<HabitDailyAmount> {
+        days.iter()
+            .map(|day| HabitDailyAmount {
+                hab_id: habit.hab_id,
+                date: date(*day),
+                amount: bigdecimal::BigDecimal::from(1),
+                records: 1,
+            })
+            .collect()
+    };
+
+    // Complete from the 2nd to the 5th, missing on the 6th, complete again on the 7th and 8th
+    let daily_amounts: Vec<HabitDailyAmount> = [
+        logged(&meditate, &[2, 3, 4, 5, 6, 7, 8, 9]),
+        logged(&stretch, &[2, 3, 4, 5, 7, 8]),
+        logged(&journal, &[2, 3]),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let habits = vec![meditate, stretch, journal];
+    let rou_id = uuid::Uuid::new_v4();
+
+    // Only meditated so far today, the streak holds until the day is over
+    let progress = build_routine_progress(rou_id, &habits, &daily_amounts, date(9));
+
+    assert_eq!(progress.habits.len(), 2);
+    assert_eq!(progress.done_count, 1);
+    assert_eq!(progress.completion_rate, Some(0.5));
+    assert!(!progress.is_complete);
+    assert_eq!(progress.streak, 2);
+    assert_eq!(progress.longest_streak, 4);
+
+    // Once the day is over without stretching, the streak is broken
+    let progress = build_routine_progress(rou_id, &habits, &daily_amounts, date(10));
+
+    assert_eq!(progress.done_count, 0);
+    assert_eq!(progress.streak, 0);
+    assert_eq!(progress.longest_streak, 4);
+}
+
+#[test]
+fn test_checklist_amount() {
+    use crate::models::database::{HabChecklistModeEnum, HabFreqTypeEnum, HabitChecklistCheck};
+    use crate::utils::checklists::{build_checklist, build_checklist_items};
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Evening shutdown: dishes, pack bag, set alarm
+    let mut habit = build_test_habit(HabFreqTypeEnum::daily, date(1), 3, false);
+    let items = build_checklist_items(
+        habit.hab_id,
+        &[
+            "Dishes".to_string(),
+            " Pack bag ".to_string(),
+            "Set alarm".to_string(),
+        ],
+        date(1).and_hms_opt(8, 0, 0).unwrap(),
+    );
+
+    assert_eq!(items[1].hab_chk_name, "Pack bag");
+    assert_eq!(items[2].hab_chk_position, 2);
+
+    // Two items checked on the 2nd, the alarm only on the 3rd
+    let checks: Vec<HabitChecklistCheck> = [(0, 2), (1, 2), (2, 3)]
+        .iter()
+        .map(|(index, day)| HabitChecklistCheck {
+            hab_chk_id: items[*index].hab_chk_id,
+            hab_chk_chk_date: date(*day),
+            hab_chk_chk_checked_at: date(*day).and_hms_opt(20, 0, 0).unwrap(),
+        })
+        .collect();
+
+    let checklist = build_checklist(&habit, &items, &checks, date(2));
+
+    assert_eq!(checklist.checked_count, 2);
+    assert_eq!(checklist.amount, bigdecimal::BigDecimal::from(2));
+    assert!(!checklist.items[2].is_checked);
+
+    habit.hab_checklist_mode = HabChecklistModeEnum::fraction;
+
+    let checklist = build_checklist(&habit, &items, &checks, date(2));
+
+    assert_eq!(
+        checklist.amount,
+        "0.67".parse::<bigdecimal::BigDecimal>().unwrap()
+    );
+
+    let checklist = build_checklist(&habit, &items, &checks, date(4));
+
+    assert_eq!(checklist.checked_count, 0);
+    assert_eq!(checklist.amount, bigdecimal::BigDecimal::from(0));
+}
+
+#[test]
+fn test_habit_chain() {
+    use crate::models::{api::events_api_models::HabitDailyAmount, database::HabitDependency};
+    use crate::utils::dependencies::{build_habit_chain, get_trigger_chain, would_create_cycle};
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Brush, then floss, then mouthwash
+    let brush = uuid::Uuid::new_v4();
+    let floss = uuid::Uuid::new_v4();
+    let mouthwash = uuid::Uuid::new_v4();
+
+    let depends_on = |hab_id: uuid::Uuid, trigger_id: uuid::Uuid| HabitDependency {
+        hab_id,
+        hab_dep_trigger_id: trigger_id,
+        hab_dep_created_at: date(1).and_hms_opt(0, 0, 0).unwrap(),
+    };
+
+    let dependencies = vec![depends_on(floss, brush), depends_on(mouthwash, floss)];
+
+    assert_eq!(
+        get_trigger_chain(&dependencies, mouthwash),
+        vec![brush, floss, mouthwash]
+    );
+    assert!(would_create_cycle(&dependencies, brush, mouthwash));
+    assert!(would_create_cycle(&dependencies, floss, floss));
+    assert!(!would_create_cycle(&dependencies, mouthwash, brush));
+
+    let logged = |hab_id: uuid::Uuid, days: &[u32]| -> Vec<HabitDailyAmount> {
+        days.iter()
+            .map(|day| HabitDailyAmount {
+                hab_id,
+                date: date(*day),
+                amount: bigdecimal::BigDecimal::from(1),
+                records: 1,
+            })
+            .collect()
+    };
+
+    let daily_amounts: Vec<HabitDailyAmount> = [
+        logged(brush, &[1, 2, 3, 4]),
+        logged(floss, &[1, 2, 3, 5]),
+        logged(mouthwash, &[1, 3]),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let chain = build_habit_chain(
+        &[brush, floss, mouthwash],
+        &[],
+        &daily_amounts,
+        date(1),
+        date(5),
+    );
+
+    assert_eq!(chain.hab_id, mouthwash);
+    assert_eq!(chain.links.len(), 2);
+    assert_eq!(chain.links[0].trigger_days, 4);
+    assert_eq!(chain.links[0].together_days, 3);
+    assert_eq!(chain.links[0].completion_rate, Some(0.75));
+    assert_eq!(chain.links[1].trigger_days, 4);
+    assert_eq!(chain.links[1].together_days, 2);
+    assert_eq!(chain.trigger_days, 4);
+    assert_eq!(chain.completed_days, 2);
+    assert_eq!(chain.completion_rate, Some(0.5));
+
+    // A habit without trigger is a chain of its own
+    let single = build_habit_chain(&[brush], &[floss], &daily_amounts, date(1), date(5));
+
+    assert!(single.links.is_empty());
+    assert_eq!(single.completion_rate, None);
+}
+
+#[test]
+fn test_derived_amount() {
+    use crate::models::{
+        api::events_api_models::HabitDailyAmount,
+        database::{HabDerivationEnum, HabitDerivation},
+    };
+    use crate::utils::derivations::get_derived_amount;
+    use bigdecimal::BigDecimal;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Half an hour of each sport a day
+    let source = || {
+        build_test_habit(
+            crate::models::database::HabFreqTypeEnum::daily,
+            date(1),
+            30,
+            false,
+        )
+    };
+    let mut sources = vec![vec![source()], vec![source()], vec![source()]];
+
+    let run = sources[0][0].hab_id;
+    let cycle = sources[1][0].hab_id;
+    let swim = sources[2][0].hab_id;
+    let other = uuid::Uuid::new_v4();
+
+    let amount = |hab_id: uuid::Uuid, day: u32, minutes: i64| HabitDailyAmount {
+        hab_id,
+        date: date(day),
+        amount: BigDecimal::from(minutes),
+        records: 1,
+    };
+
+    let daily_amounts = vec![
+        amount(run, 1, 30),
+        amount(cycle, 1, 45),
+        amount(other, 1, 100),
+        amount(swim, 2, 20),
+    ];
+
+    let derivation = |kind: HabDerivationEnum, quorum: Option<i32>| HabitDerivation {
+        hab_id: uuid::Uuid::new_v4(),
+        hab_der_kind: kind,
+        hab_der_quorum: quorum,
+        hab_der_created_at: date(1).and_hms_opt(0, 0, 0).unwrap(),
+    };
+
+    // Active minutes only add up the sources
+    let active_minutes = derivation(HabDerivationEnum::sum, None);
+
+    assert_eq!(
+        get_derived_amount(&active_minutes, &sources, &daily_amounts, date(1)),
+        BigDecimal::from(75)
+    );
+    assert_eq!(
+        get_derived_amount(&active_minutes, &sources, &daily_amounts, date(3)),
+        BigDecimal::from(0)
+    );
+
+    // Done once two of the three sources were
+    let quorum = derivation(HabDerivationEnum::quorum, Some(2));
+
+    assert_eq!(
+        get_derived_amount(&quorum, &sources, &daily_amounts, date(1)),
+        BigDecimal::from(1)
+    );
+    assert_eq!(
+        get_derived_amount(&quorum, &sources, &daily_amounts, date(2)),
+        BigDecimal::from(0)
+    );
+
+    // Sources logged short of their goal weren't done
+    sources[1][0].hab_goal = BigDecimal::from(60);
+
+    assert_eq!(
+        get_derived_amount(&quorum, &sources, &daily_amounts, date(1)),
+        BigDecimal::from(0)
+    );
+}
+
+#[test]
+fn test_stale_closure_saves_no_periods() {
+    use crate::models::database::{HabFreqTypeEnum, HabitPeriod};
+    use crate::schema::habit_period;
+    use crate::utils::periods::HabitClosure;
+    use diesel::prelude::*;
+
+    let user = TestUser::new();
+    let manager = &user.manager;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    let mut habit = build_test_habit(HabFreqTypeEnum::daily, date(1), 1, false);
+    habit.hab_next_closure_date = date(3);
+    let habit = user.insert_habit(habit);
+
+    let period = |start: u32| HabitPeriod {
+        hab_per_id: uuid::Uuid::new_v4(),
+        hab_per_start_date: date(start),
+        hab_per_end_date: date(start),
+        hab_per_amount: bigdecimal::BigDecimal::from(0),
+        hab_per_goal: bigdecimal::BigDecimal::from(1),
+        hab_per_is_met: false,
+        hab_per_closed_at: date(3).and_hms_opt(0, 0, 0).unwrap(),
+        hab_id: habit.hab_id,
+    };
+
+    // Another run already moved the closure date past the 2nd
+    let stale = HabitClosure {
+        hab_id: habit.hab_id,
+        prev_closure_date: date(2),
+        next_closure_date: date(4),
+    };
+
+    assert_eq!(
+        manager.close_habit_periods(&[period(1)], &[stale]).unwrap(),
+        0
+    );
+
+    let current = HabitClosure {
+        hab_id: habit.hab_id,
+        prev_closure_date: date(3),
+        next_closure_date: date(4),
+    };
+
+    assert_eq!(
+        manager
+            .close_habit_periods(&[period(2)], &[current])
+            .unwrap(),
+        1
+    );
+
+    let saved = habit_period::table
+        .select(habit_period::hab_per_start_date)
+        .filter(habit_period::hab_id.eq(habit.hab_id))
+        .load::<chrono::NaiveDate>(&mut manager.get_write_connection().unwrap())
+        .unwrap();
+
+    assert_eq!(saved, vec![date(2)]);
+}
+
+#[tokio::test]
+async fn test_goal_change_period_treatment() {
+    use crate::models::database::HabFreqTypeEnum;
+
+    let user = TestUser::new();
+
+    let today = chrono::Local::now().date_naive();
+    let days = chrono::Duration::days;
+
+    // Weekly habit three days into its first period
+    let mut habit = build_test_habit(HabFreqTypeEnum::weekly, today - days(3), 3, false);
+    habit.hab_next_closure_date = today + days(4);
+    let habit = user.insert_habit(habit);
+
+    let routes = crate::routes::get_routes(Some(crate::db::create_pool_write().unwrap()), None);
+    let (routes, usr_id, hab_id) = (&routes, user.usr_id.as_str(), habit.hab_id);
+    let update = |data: serde_json::Value| async move {
+        let value = test::request()
+            .method("PATCH")
+            .path(&format!("/api/v1/habits/{}", hab_id))
+            .header("user_id", usr_id)
+            .json(&data)
+            .reply(routes)
+            .await;
+
+        assert_eq!(value.status(), 200);
+
+        serde_json::from_slice::<serde_json::Value>(value.body()).unwrap()["in_flight_period"]
+            .clone()
+    };
+
+    // A new goal applies to the period in progress, which keeps going
+    assert!(update(serde_json::json!({ "goal": 4 })).await.is_null());
+    assert_eq!(
+        user.manager
+            .get_habit_by_id(habit.hab_id)
+            .unwrap()
+            .hab_next_closure_date,
+        today + days(4)
+    );
+
+    // A new frequency cuts it short, three days out of seven ask for their share of the goal
+    let in_flight_period = update(serde_json::json!({ "frequency_type": "daily" })).await;
+
+    let closed_period = &in_flight_period["closed_period"];
+
+    assert_eq!(in_flight_period["treatment"], "closed");
+    assert_eq!(
+        closed_period["hab_per_start_date"],
+        serde_json::json!(today - days(3))
+    );
+    assert_eq!(
+        closed_period["hab_per_end_date"],
+        serde_json::json!(today - days(1))
+    );
+    assert_eq!(closed_period["hab_per_goal"], "1.71");
+    assert_eq!(
+        in_flight_period["next_closure_date"],
+        serde_json::json!(today + days(1))
+    );
+    assert_eq!(
+        user.manager
+            .get_habit_by_id(habit.hab_id)
+            .unwrap()
+            .hab_next_closure_date,
+        today + days(1)
+    );
+
+    // Same day changes start over the period that began with the previous one
+    let in_flight_period = update(serde_json::json!({ "frequency_type": "weekly" })).await;
+
+    assert_eq!(in_flight_period["treatment"], "restarted");
+    assert!(in_flight_period["closed_period"].is_null());
+
+    // Nothing to do when the definition stays the same
+    assert!(update(serde_json::json!({ "goal": 4, "name": "Renamed" }))
+        .await
+        .is_null());
+}
+
+#[test]
+fn test_parse_range_days() {
+    use crate::utils::time::parse_range_days;
+
+    assert_eq!(parse_range_days("30"), Some(30));
+    assert_eq!(parse_range_days("30d"), Some(30));
+    assert_eq!(parse_range_days("12W"), Some(84));
+    assert_eq!(parse_range_days("6m"), Some(180));
+    assert_eq!(parse_range_days("1y"), Some(365));
+    assert_eq!(parse_range_days("0d"), None);
+    assert_eq!(parse_range_days("-3d"), None);
+    assert_eq!(parse_range_days("y"), None);
+    assert_eq!(parse_range_days("3h"), None);
+    assert_eq!(parse_range_days("99999999999999999y"), None);
+}
+
+#[tokio::test]
+async fn test_finished_habits_catch_up() {
+    use crate::models::database::HabFreqTypeEnum;
+
+    let user = TestUser::new();
+    let manager = &user.manager;
+
+    let date = |day: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+    let is_finished = |as_of: chrono::NaiveDate, habit_id: uuid::Uuid| {
+        manager
+            .get_finished_habits(as_of)
+            .unwrap()
+            .iter()
+            .any(|habit| habit.hab_id == habit_id)
+    };
+
+    let mut habit = build_test_habit(HabFreqTypeEnum::daily, date(1), 1, false);
+    habit.hab_end_date = Some(date(10));
+    let habit = user.insert_habit(habit);
+
+    assert!(!is_finished(date(10), habit.hab_id));
+
+    // Still pending days after it ended when no run sent it
+    assert!(is_finished(date(14), habit.hab_id));
+
+    // Nor when the gateway didn't take it
+    let mut plan = crate::jobs::plan_challenges_finished(manager, date(14)).unwrap();
+    let index = plan
+        .habits
+        .iter()
+        .position(|item| item.hab_id == habit.hab_id)
+        .unwrap();
+
+    plan.habits = vec![plan.habits.remove(index)];
+    plan.notifications = vec![plan.notifications.remove(index)];
+
+    std::env::set_var("GATEWAY_URL", "http://127.0.0.1:9");
+
+    assert!(crate::jobs::apply_challenges_finished(manager, &plan)
+        .await
+        .is_err());
+    assert!(is_finished(date(14), habit.hab_id));
+
+    manager
+        .mark_summaries_sent(&[habit.hab_id], date(14))
+        .unwrap();
+
+    assert!(!is_finished(date(15), habit.hab_id));
+}
+
+#[tokio::test]
+async fn test_challenge_habit_keeps_goal() {
+    let user = TestUser::new();
+
+    let today = chrono::Local::now().date_naive();
+    let routes = crate::routes::get_routes(Some(crate::db::create_pool_write().unwrap()), None);
+
+    let value = test::request()
+        .method("POST")
+        .path("/api/v1/challenges")
+        .header("user_id", user.usr_id.as_str())
+        .json(&serde_json::json!({
+            "name": "Test challenge",
+            "description": "Test challenge",
+            "is_yn": false,
+            "units": "times",
+            "goal": 3,
+            "frequency_type": "weekly",
+            "start_date": today,
+            "end_date": today + chrono::Duration::days(13),
+            "is_public": true,
+        }))
+        .reply(&routes)
+        .await;
+
+    assert_eq!(value.status(), 201);
+
+    let challenge_id = serde_json::from_slice::<serde_json::Value>(value.body()).unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let value = test::request()
+        .method("POST")
+        .path(&format!("/api/v1/challenges/{}/join", challenge_id))
+        .header("user_id", user.usr_id.as_str())
+        .json(&serde_json::json!({ "category": user.category }))
+        .reply(&routes)
+        .await;
+
+    assert_eq!(value.status(), 201);
+
+    let hab_id = serde_json::from_slice::<serde_json::Value>(value.body()).unwrap()["participant"]
+        ["hab_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (routes, usr_id, hab_id) = (&routes, user.usr_id.as_str(), hab_id.as_str());
+    let update = |data: serde_json::Value| async move {
+        test::request()
+            .method("PATCH")
+            .path(&format!("/api/v1/habits/{}", hab_id))
+            .header("user_id", usr_id)
+            .json(&data)
+            .reply(routes)
+            .await
+            .status()
+    };
+
+    assert_eq!(update(serde_json::json!({ "goal": 1 })).await, 400);
+    assert_eq!(
+        update(serde_json::json!({ "goal": 3, "name": "Renamed" })).await,
+        200
+    );
+}
+
+#[tokio::test]
+async fn test_routine_completion_checks() {
+    use crate::models::database::HabFreqTypeEnum;
+
+    let user = TestUser::new();
+
+    let today = chrono::Local::now().date_naive();
+    let days = chrono::Duration::days;
+
+    let first = user.insert_habit(build_test_habit(
+        HabFreqTypeEnum::daily,
+        today - days(10),
+        1,
+        false,
+    ));
+    let second = user.insert_habit(build_test_habit(
+        HabFreqTypeEnum::daily,
+        today - days(10),
+        1,
+        false,
+    ));
+
+    let routes = crate::routes::get_routes(Some(crate::db::create_pool_write().unwrap()), None);
+    let (routes, usr_id) = (&routes, user.usr_id.as_str());
+    let request = |method: &'static str, path: String, data: serde_json::Value| async move {
+        let value = test::request()
+            .method(method)
+            .path(&format!("/api/v1/{}", path))
+            .header("user_id", usr_id)
+            .json(&data)
+            .reply(routes)
+            .await;
+
+        (
+            value.status(),
+            serde_json::from_slice::<serde_json::Value>(value.body()).unwrap_or_default(),
+        )
+    };
+
+    let (status, body) = request(
+        "POST",
+        "routines".to_string(),
+        serde_json::json!({
+            "name": "Test routine",
+            "description": "Test routine",
+            "habits": [first.hab_id, second.hab_id],
+        }),
+    )
+    .await;
+
+    assert_eq!(status, 201);
+
+    let complete = |data: serde_json::Value| {
+        request(
+            "POST",
+            format!("routines/{}/complete", body["id"].as_str().unwrap()),
+            data,
+        )
+    };
+
+    // Amounts of each habit are validated like single records
+    let (status, _) = complete(serde_json::json!({
+        "amounts": [{ "habit_id": first.hab_id, "amount": -1 }],
+    }))
+    .await;
+
+    assert_eq!(status, 400);
+
+    // Dates can't go back before the last data of any of its habits
+    let (status, _) = request(
+        "POST",
+        "habitdata".to_string(),
+        serde_json::json!({
+            "habit_id": second.hab_id,
+            "amount": 1,
+            "collected_at": today - days(2),
+        }),
+    )
+    .await;
+
+    assert_eq!(status, 201);
+
+    let (status, _) = complete(serde_json::json!({ "collected_at": today - days(3) })).await;
+
+    assert_eq!(status, 400);
+
+    let (status, body) = complete(serde_json::json!({ "collected_at": today - days(1) })).await;
+
+    assert_eq!(status, 201);
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+
+    // Habits with a checklist are left to their items
+    let (status, _) = request(
+        "PUT",
+        format!("habits/{}/checklist", first.hab_id),
+        serde_json::json!({ "items": [{ "name": "Test item" }] }),
+    )
+    .await;
+
+    assert_eq!(status, 200);
+
+    let (status, _) = complete(serde_json::json!({
+        "collected_at": today,
+        "amounts": [{ "habit_id": first.hab_id, "amount": 1 }],
+    }))
+    .await;
+
+    assert_eq!(status, 400);
+
+    let (status, body) = complete(serde_json::json!({ "collected_at": today })).await;
+
+    assert_eq!(status, 201);
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_concurrent_triggers_make_no_loop() {
+    use crate::models::database::HabFreqTypeEnum;
+
+    let user = TestUser::new();
+    let manager = &user.manager;
+
+    let today = chrono::Local::now().date_naive();
+    let first = user.insert_habit(build_test_habit(HabFreqTypeEnum::daily, today, 1, true));
+    let second = user.insert_habit(build_test_habit(HabFreqTypeEnum::daily, today, 1, true));
+
+    // Each habit set as the trigger of the other at the same time, only one of them holds
+    let barrier = std::sync::Barrier::new(2);
+    let results: Vec<bool> = std::thread::scope(|scope| {
+        [(first.hab_id, second.hab_id), (second.hab_id, first.hab_id)]
+            .map(|(hab_id, trigger_id)| {
+                let (user, barrier) = (&user, &barrier);
+
+                scope.spawn(move || {
+                    barrier.wait();
+                    manager
+                        .set_habit_trigger(user.usr_id.clone(), hab_id, Some(trigger_id))
+                        .is_ok()
+                })
+            })
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(results.iter().filter(|is_set| **is_set).count(), 1);
+}
+
+#[test]
+fn test_concurrent_sources_derive_their_sum() {
+    use crate::models::database::HabFreqTypeEnum;
+
+    let user = TestUser::new();
+    let manager = &user.manager;
+
+    let today = chrono::Local::now().date_naive();
+    let sources = [0, 1]
+        .map(|_| user.insert_habit(build_test_habit(HabFreqTypeEnum::daily, today, 1, false)));
+
+    let derived_id = manager
+        .add_habit(
+            user.usr_id.clone(),
+            serde_json::from_value(serde_json::json!({
+                "name": "Test derived habit",
+                "description": "Test derived habit",
+                "is_favorite": false,
+                "is_yn": false,
+                "color": "ffffff",
+                "units": "times",
+                "goal": 1,
+                "frequency_type": "daily",
+                "category": user.category,
+                "derivation": {
+                    "kind": "sum",
+                    "sources": [sources[0].hab_id, sources[1].hab_id],
+                },
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+
+    // Both sources logged at the same time, each write recomputes the derived habit
+    let barrier = std::sync::Barrier::new(2);
+    std::thread::scope(|scope| {
+        for (source, amount) in sources.iter().zip([2, 3]) {
+            let barrier = &barrier;
+
+            scope.spawn(move || {
+                barrier.wait();
+                manager
+                    .add_habit_data(
+                        serde_json::from_value(serde_json::json!({
+                            "habit_id": source.hab_id,
+                            "amount": amount,
+                            "collected_at": today,
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+            });
+        }
+    });
+
+    let derived_data = manager
+        .get_all_habit_data(derived_id, Some(today), Some(today), None, None)
+        .unwrap();
+
+    assert_eq!(derived_data.len(), 1);
+    assert_eq!(
+        derived_data[0].hab_dat_amount,
+        bigdecimal::BigDecimal::from(5)
+    );
+}
+
+#[test]
+fn test_versioned_aggregation_amounts() {
+    use crate::models::api::events_api_models::CalendarGrouping;
+    use crate::models::database::{HabAggregationEnum, HabFreqTypeEnum};
+    use crate::utils::periods::get_goal_version;
+    use diesel::prelude::*;
+
+    let user = TestUser::new();
+    let manager = &user.manager;
+
+    let date = |day| chrono::NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    // Summed the first week, the highest record counts from the second one on
+    let mut habit = build_test_habit(HabFreqTypeEnum::weekly, date(5), 70, false);
+    habit.hab_aggregation = HabAggregationEnum::max;
+    let habit = user.insert_habit(habit);
+
+    let versions = vec![
+        get_goal_version(
+            &crate::models::database::Habit {
+                hab_aggregation: HabAggregationEnum::sum,
+                ..habit.clone()
+            },
+            date(5),
+        ),
+        get_goal_version(&habit, date(12)),
+    ];
+    let data = build_test_data(&habit, &[(6, 30), (7, 40), (13, 30), (14, 40)]);
+
+    let mut conn = manager.get_write_connection().unwrap();
+
+    diesel::insert_into(crate::schema::habit_goal_version::table)
+        .values(&versions)
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(crate::schema::habit_data_collected::table)
+        .values(&data)
+        .execute(&mut conn)
+        .unwrap();
+
+    let period_amounts = manager
+        .get_habit_period_amounts(&habit, &[(date(5), date(11)), (date(12), date(18))])
+        .unwrap();
+    let calendar = manager
+        .get_habitdata_as_calendar(
+            None,
+            Some(habit.hab_id),
+            Some(date(5)),
+            Some(date(18)),
+            Some(CalendarGrouping::Month),
+        )
+        .unwrap();
+
+    assert_eq!(
+        period_amounts
+            .iter()
+            .map(|period| period.amount.clone())
+            .collect::<Vec<_>>(),
+        [
+            bigdecimal::BigDecimal::from(70),
+            bigdecimal::BigDecimal::from(40)
+        ]
+    );
+
+    // Each version aggregates its own part of the month
+    assert_eq!(calendar.len(), 1);
+    assert_eq!(calendar[0].data, bigdecimal::BigDecimal::from(110));
+}
+
+#[tokio::test]
+async fn test_dashboard_pending_periods() {
+    use crate::models::database::HabFreqTypeEnum;
+
+    let user = TestUser::new();
+
+    let today = chrono::Local::now().date_naive();
+    let days = chrono::Duration::days;
+
+    // Daily habit whose last three days weren't closed yet, all of them met
+    let mut habit = build_test_habit(HabFreqTypeEnum::daily, today - days(3), 1, false);
+    habit.hab_next_closure_date = today - days(2);
+    let habit = user.insert_habit(habit);
+
+    let routes = crate::routes::get_routes(Some(crate::db::create_pool_write().unwrap()), None);
+
+    for day in (1..=3).rev() {
+        let value = test::request()
+            .method("POST")
+            .path("/api/v1/habitdata")
+            .header("user_id", user.usr_id.as_str())
+            .json(&serde_json::json!({
+                "habit_id": habit.hab_id,
+                "amount": 1,
+                "collected_at": today - days(day),
+            }))
+            .reply(&routes)
+            .await;
+
+        assert_eq!(value.status(), 201);
+    }
+
+    let value = test::request()
+        .method("GET")
+        .path("/api/v1/dashboard")
+        .header("user_id", user.usr_id.as_str())
+        .reply(&routes)
+        .await;
+
+    assert_eq!(value.status(), 200);
+
+    let dashboard = serde_json::from_slice::<serde_json::Value>(value.body()).unwrap();
+    let item = &dashboard["habits"][0];
+
+    assert_eq!(item["habit"]["hab_id"], serde_json::json!(habit.hab_id));
+    assert_eq!(item["streak"], 3);
+    assert_eq!(
+        item["next_closure_date"],
+        serde_json::json!(today + days(1))
+    );
+}
+
+#[tokio::test]
+async fn test_habit_stats_wrong_range() {
+    let user = TestUser::new();
+
+    let habit = user.insert_habit(build_test_habit(
+        crate::models::database::HabFreqTypeEnum::daily,
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        1,
+        false,
+    ));
+
+    let routes = crate::routes::get_routes(Some(crate::db::create_pool_write().unwrap()), None);
+
+    let value = test::request()
+        .method("GET")
+        .path(&format!(
+            "/api/v1/habits/{}/stats?start_date=2024-01-01&end_date=2026-01-01",
+            habit.hab_id
+        ))
+        .header("user_id", user.usr_id.as_str())
+        .reply(&routes)
+        .await;
+
+    assert_eq!(value.status(), 400);
+
+    // Habits older than the longest range are looked at over that range by default
+    let value = test::request()
+        .method("GET")
+        .path(&format!("/api/v1/habits/{}/stats", habit.hab_id))
+        .header("user_id", user.usr_id.as_str())
+        .reply(&routes)
+        .await;
+
+    assert_eq!(value.status(), 200);
+}
+
+#[tokio::test]
+async fn test_checklist_habit_data_creation() {
+    let user = TestUser::new();
+
+    let habit = user.insert_habit(build_test_habit(
+        crate::models::database::HabFreqTypeEnum::daily,
+        chrono::Local::now().date_naive(),
+        1,
+        false,
+    ));
+
+    let routes = crate::routes::get_routes(Some(crate::db::create_pool_write().unwrap()), None);
+
+    let value = test::request()
+        .method("PUT")
+        .path(&format!("/api/v1/habits/{}/checklist", habit.hab_id))
+        .header("user_id", user.usr_id.as_str())
+        .json(&serde_json::json!({ "items": [{ "name": "Test item" }] }))
+        .reply(&routes)
+        .await;
+
+    assert_eq!(value.status(), 200);
+
+    let value = test::request()
+        .method("POST")
+        .path("/api/v1/habitdata")
+        .header("user_id", user.usr_id.as_str())
+        .json(&serde_json::json!({ "habit_id": habit.hab_id, "amount": 1 }))
+        .reply(&routes)
+        .await;
+
+    assert_eq!(value.status(), 400);
+}
+
+#[test]
+fn test_concurrent_derivations_make_no_chain() {
+    use crate::models::database::HabFreqTypeEnum;
+
+    let user = TestUser::new();
+    let manager = &user.manager;
+
+    let today = chrono::Local::now().date_naive();
+    let habits = [0, 1, 2]
+        .map(|_| user.insert_habit(build_test_habit(HabFreqTypeEnum::daily, today, 1, false)));
+
+    // Each habit derived from the other ones at the same time, only one of them holds
+    let barrier = std::sync::Barrier::new(2);
+    let results: Vec<bool> = std::thread::scope(|scope| {
+        [(0, [1, 2]), (1, [0, 2])]
+            .map(|(derived, sources)| {
+                let (user, barrier, habits) = (&user, &barrier, &habits);
+
+                scope.spawn(move || {
+                    let data = serde_json::from_value(serde_json::json!({
+                        "kind": "sum",
+                        "sources": sources.map(|source| habits[source].hab_id),
+                    }))
+                    .unwrap();
+
+                    barrier.wait();
+                    manager
+                        .set_habit_derivation(user.usr_id.clone(), &habits[derived], Some(data))
+                        .is_ok()
+                })
+            })
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(results.iter().filter(|is_set| **is_set).count(), 1);
+}
+
+#[tokio::test]
+async fn test_share_heatmap_range() {
+    let user = TestUser::new();
+
+    let habit = user.insert_habit(build_test_habit(
+        crate::models::database::HabFreqTypeEnum::daily,
+        chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+        1,
+        false,
+    ));
+    let share = user.manager.add_habit_share(habit.hab_id).unwrap();
+
+    let routes = crate::routes::get_routes(Some(crate::db::create_pool_write().unwrap()), None);
+    let chart = |query: &str| {
+        test::request()
+            .method("GET")
+            .path(&format!(
+                "/api/v1/share/{}/chart.svg?kind=heatmap&{}",
+                share.hab_sha_token, query
+            ))
+            .reply(&routes)
+    };
+
+    // A cell for each day of the range
+    let value = chart("range=2w&end_date=2026-01-14").await;
+
+    assert_eq!(value.status(), 200);
+    assert_eq!(
+        String::from_utf8_lossy(value.body())
+            .matches("<rect")
+            .count(),
+        14
+    );
+
+    // Ranges reaching out of bounds are rejected, even before the earliest date there is
+    for end_date in ["2000-02-01", "-262144-02-01"] {
+        let value = chart(&format!("range=1y&end_date={}", end_date)).await;
+
+        assert_eq!(value.status(), 400);
+    }
+}
@@ -0,0 +1,136 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+
+use crate::{
+    models::{
+        api::{
+            challenge_api_models::{ChallengeLeaderboard, LeaderboardEntry},
+            events_api_models::HabitDailyAmount,
+        },
+        database::{
+            Challenge, ChallengeParticipant, ChlParPrivacyEnum, HabGoalDirectionEnum, Habit,
+        },
+    },
+    utils::periods::{aggregate_daily_amounts, get_versioned_period_bounds, is_goal_met},
+};
+
+// Rank the participants of a challenge by periods met, then by amount collected (the lowest first
+// for limits). Only periods already over count. Hidden participants keep their rank but are only
+// listed to themselves, anonymous ones are listed without their user id. Habits come with their
+// versions
+pub fn build_leaderboard(
+    challenge: &Challenge,
+    participants: &[ChallengeParticipant],
+    habits: &[Vec<Habit>],
+    daily_amounts: &[HabitDailyAmount],
+    requester_id: &str,
+    current_date: NaiveDate,
+) -> ChallengeLeaderboard {
+    let start_date = challenge.chl_start_date;
+    let end_date = challenge.chl_end_date.min(current_date);
+
+    let mut results: Vec<(&ChallengeParticipant, LeaderboardEntry)> = Vec::new();
+
+    for participant in participants {
+        let versions = habits
+            .iter()
+            .find(|versions| versions[0].hab_id == participant.hab_id);
+
+        // Participation without a habit can't be evaluated
+        if versions.is_none() {
+            continue;
+        }
+
+        let versions = versions.unwrap();
+
+        let in_range = |from: NaiveDate, to: NaiveDate| -> Vec<&HabitDailyAmount> {
+            daily_amounts
+                .iter()
+                .filter(|item| {
+                    item.hab_id == participant.hab_id && item.date >= from && item.date <= to
+                })
+                .collect()
+        };
+
+        let periods: Vec<bool> =
+            get_versioned_period_bounds(versions, start_date, challenge.chl_end_date)
+                .into_iter()
+                .filter(|(_, period_end, _)| period_end < &current_date)
+                .map(|(period_start, period_end, version)| {
+                    let amount =
+                        aggregate_daily_amounts(&version, &in_range(period_start, period_end));
+
+                    is_goal_met(&version, &amount)
+                })
+                .collect();
+
+        let window = in_range(start_date, end_date);
+
+        let periods_count = periods.len() as i64;
+        let periods_met = periods.iter().filter(|met| **met).count() as i64;
+
+        results.push((
+            participant,
+            LeaderboardEntry {
+                rank: 0,
+                usr_id: Some(participant.usr_id.clone()),
+                is_requester: participant.usr_id == requester_id,
+                periods_count,
+                periods_met,
+                completion_rate: match periods_count {
+                    0 => None,
+                    _ => Some(periods_met as f64 / periods_count as f64),
+                },
+                amount: aggregate_daily_amounts(&versions[versions.len() - 1], &window),
+                days_logged: window.len() as i64,
+            },
+        ));
+    }
+
+    let is_limit = challenge.chl_goal_direction == HabGoalDirectionEnum::at_most;
+
+    results.sort_by(|(_, a), (_, b)| {
+        let by_amount = match is_limit {
+            true => a.amount.cmp(&b.amount),
+            false => b.amount.cmp(&a.amount),
+        };
+
+        b.periods_met.cmp(&a.periods_met).then(by_amount)
+    });
+
+    // Ties share the rank, the next one skips as many places
+    let mut previous: Option<(i64, i64, BigDecimal)> = None;
+
+    for (index, (_, entry)) in results.iter_mut().enumerate() {
+        entry.rank = match &previous {
+            Some((rank, periods_met, amount))
+                if *periods_met == entry.periods_met && *amount == entry.amount =>
+            {
+                *rank
+            }
+            _ => index as i64 + 1,
+        };
+
+        previous = Some((entry.rank, entry.periods_met, entry.amount.clone()));
+    }
+
+    ChallengeLeaderboard {
+        chl_id: challenge.chl_id,
+        start_date,
+        end_date,
+        participants_count: participants.len() as i64,
+        entries: results
+            .into_iter()
+            .filter(|(participant, entry)| {
+                entry.is_requester || participant.chl_par_privacy != ChlParPrivacyEnum::hidden
+            })
+            .map(|(participant, mut entry)| {
+                if !entry.is_requester && participant.chl_par_privacy != ChlParPrivacyEnum::public {
+                    entry.usr_id = None;
+                }
+
+                entry
+            })
+            .collect(),
+    }
+}
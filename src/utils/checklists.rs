@@ -0,0 +1,74 @@
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::models::{
+    api::habit_api_models::{HabitChecklist, HabitChecklistItemState},
+    database::{HabChecklistModeEnum, Habit, HabitChecklistCheck, HabitChecklistItem},
+};
+
+// Items of a new checklist, in the order given
+pub fn build_checklist_items(
+    hab_id: Uuid,
+    names: &[String],
+    current_datetime: chrono::NaiveDateTime,
+) -> Vec<HabitChecklistItem> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(position, name)| HabitChecklistItem {
+            hab_chk_id: Uuid::new_v4(),
+            hab_chk_name: name.trim().to_string(),
+            hab_chk_position: position as i32,
+            hab_chk_created_at: current_datetime,
+            hab_id,
+        })
+        .collect()
+}
+
+// Daily amount of a habit made of its checked items, shares are kept to two decimals like
+// collected amounts
+pub fn get_checklist_amount(
+    mode: HabChecklistModeEnum,
+    checked_count: i64,
+    items_count: i64,
+) -> BigDecimal {
+    match (mode, items_count) {
+        (HabChecklistModeEnum::count, _) => BigDecimal::from(checked_count),
+        (HabChecklistModeEnum::fraction, 0) => BigDecimal::from(0),
+        (HabChecklistModeEnum::fraction, _) => (BigDecimal::from(checked_count)
+            / BigDecimal::from(items_count))
+        .with_scale_round(2, RoundingMode::HalfUp),
+    }
+}
+
+// State of a checklist on a day, checks of other days are ignored
+pub fn build_checklist(
+    habit: &Habit,
+    items: &[HabitChecklistItem],
+    checks: &[HabitChecklistCheck],
+    date: NaiveDate,
+) -> HabitChecklist {
+    let items: Vec<HabitChecklistItemState> = items
+        .iter()
+        .map(|item| HabitChecklistItemState {
+            hab_chk_id: item.hab_chk_id,
+            hab_chk_name: item.hab_chk_name.clone(),
+            hab_chk_position: item.hab_chk_position,
+            is_checked: checks
+                .iter()
+                .any(|check| check.hab_chk_id == item.hab_chk_id && check.hab_chk_chk_date == date),
+        })
+        .collect();
+
+    let checked_count = items.iter().filter(|item| item.is_checked).count() as i64;
+
+    HabitChecklist {
+        hab_id: habit.hab_id,
+        date,
+        mode: habit.hab_checklist_mode,
+        amount: get_checklist_amount(habit.hab_checklist_mode, checked_count, items.len() as i64),
+        checked_count,
+        items,
+    }
+}
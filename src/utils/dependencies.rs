@@ -0,0 +1,117 @@
+use chrono::{Duration, NaiveDate};
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::models::{
+    api::{
+        events_api_models::HabitDailyAmount,
+        habit_api_models::{HabitChain, HabitChainLink},
+    },
+    database::HabitDependency,
+};
+
+// Habits from the first trigger of the chain down to the given one, following each habit's
+// trigger among the user's dependencies
+pub fn get_trigger_chain(dependencies: &[HabitDependency], hab_id: Uuid) -> Vec<Uuid> {
+    let mut chain = vec![hab_id];
+    let mut current = hab_id;
+
+    while let Some(dependency) = dependencies.iter().find(|item| item.hab_id == current) {
+        // Stored dependencies have no cycles, this only guards the walk
+        if chain.contains(&dependency.hab_dep_trigger_id) {
+            break;
+        }
+
+        current = dependency.hab_dep_trigger_id;
+        chain.push(current);
+    }
+
+    chain.reverse();
+    chain
+}
+
+// A habit can't be triggered by itself nor by any habit it leads to
+pub fn would_create_cycle(
+    dependencies: &[HabitDependency],
+    hab_id: Uuid,
+    trigger_id: Uuid,
+) -> bool {
+    get_trigger_chain(dependencies, trigger_id).contains(&hab_id)
+}
+
+fn get_completion_rate(completed: i64, total: i64) -> Option<f64> {
+    match total {
+        0 => None,
+        total => Some(completed as f64 / total as f64),
+    }
+}
+
+// How often a chain completes together within a date range. Each link compares the days its
+// trigger was logged with the days both habits were, the whole chain counts the days every
+// habit was logged against the days its first trigger was
+pub fn build_habit_chain(
+    chain: &[Uuid],
+    dependents: &[Uuid],
+    daily_amounts: &[HabitDailyAmount],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> HabitChain {
+    let logged: HashSet<(Uuid, NaiveDate)> = daily_amounts
+        .iter()
+        .filter(|item| item.records > 0)
+        .map(|item| (item.hab_id, item.date))
+        .collect();
+
+    let mut days = vec![];
+    let mut day = start_date;
+
+    while day <= end_date {
+        days.push(day);
+        day += Duration::days(1);
+    }
+
+    let count_days = |habits: &[Uuid]| -> i64 {
+        days.iter()
+            .filter(|day| {
+                habits
+                    .iter()
+                    .all(|hab_id| logged.contains(&(*hab_id, **day)))
+            })
+            .count() as i64
+    };
+
+    let links: Vec<HabitChainLink> = chain
+        .windows(2)
+        .map(|pair| {
+            let trigger_days = count_days(&pair[..1]);
+            let together_days = count_days(pair);
+
+            HabitChainLink {
+                trigger_id: pair[0],
+                dependent_id: pair[1],
+                trigger_days,
+                together_days,
+                completion_rate: get_completion_rate(together_days, trigger_days),
+            }
+        })
+        .collect();
+
+    let trigger_days = count_days(&chain[..1]);
+    let completed_days = count_days(chain);
+
+    HabitChain {
+        hab_id: chain[chain.len() - 1],
+        habits: chain.to_vec(),
+        dependents: dependents.to_vec(),
+        start_date,
+        end_date,
+        links,
+        trigger_days,
+        completed_days,
+        completion_rate: match chain.len() {
+            1 => None,
+            _ => get_completion_rate(completed_days, trigger_days),
+        },
+    }
+}
@@ -0,0 +1,116 @@
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, NaiveDateTime};
+
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    models::{
+        api::{events_api_models::HabitDailyAmount, habit_api_models::HabitDerivationSchema},
+        database::{HabDerivationEnum, Habit, HabitDerivation, HabitDerivationSource},
+    },
+    utils::periods::{get_habit_version_at, is_goal_met},
+};
+
+// Derived habits are computed from other logged habits of their owner. Sums add amounts up
+// and quorums are Y/N habits done once enough sources were. Sources are checked against the
+// owner's habits when the derivation is written
+pub fn check_habit_derivation(habit: &Habit, data: &HabitDerivationSchema) -> Result<(), Error> {
+    let mut unique = data.sources.clone();
+    unique.sort();
+    unique.dedup();
+
+    if unique.len() != data.sources.len() || unique.contains(&habit.hab_id) {
+        return Err(Error::BadRequest(
+            "Derivation sources must be distinct habits other than the derived one".to_string(),
+        ));
+    }
+
+    match data.kind {
+        HabDerivationEnum::sum if habit.hab_is_yn || data.quorum.is_some() => {
+            return Err(Error::BadRequest(
+                "Sum derivations are for habits with amounts and take no quorum".to_string(),
+            ));
+        }
+        HabDerivationEnum::quorum
+            if !habit.hab_is_yn
+                || !matches!(data.quorum, Some(quorum) if quorum as usize <= data.sources.len()) =>
+        {
+            return Err(Error::BadRequest(
+                "Quorum derivations are for Y/N habits and need a quorum up to the number of sources"
+                    .to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// Daily value of a derived habit out of the daily amounts of its sources, which come with their
+// versions. Sums add the amounts up, quorums are done (1) once enough sources met the goal they
+// had on the day
+pub fn get_derived_amount(
+    derivation: &HabitDerivation,
+    sources: &[Vec<Habit>],
+    daily_amounts: &[HabitDailyAmount],
+    date: NaiveDate,
+) -> BigDecimal {
+    let source_amounts: Vec<(&Vec<Habit>, &HabitDailyAmount)> = sources
+        .iter()
+        .filter_map(|versions| {
+            daily_amounts
+                .iter()
+                .find(|item| {
+                    item.hab_id == versions[0].hab_id && item.date == date && item.records > 0
+                })
+                .map(|item| (versions, item))
+        })
+        .collect();
+
+    match derivation.hab_der_kind {
+        HabDerivationEnum::sum => source_amounts
+            .iter()
+            .map(|(_, item)| item.amount.clone())
+            .sum(),
+        HabDerivationEnum::quorum => {
+            let quorum = derivation.hab_der_quorum.unwrap_or(sources.len() as i32);
+            let sources_met = source_amounts
+                .iter()
+                .filter(|(versions, item)| {
+                    is_goal_met(get_habit_version_at(versions, date), &item.amount)
+                })
+                .count();
+
+            match sources_met as i32 >= quorum {
+                true => BigDecimal::from(1),
+                false => BigDecimal::from(0),
+            }
+        }
+    }
+}
+
+// Definition of a derived habit along with its sources
+pub fn build_habit_derivation(
+    hab_id: Uuid,
+    data: &HabitDerivationSchema,
+    current_datetime: NaiveDateTime,
+) -> (HabitDerivation, Vec<HabitDerivationSource>) {
+    let derivation = HabitDerivation {
+        hab_id,
+        hab_der_kind: data.kind,
+        hab_der_quorum: data.quorum,
+        hab_der_created_at: current_datetime,
+    };
+
+    let sources = data
+        .sources
+        .iter()
+        .map(|hab_src_id| HabitDerivationSource {
+            hab_id,
+            hab_src_id: *hab_src_id,
+        })
+        .collect();
+
+    (derivation, sources)
+}
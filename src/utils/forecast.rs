@@ -0,0 +1,221 @@
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::HashMap;
+
+use crate::{
+    models::{
+        api::{events_api_models::HabitDailyAmount, stats_api_models::PeriodForecast},
+        database::{HabGoalDirectionEnum, Habit},
+    },
+    utils::{
+        periods::{
+            aggregate_daily_amounts, get_habit_reference_date, get_versioned_period_bounds,
+            is_amount_growing, is_amount_summed, is_goal_met,
+        },
+        FORECAST_HISTORY_DAYS,
+    },
+};
+
+// Error function approximation (Abramowitz and Stegun 7.1.26), good to 1.5e-7
+fn erf(x: f64) -> f64 {
+    let sign = match x < 0.0 {
+        true => -1.0,
+        false => 1.0,
+    };
+    let x = x.abs();
+
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let y = 1.0
+        - (((((1.061405429 * t - 1.453152027) * t) + 1.421413741) * t - 0.284496736) * t
+            + 0.254829592)
+            * t
+            * (-x * x).exp();
+
+    sign * y
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+fn mean_and_variance(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64;
+
+    Some((mean, variance))
+}
+
+// Estimate how the current period of a habit will end. Each remaining day is expected to go like
+// the same weekday did in the past weeks, blended with the pace of the period so far, and the
+// probability of meeting the goal comes from a normal approximation of the remaining amount.
+// The current period is judged against the latest goal version, daily amounts should cover the
+// history window and the current period. Habits that aren't summed only get a probability once
+// their period is over
+pub fn build_period_forecast(
+    versions: &[Habit],
+    daily_amounts: &[HabitDailyAmount],
+    current_date: NaiveDate,
+) -> Option<PeriodForecast> {
+    let (period_start, period_end, habit) =
+        get_versioned_period_bounds(versions, current_date, current_date)
+            .into_iter()
+            .next()?;
+    let habit = &habit;
+
+    let amounts: HashMap<NaiveDate, f64> = daily_amounts
+        .iter()
+        .filter(|item| item.hab_id == habit.hab_id)
+        .map(|item| (item.date, item.amount.to_f64().unwrap_or(0.0)))
+        .collect();
+
+    let get_amount = |date: &NaiveDate| amounts.get(date).copied().unwrap_or(0.0);
+
+    let period_amounts: Vec<&HabitDailyAmount> = daily_amounts
+        .iter()
+        .filter(|item| {
+            item.hab_id == habit.hab_id && item.date >= period_start && item.date <= current_date
+        })
+        .collect();
+    let amount = aggregate_daily_amounts(habit, &period_amounts);
+    let is_met = is_goal_met(habit, &amount);
+    let amount = amount.to_f64().unwrap_or(0.0);
+
+    let goal = habit.hab_goal.to_f64().unwrap_or(0.0);
+    let remaining_amount = (goal - amount).max(0.0);
+
+    // Today still counts as a remaining day until something is logged
+    let first_remaining_day = match amounts.contains_key(&current_date) {
+        true => current_date + Duration::days(1),
+        false => current_date,
+    };
+    let remaining_days = ((period_end - first_remaining_day).num_days() + 1).max(0);
+
+    // Past behavior of each weekday, days without data count as zero
+    let history_start = (period_start - Duration::days(FORECAST_HISTORY_DAYS))
+        .max(get_habit_reference_date(&versions[0]));
+    let mut history: HashMap<u32, Vec<f64>> = HashMap::new();
+    let mut date = history_start;
+
+    while date < period_start {
+        history
+            .entry(date.weekday().num_days_from_monday())
+            .or_default()
+            .push(get_amount(&date));
+        date += Duration::days(1);
+    }
+
+    // Pace of the days of the period already over
+    let elapsed: Vec<f64> = (0..(first_remaining_day - period_start).num_days())
+        .map(|offset| get_amount(&(period_start + Duration::days(offset))))
+        .collect();
+    let pace = mean_and_variance(&elapsed);
+
+    let mut expected = 0.0;
+    let mut variance = 0.0;
+
+    // Days can only be added up for summed habits, there's no model for the others
+    let is_summed = is_amount_summed(habit);
+    let mut is_known = is_summed;
+
+    let modeled_days = match is_summed {
+        true => remaining_days,
+        false => 0,
+    };
+
+    for offset in 0..modeled_days {
+        let weekday = (first_remaining_day + Duration::days(offset))
+            .weekday()
+            .num_days_from_monday();
+        let past = history
+            .get(&weekday)
+            .and_then(|values| mean_and_variance(values));
+
+        // Blended days average both estimates, and so does their uncertainty
+        match (past, pace) {
+            (Some((past_mean, past_variance)), Some((pace_mean, pace_variance))) => {
+                expected += (past_mean + pace_mean) / 2.0;
+                variance += (past_variance + pace_variance) / 4.0;
+            }
+            (Some((past_mean, past_variance)), None) => {
+                expected += past_mean;
+                variance += past_variance;
+            }
+            (None, Some((pace_mean, pace_variance))) => {
+                expected += pace_mean;
+                variance += pace_variance;
+            }
+            (None, None) => is_known = false,
+        }
+    }
+
+    // Range the amount still to come must fall in for the goal to be met
+    let tolerance = habit.hab_goal_tolerance.to_f64().unwrap_or(0.0);
+    let (low, high) = match habit.hab_goal_direction {
+        HabGoalDirectionEnum::at_least => (Some(goal - amount), None),
+        HabGoalDirectionEnum::at_most => (None, Some(goal - amount)),
+        HabGoalDirectionEnum::exact => (
+            Some(goal - tolerance - amount),
+            Some(goal + tolerance - amount),
+        ),
+    };
+
+    let is_within = |value: f64| match low.is_none_or(|low| value >= low)
+        && high.is_none_or(|high| value <= high)
+    {
+        true => 1.0,
+        false => 0.0,
+    };
+
+    let is_growing = is_amount_growing(habit);
+
+    let probability = if is_growing && high.is_some_and(|high| high < 0.0) {
+        // Already over the limit
+        Some(0.0)
+    } else if is_growing && high.is_none() && low.is_none_or(|low| low <= 0.0) {
+        // Minimum already reached
+        Some(1.0)
+    } else if remaining_days == 0 {
+        Some(match is_met {
+            true => 1.0,
+            false => 0.0,
+        })
+    } else if !is_known {
+        None
+    } else if variance <= 0.0 {
+        Some(is_within(expected))
+    } else {
+        let deviation = variance.sqrt();
+        let cdf = |bound: Option<f64>, default: f64| {
+            bound.map_or(default, |bound| normal_cdf((bound - expected) / deviation))
+        };
+
+        Some(cdf(high, 1.0) - cdf(low, 0.0))
+    };
+
+    let needed_per_day = match (remaining_days, is_summed) {
+        (0, _) | (_, false) => 0.0,
+        (days, true) => remaining_amount / days as f64,
+    };
+
+    Some(PeriodForecast {
+        hab_id: habit.hab_id,
+        period_start_date: period_start,
+        period_end_date: period_end,
+        next_closure_date: habit.hab_next_closure_date,
+        goal: habit.hab_goal.clone(),
+        amount: BigDecimal::try_from(amount).unwrap_or_default(),
+        remaining_amount: BigDecimal::try_from(remaining_amount).unwrap_or_default(),
+        remaining_days,
+        needed_per_day,
+        expected_amount: amount + expected,
+        probability,
+    })
+}
@@ -1,4 +1,13 @@
+pub mod challenges;
+pub mod checklists;
+pub mod dependencies;
+pub mod derivations;
+pub mod forecast;
+pub mod periods;
 pub mod queries;
+pub mod reports;
+pub mod routines;
+pub mod svg;
 pub mod time;
 
 use crate::db::{DBManager, PostgresPool};
@@ -10,6 +19,15 @@ pub const MAX_QUERY_LIMIT: i64 = 100;
 pub const DEFAULT_QUERY_LIMIT: i64 = 100;
 pub const MAX_DAYS_OFFSET: i64 = 1; // Grace period a user will be given to mark a habit as completed
 pub const HABIT_CREATION_DATE_AS_REFERENCE: bool = true; // Habit's creation date represents the start of its own recurrences
+pub const MAX_CALENDAR_DAYS: i64 = 366; // Longest date range calendar periods can be requested for
+pub const MAX_PERIOD_DAYS: i64 = 62; // Longest a habit period can last (two months)
+pub const DEFAULT_MIN_OVERLAP: i64 = 7; // Days two series must share for their correlation to be reported
+pub const FORECAST_HISTORY_DAYS: i64 = 84; // Days of past behavior forecasts look at for each weekday
+pub const FORECAST_REMINDER_PROBABILITY: f64 = 0.5; // Habits less likely than this to meet their goal get a reminder
+pub const ROUTINE_HISTORY_DAYS: i64 = 366; // Days routine streaks are looked for over
+pub const MAX_CHECKLIST_ITEMS: usize = 50; // Most sub-items a habit checklist can have
+pub const JOB_TICK_TOLERANCE_SECONDS: i64 = 60; // How far from its fire time a scheduled job can start and still claim that tick
+pub const ADMIN_TOKEN_HEADER: &str = "admin_token"; // Header carrying the token of administration requests
 
 pub fn with_db_manager(
     pool_write: Option<PostgresPool>,
@@ -39,5 +57,19 @@ pub fn with_authenticator() -> impl Filter<Extract = (AuthData,), Error = Reject
             role: AuthRole::Guest,
         }))
         .unify()
+        .and(warp::header::optional::<String>(ADMIN_TOKEN_HEADER))
+        .map(|mut auth: AuthData, admin_token: Option<String>| {
+            // Only the gateway knows the token set in ADMIN_TOKEN, no token configured means no
+            // administrators at all
+            let expected_token = std::env::var("ADMIN_TOKEN").unwrap_or_default();
+
+            if !expected_token.is_empty()
+                && admin_token.is_some_and(|token| token == expected_token)
+            {
+                auth.role = AuthRole::Admin;
+            }
+
+            auth
+        })
         .and_then(|auth| async move { Ok::<AuthData, Rejection>(auth) })
 }
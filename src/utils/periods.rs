@@ -0,0 +1,530 @@
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::NaiveDate;
+use serde_derive::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        api::events_api_models::{
+            CalendarPeriod, HabitDailyAmount, HeatmapDay, HeatmapDayStatus, PeriodStatus,
+        },
+        database::{
+            HabAggregationEnum, HabGoalDirectionEnum, Habit, HabitDataCollected, HabitGoalVersion,
+        },
+    },
+    utils::{
+        time::{get_next_closure_date, DateRange, MAXIMUM_DATE, REFERENCE_DATE},
+        HABIT_CREATION_DATE_AS_REFERENCE,
+    },
+};
+
+// Move of a habit's closure date when its pending periods get closed
+#[derive(Debug, Serialize, Clone)]
+pub struct HabitClosure {
+    pub hab_id: Uuid,
+
+    pub prev_closure_date: NaiveDate,
+
+    pub next_closure_date: NaiveDate,
+}
+
+// Amount collected within a period following the habit's aggregation mode, Y/N habits count
+// the days they were done. Periods without data amount to 0
+pub fn aggregate_period_amount(habit: &Habit, data: &[&HabitDataCollected]) -> BigDecimal {
+    if habit.hab_is_yn {
+        return BigDecimal::from(data.len() as i64);
+    }
+
+    let amounts = data.iter().map(|item| &item.hab_dat_amount);
+
+    match habit.hab_aggregation {
+        HabAggregationEnum::sum => {
+            amounts.fold(BigDecimal::from(0), |total, amount| total + amount)
+        }
+        HabAggregationEnum::last => data
+            .iter()
+            .max_by_key(|item| {
+                (
+                    item.hab_dat_collected_at,
+                    item.hab_dat_created_at,
+                    item.hab_dat_id,
+                )
+            })
+            .map(|item| item.hab_dat_amount.clone())
+            .unwrap_or_default(),
+        HabAggregationEnum::average => match data.len() {
+            0 => BigDecimal::from(0),
+            length => (amounts.fold(BigDecimal::from(0), |total, amount| total + amount)
+                / BigDecimal::from(length as i64))
+            .round(2),
+        },
+        HabAggregationEnum::min => amounts.min().cloned().unwrap_or_default(),
+        HabAggregationEnum::max => amounts.max().cloned().unwrap_or_default(),
+    }
+}
+
+// Same as aggregate_period_amount, from amounts already aggregated by day
+pub fn aggregate_daily_amounts(habit: &Habit, daily_amounts: &[&HabitDailyAmount]) -> BigDecimal {
+    let amounts = daily_amounts.iter().map(|item| &item.amount);
+
+    if habit.hab_is_yn {
+        return amounts.fold(BigDecimal::from(0), |total, amount| total + amount);
+    }
+
+    match habit.hab_aggregation {
+        HabAggregationEnum::sum => {
+            amounts.fold(BigDecimal::from(0), |total, amount| total + amount)
+        }
+        HabAggregationEnum::last => daily_amounts
+            .iter()
+            .max_by_key(|item| item.date)
+            .map(|item| item.amount.clone())
+            .unwrap_or_default(),
+        // Daily averages weighted by the records they were made of
+        HabAggregationEnum::average => {
+            let records: i64 = daily_amounts.iter().map(|item| item.records).sum();
+
+            match records {
+                0 => BigDecimal::from(0),
+                records => (daily_amounts
+                    .iter()
+                    .fold(BigDecimal::from(0), |total, item| {
+                        total + &item.amount * BigDecimal::from(item.records)
+                    })
+                    / BigDecimal::from(records))
+                .round(2),
+            }
+        }
+        HabAggregationEnum::min => amounts.min().cloned().unwrap_or_default(),
+        HabAggregationEnum::max => amounts.max().cloned().unwrap_or_default(),
+    }
+}
+
+// Whether each record adds up to the amount of its period
+pub fn is_amount_summed(habit: &Habit) -> bool {
+    habit.hab_is_yn || habit.hab_aggregation == HabAggregationEnum::sum
+}
+
+// Whether the amount of a period can only go up as more data is collected
+pub fn is_amount_growing(habit: &Habit) -> bool {
+    is_amount_summed(habit) || habit.hab_aggregation == HabAggregationEnum::max
+}
+
+// Whether the amount collected within a period satisfies the habit's goal
+pub fn is_goal_met(habit: &Habit, amount: &BigDecimal) -> bool {
+    match habit.hab_goal_direction {
+        HabGoalDirectionEnum::at_least => amount >= &habit.hab_goal,
+        HabGoalDirectionEnum::at_most => amount <= &habit.hab_goal,
+        HabGoalDirectionEnum::exact => (amount - &habit.hab_goal).abs() <= habit.hab_goal_tolerance,
+    }
+}
+
+// Whether more data can no longer make a period meet its goal
+pub fn is_goal_exceeded(habit: &Habit, amount: &BigDecimal) -> bool {
+    if !is_amount_growing(habit) {
+        return false;
+    }
+
+    match habit.hab_goal_direction {
+        HabGoalDirectionEnum::at_least => false,
+        HabGoalDirectionEnum::at_most => amount > &habit.hab_goal,
+        HabGoalDirectionEnum::exact => amount > &(&habit.hab_goal + &habit.hab_goal_tolerance),
+    }
+}
+
+// Whether meeting the goal before the period is over means it's met for good
+pub fn is_goal_settled(habit: &Habit) -> bool {
+    habit.hab_goal_direction == HabGoalDirectionEnum::at_least && is_amount_growing(habit)
+}
+
+// How close an amount is to the habit's goal, from 0 to 1 (goal met)
+pub fn get_goal_progress(habit: &Habit, amount: f64) -> f64 {
+    let goal = habit.hab_goal.to_f64().unwrap_or(0.0);
+    let tolerance = habit.hab_goal_tolerance.to_f64().unwrap_or(0.0);
+
+    // Going over a limit scores less the further it goes
+    let over = |limit: f64| match amount > limit {
+        true => match amount > 0.0 {
+            true => (limit / amount).clamp(0.0, 1.0),
+            false => 0.0,
+        },
+        false => 1.0,
+    };
+
+    let under = |target: f64| match target > 0.0 {
+        true => (amount / target).clamp(0.0, 1.0),
+        false => 1.0,
+    };
+
+    match habit.hab_goal_direction {
+        HabGoalDirectionEnum::at_least => under(goal),
+        HabGoalDirectionEnum::at_most => over(goal),
+        HabGoalDirectionEnum::exact => under(goal - tolerance).min(over(goal + tolerance)),
+    }
+}
+
+// Date a habit's recurrence starts from, scheduled habits start from their own start date
+pub fn get_habit_reference_date(habit: &Habit) -> NaiveDate {
+    if let Some(start_date) = habit.hab_start_date {
+        return start_date;
+    }
+
+    match HABIT_CREATION_DATE_AS_REFERENCE {
+        true => habit.hab_created_at.date(),
+        false => REFERENCE_DATE.unwrap(),
+    }
+}
+
+// Whether a habit is running at a date, between its start and its end date if any
+pub fn is_habit_active(habit: &Habit, date: NaiveDate) -> bool {
+    get_habit_reference_date(habit) <= date
+        && habit.hab_end_date.is_none_or(|end_date| date <= end_date)
+}
+
+// Status of a period given what was collected within it
+pub fn get_period_status(
+    habit: &Habit,
+    amount: &BigDecimal,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    current_date: NaiveDate,
+) -> PeriodStatus {
+    if start_date > current_date {
+        return PeriodStatus::Upcoming;
+    }
+
+    if is_goal_exceeded(habit, amount) {
+        return PeriodStatus::Missed;
+    }
+
+    // Only reached minimums of growing amounts are settled before the period is over
+    let is_over = end_date < current_date;
+
+    if is_goal_met(habit, amount) && (is_over || is_goal_settled(habit)) {
+        return PeriodStatus::Done;
+    }
+
+    let has_data = amount > &BigDecimal::from(0);
+
+    // Period in progress can still be completed
+    if !is_over {
+        return match has_data {
+            true => PeriodStatus::Partial,
+            false => PeriodStatus::Upcoming,
+        };
+    }
+
+    match has_data {
+        true => PeriodStatus::Partial,
+        false => PeriodStatus::Missed,
+    }
+}
+
+// Bounds (both inclusive) of the periods of a habit overlapping a date range
+pub fn get_habit_period_bounds(
+    habit: &Habit,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Vec<(NaiveDate, NaiveDate)> {
+    let reference_date = get_habit_reference_date(habit);
+
+    // Period containing the start date begins at most one frequency step before it
+    let first_period_start = DateRange::get_next_closest_date(
+        habit.hab_freq_type,
+        Some(DateRange::get_period_start_date(
+            habit.hab_freq_type,
+            start_date,
+        )),
+        Some(reference_date),
+    );
+
+    DateRange::new(
+        end_date,
+        habit.hab_freq_type,
+        Some(first_period_start),
+        Some(reference_date),
+    )
+    .map(|period_start| {
+        let period_end =
+            get_next_closure_date(habit.hab_freq_type, period_start) - chrono::Duration::days(1);
+
+        (period_start, period_end)
+    })
+    .filter(|(_, period_end)| period_end >= &start_date)
+    .collect()
+}
+
+// Snapshot of a habit's current goal definition, in effect from the given date
+pub fn get_goal_version(habit: &Habit, valid_from: NaiveDate) -> HabitGoalVersion {
+    HabitGoalVersion {
+        hab_ver_id: Uuid::new_v4(),
+        hab_ver_valid_from: valid_from,
+        hab_ver_goal: habit.hab_goal.clone(),
+        hab_ver_goal_direction: habit.hab_goal_direction,
+        hab_ver_goal_tolerance: habit.hab_goal_tolerance.clone(),
+        hab_ver_freq_type: habit.hab_freq_type,
+        hab_ver_units: habit.hab_units.clone(),
+        hab_id: habit.hab_id,
+        hab_ver_aggregation: habit.hab_aggregation,
+        hab_ver_is_yn: habit.hab_is_yn,
+    }
+}
+
+// Habit as defined by each of its goal versions, oldest first. Versions after the first one start
+// their own recurrence on the day they became effective, or when the habit starts if later
+pub fn get_habit_versions(habit: &Habit, versions: &[HabitGoalVersion]) -> Vec<Habit> {
+    let start_date = get_habit_reference_date(habit);
+
+    let mut versions: Vec<&HabitGoalVersion> = versions
+        .iter()
+        .filter(|version| version.hab_id == habit.hab_id)
+        .collect();
+
+    versions.sort_by_key(|version| version.hab_ver_valid_from);
+
+    if versions.is_empty() {
+        return vec![habit.clone()];
+    }
+
+    versions
+        .iter()
+        .enumerate()
+        .map(|(index, version)| Habit {
+            hab_goal: version.hab_ver_goal.clone(),
+            hab_goal_direction: version.hab_ver_goal_direction,
+            hab_goal_tolerance: version.hab_ver_goal_tolerance.clone(),
+            hab_freq_type: version.hab_ver_freq_type,
+            hab_units: version.hab_ver_units.clone(),
+            hab_aggregation: version.hab_ver_aggregation,
+            hab_is_yn: version.hab_ver_is_yn,
+            hab_created_at: match index {
+                0 => habit.hab_created_at,
+                _ => version.hab_ver_valid_from.and_time(chrono::NaiveTime::MIN),
+            },
+            hab_start_date: match index {
+                0 => habit.hab_start_date,
+                _ => Some(version.hab_ver_valid_from.max(start_date)),
+            },
+            ..habit.clone()
+        })
+        .collect()
+}
+
+// First day a version is in effect
+fn get_version_start_date(versions: &[Habit], index: usize) -> NaiveDate {
+    get_habit_reference_date(&versions[index])
+}
+
+// Version of a habit in effect at a date, dates before the habit started get its first version
+pub fn get_habit_version_at(versions: &[Habit], date: NaiveDate) -> &Habit {
+    let index = (0..versions.len())
+        .rev()
+        .find(|index| get_version_start_date(versions, *index) <= date)
+        .unwrap_or(0);
+
+    &versions[index]
+}
+
+// Version a period cut short is judged against, its goal scaled down to the days it lasted
+pub fn get_prorated_version(version: &Habit, days: i64, full_days: i64) -> Habit {
+    if days >= full_days {
+        return version.clone();
+    }
+
+    let ratio = BigDecimal::from(days) / BigDecimal::from(full_days);
+
+    Habit {
+        hab_goal: (&version.hab_goal * &ratio).round(2),
+        hab_goal_tolerance: (&version.hab_goal_tolerance * &ratio).round(2),
+        ..version.clone()
+    }
+}
+
+// Bounds (both inclusive) of the periods of a habit overlapping a date range along with the
+// version each one is judged against, the one in effect when it ends. Versions keep the recurrence
+// of the previous one unless they change the frequency, then the period they fall in is cut short
+// and judged against a prorated goal. The end of the habit cuts the last period short as well
+pub fn get_versioned_period_bounds(
+    versions: &[Habit],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Vec<(NaiveDate, NaiveDate, Habit)> {
+    let mut bounds: Vec<(NaiveDate, NaiveDate, Habit)> = Vec::new();
+
+    // Versions starting a recurrence of their own
+    let anchors: Vec<usize> = (0..versions.len())
+        .filter(|index| {
+            *index == 0 || versions[*index].hab_freq_type != versions[*index - 1].hab_freq_type
+        })
+        .collect();
+
+    for (position, anchor) in anchors.iter().enumerate() {
+        let recurrence = &versions[*anchor];
+        let recurrence_start = get_version_start_date(versions, *anchor);
+        let recurrence_end = match anchors.get(position + 1) {
+            Some(next_anchor) => {
+                get_version_start_date(versions, *next_anchor) - chrono::Duration::days(1)
+            }
+            None => recurrence.hab_end_date.unwrap_or(MAXIMUM_DATE.unwrap()),
+        };
+
+        if recurrence_end < start_date
+            || recurrence_start > end_date
+            || recurrence_end < recurrence_start
+        {
+            continue;
+        }
+
+        for (period_start, period_end) in get_habit_period_bounds(
+            recurrence,
+            start_date.max(recurrence_start),
+            end_date.min(recurrence_end),
+        ) {
+            let period_start = period_start.max(recurrence_start);
+            let version = get_habit_version_at(versions, period_end.min(recurrence_end));
+
+            if period_end <= recurrence_end || position + 1 == anchors.len() {
+                bounds.push((
+                    period_start,
+                    period_end.min(recurrence_end),
+                    version.clone(),
+                ));
+                continue;
+            }
+
+            bounds.push((
+                period_start,
+                recurrence_end,
+                get_prorated_version(
+                    version,
+                    (recurrence_end - period_start).num_days() + 1,
+                    (period_end - period_start).num_days() + 1,
+                ),
+            ));
+        }
+    }
+
+    bounds
+}
+
+// Periods of a habit overlapping a date range, each one evaluated against the habit's data and
+// the version of its goal in effect at the time
+pub fn build_calendar_periods(
+    versions: &[Habit],
+    data: &[HabitDataCollected],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    current_date: NaiveDate,
+) -> Vec<CalendarPeriod> {
+    let mut periods: Vec<CalendarPeriod> = Vec::new();
+    let habit = &versions[0];
+    let reference_date = get_habit_reference_date(habit);
+
+    // Habit wasn't running yet during the beginning of the range
+    if start_date < reference_date {
+        periods.push(CalendarPeriod {
+            hab_id: habit.hab_id,
+            start_date,
+            end_date: end_date.min(reference_date - chrono::Duration::days(1)),
+            amount: BigDecimal::from(0),
+            goal: habit.hab_goal.clone(),
+            status: PeriodStatus::Paused,
+        });
+    }
+
+    for (period_start, period_end, version) in
+        get_versioned_period_bounds(versions, start_date, end_date)
+    {
+        let period_data: Vec<&HabitDataCollected> = data
+            .iter()
+            .filter(|item| {
+                item.hab_dat_collected_at >= period_start && item.hab_dat_collected_at <= period_end
+            })
+            .collect();
+
+        let amount = aggregate_period_amount(&version, &period_data);
+        let status = get_period_status(&version, &amount, period_start, period_end, current_date);
+
+        periods.push(CalendarPeriod {
+            hab_id: version.hab_id,
+            start_date: period_start,
+            end_date: period_end,
+            amount,
+            goal: version.hab_goal.clone(),
+            status,
+        });
+    }
+
+    // Nor after its end
+    if let Some(last_date) = habit.hab_end_date.filter(|last_date| last_date < &end_date) {
+        periods.push(CalendarPeriod {
+            hab_id: habit.hab_id,
+            start_date: start_date.max(last_date + chrono::Duration::days(1)),
+            end_date,
+            amount: BigDecimal::from(0),
+            goal: versions[versions.len() - 1].hab_goal.clone(),
+            status: PeriodStatus::Paused,
+        });
+    }
+
+    periods
+}
+
+// One entry per day between two dates, each day's amount relative to the goal in effect that day
+// (averaged over habits running that day when there are several). Habits come with their versions
+pub fn build_heatmap_days(
+    habits: &[Vec<Habit>],
+    daily_amounts: &[HabitDailyAmount],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    current_date: NaiveDate,
+) -> Vec<HeatmapDay> {
+    let amounts: std::collections::HashMap<(Uuid, NaiveDate), &BigDecimal> = daily_amounts
+        .iter()
+        .map(|item| ((item.hab_id, item.date), &item.amount))
+        .collect();
+
+    let mut days: Vec<HeatmapDay> = Vec::new();
+    let mut date = start_date;
+
+    while date <= end_date {
+        if date > current_date {
+            days.push(HeatmapDay {
+                value: 0.0,
+                status: HeatmapDayStatus::Upcoming,
+            });
+        } else {
+            let values: Vec<f64> = habits
+                .iter()
+                .filter(|versions| is_habit_active(&versions[0], date))
+                .map(|versions| {
+                    let habit = get_habit_version_at(versions, date);
+                    let amount = amounts
+                        .get(&(habit.hab_id, date))
+                        .and_then(|amount| amount.to_f64())
+                        .unwrap_or(0.0);
+
+                    get_goal_progress(habit, amount)
+                })
+                .collect();
+
+            days.push(match values.is_empty() {
+                true => HeatmapDay {
+                    value: 0.0,
+                    status: HeatmapDayStatus::Paused,
+                },
+                false => HeatmapDay {
+                    value: values.iter().sum::<f64>() / values.len() as f64,
+                    status: HeatmapDayStatus::Active,
+                },
+            });
+        }
+
+        date = match date.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    days
+}
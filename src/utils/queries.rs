@@ -1,8 +1,52 @@
-use crate::models::{
-    api::habit_api_models::*,
-    database::{Habit, HabitDataCollected},
+use crate::{
+    error::Error,
+    models::{
+        api::{data_api_models::HabitDataCreateSchema, habit_api_models::*},
+        database::{
+            HabAggregationEnum, HabChecklistModeEnum, HabGoalDirectionEnum, Habit,
+            HabitDataCollected,
+        },
+    },
+    utils::{time::DateRange, HABIT_CREATION_DATE_AS_REFERENCE},
 };
 
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+
+// Goal version in effect at the given date as v, dates before the first version get that one.
+// Expects habit as h
+pub fn get_goal_version_join_sql(date: &str) -> String {
+    format!(
+        "LEFT JOIN LATERAL ( \
+            SELECT gv.hab_ver_aggregation, gv.hab_ver_is_yn \
+            FROM habit_goal_version gv \
+            WHERE gv.hab_id = h.hab_id \
+            ORDER BY gv.hab_ver_valid_from <= {date} DESC, \
+                ABS({date} - gv.hab_ver_valid_from) ASC \
+            LIMIT 1 \
+        ) v ON TRUE"
+    )
+}
+
+// Amount of a group of records of a single habit following the aggregation mode of their goal
+// version, Y/N habits count the records and the last one logged wins ties between records of the
+// same day. Habits without versions fall back to their own settings.
+// Expects habit_data_collected as hd and get_goal_version_join_sql, grouped by h.hab_id and a
+// single version
+pub const AGGREGATED_AMOUNT_SQL: &str = "CASE \
+        WHEN BOOL_OR(COALESCE(v.hab_ver_is_yn, h.hab_is_yn)) THEN COUNT(hd.hab_dat_id)::numeric \
+        WHEN MIN(COALESCE(v.hab_ver_aggregation, h.hab_aggregation)) = 'last' THEN \
+            (ARRAY_AGG(hd.hab_dat_amount ORDER BY hd.hab_dat_collected_at DESC, \
+                hd.hab_dat_created_at DESC, hd.hab_dat_id DESC))[1] \
+        WHEN MIN(COALESCE(v.hab_ver_aggregation, h.hab_aggregation)) = 'average' THEN \
+            ROUND(AVG(hd.hab_dat_amount), 2) \
+        WHEN MIN(COALESCE(v.hab_ver_aggregation, h.hab_aggregation)) = 'min' THEN \
+            MIN(hd.hab_dat_amount) \
+        WHEN MIN(COALESCE(v.hab_ver_aggregation, h.hab_aggregation)) = 'max' THEN \
+            MAX(hd.hab_dat_amount) \
+        ELSE SUM(hd.hab_dat_amount) \
+    END";
+
 pub fn join_habit_with_data(
     habit_item: Habit,
     data_array: Vec<HabitDataCollected>,
@@ -17,8 +61,88 @@ pub fn join_habit_with_data(
         hab_units: habit_item.hab_units,
         hab_goal: habit_item.hab_goal,
         hab_freq_type: habit_item.hab_freq_type,
+        hab_goal_direction: habit_item.hab_goal_direction,
+        hab_goal_tolerance: habit_item.hab_goal_tolerance,
+        hab_aggregation: habit_item.hab_aggregation,
+        hab_start_date: habit_item.hab_start_date,
+        hab_end_date: habit_item.hab_end_date,
+        hab_checklist_mode: habit_item.hab_checklist_mode,
         usr_id: habit_item.usr_id,
         cat_id: habit_item.cat_id,
         data: data_array,
     }
 }
+
+// Habit about to be created by a user, closing for the first time when it starts
+pub fn build_new_habit(
+    user_id: String,
+    data: HabitCreateSchema,
+    current_datetime: chrono::NaiveDateTime,
+) -> Result<Habit, Error> {
+    let current_date = current_datetime.date();
+
+    // Scheduled habits begin at their start date
+    let start_date = data.start_date.unwrap_or(current_date);
+
+    if data.end_date.is_some_and(|end_date| end_date < start_date) {
+        return Err(Error::BadRequest(
+            "End date must not be before start date".to_string(),
+        ));
+    }
+
+    let closure_date = if HABIT_CREATION_DATE_AS_REFERENCE || data.start_date.is_some() {
+        start_date
+    } else {
+        DateRange::get_next_closest_date(
+            data.frequency_type,
+            // Change if habit start should be another (usually a week later from current date)
+            Some(start_date),
+            // Change to None when reference date should be another (usually a constant)
+            None,
+        )
+    };
+
+    let habit = Habit {
+        hab_id: Uuid::new_v4(),
+        hab_name: data.name,
+        hab_description: data.description,
+        hab_created_at: current_datetime,
+        hab_updated_at: current_datetime,
+        hab_is_favorite: data.is_favorite,
+        hab_is_yn: data.is_yn,
+        hab_color: data.color,
+        hab_units: data.units,
+        hab_goal: data.goal,
+        hab_freq_type: data.frequency_type,
+
+        hab_next_closure_date: closure_date,
+        hab_location: data.location,
+        hab_goal_direction: data
+            .goal_direction
+            .unwrap_or(HabGoalDirectionEnum::at_least),
+        hab_goal_tolerance: data.goal_tolerance.unwrap_or(BigDecimal::from(0)),
+        hab_aggregation: data.aggregation.unwrap_or(HabAggregationEnum::sum),
+        hab_start_date: data.start_date,
+        hab_end_date: data.end_date,
+        hab_checklist_mode: data.checklist_mode.unwrap_or(HabChecklistModeEnum::count),
+
+        usr_id: user_id,
+        cat_id: data.category,
+    };
+
+    Ok(habit)
+}
+
+// Habit data record about to be logged, today by default
+pub fn build_habit_data(data: HabitDataCreateSchema) -> HabitDataCollected {
+    HabitDataCollected {
+        hab_dat_id: Uuid::new_v4(),
+        hab_dat_amount: data.amount,
+        hab_dat_collected_at: data
+            .collected_at
+            .unwrap_or_else(|| chrono::Utc::now().naive_utc().date()),
+        hab_id: data.habit_id,
+        hab_dat_mood: data.mood,
+        hab_dat_created_at: chrono::Utc::now().naive_utc(),
+    }
+}
@@ -0,0 +1,339 @@
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+use crate::{
+    models::{
+        api::{
+            events_api_models::HabitDailyAmount,
+            report_api_models::{HabitReview, Review, ReviewPeriod, ReviewStreakChange},
+        },
+        database::Habit,
+    },
+    utils::{
+        periods::{aggregate_daily_amounts, get_versioned_period_bounds, is_goal_met},
+        svg::escape_xml,
+        MAX_PERIOD_DAYS,
+    },
+};
+
+// First and last day of the week (Monday first) or month containing a date
+pub fn get_review_window(period: ReviewPeriod, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    match period {
+        ReviewPeriod::Week => {
+            let start_date = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+
+            (start_date, start_date + Duration::days(6))
+        }
+        ReviewPeriod::Month => {
+            let start_date = date.with_day(1).unwrap_or(date);
+            let end_date = start_date
+                .checked_add_months(Months::new(1))
+                .map(|next_month| next_month - Duration::days(1))
+                .unwrap_or(start_date);
+
+            (start_date, end_date)
+        }
+    }
+}
+
+fn get_window_amount(
+    habit: &Habit,
+    daily_amounts: &[HabitDailyAmount],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> BigDecimal {
+    let window: Vec<&HabitDailyAmount> = daily_amounts
+        .iter()
+        .filter(|item| {
+            item.hab_id == habit.hab_id && item.date >= start_date && item.date <= end_date
+        })
+        .collect();
+
+    aggregate_daily_amounts(habit, &window)
+}
+
+fn get_completion(periods: &[(NaiveDate, bool)]) -> Option<f64> {
+    match periods.len() {
+        0 => None,
+        length => Some(periods.iter().filter(|(_, met)| *met).count() as f64 / length as f64),
+    }
+}
+
+// Compare the window containing a date with the one right before it, for each habit. Only
+// periods already over at the current date are evaluated
+pub fn build_review(
+    habits: &[Vec<Habit>],
+    daily_amounts: &[HabitDailyAmount],
+    period: ReviewPeriod,
+    date: NaiveDate,
+    current_date: NaiveDate,
+) -> Review {
+    let (current_start, current_end) = get_review_window(period, date);
+    let (previous_start, previous_end) =
+        get_review_window(period, current_start - Duration::days(1));
+
+    // Going one full period back finds what came before the current window for every frequency
+    let lookback_date = current_start - Duration::days(MAX_PERIOD_DAYS);
+
+    let mut reviews: Vec<HabitReview> = Vec::new();
+
+    for versions in habits {
+        // Latest version describes the habit, periods are judged against their own
+        let habit = &versions[versions.len() - 1];
+
+        // End date of each evaluated period along with whether it met the goal
+        let periods: Vec<(NaiveDate, bool)> =
+            get_versioned_period_bounds(versions, lookback_date, current_end)
+                .into_iter()
+                .filter(|(_, end_date, _)| end_date >= &lookback_date && end_date < &current_date)
+                .map(|(start_date, end_date, version)| {
+                    let amount = get_window_amount(&version, daily_amounts, start_date, end_date);
+
+                    (end_date, is_goal_met(&version, &amount))
+                })
+                .collect();
+
+        let in_window = |start_date: NaiveDate, end_date: NaiveDate| -> Vec<(NaiveDate, bool)> {
+            periods
+                .iter()
+                .filter(|(period_end, _)| period_end >= &start_date && period_end <= &end_date)
+                .copied()
+                .collect()
+        };
+
+        let current_periods = in_window(current_start, current_end);
+        let previous_periods = in_window(previous_start, previous_end);
+
+        let last_before = periods
+            .iter()
+            .rev()
+            .find(|(end_date, _)| end_date < &current_start)
+            .map(|(_, met)| *met);
+        let last_current = current_periods.last().map(|(_, met)| *met);
+
+        let streak = match (last_before, last_current) {
+            (Some(true), Some(true)) => ReviewStreakChange::Kept,
+            (Some(true), Some(false)) => ReviewStreakChange::Broken,
+            (_, Some(true)) => ReviewStreakChange::Started,
+            _ => ReviewStreakChange::None,
+        };
+
+        let current_total = get_window_amount(habit, daily_amounts, current_start, current_end);
+        let previous_total = get_window_amount(habit, daily_amounts, previous_start, previous_end);
+
+        let current_completion = get_completion(&current_periods);
+        let previous_completion = get_completion(&previous_periods);
+
+        reviews.push(HabitReview {
+            hab_id: habit.hab_id,
+            hab_name: habit.hab_name.clone(),
+            hab_units: habit.hab_units.clone(),
+            total_delta: &current_total - &previous_total,
+            current_total,
+            previous_total,
+            completion_delta: current_completion
+                .zip(previous_completion)
+                .map(|(current, previous)| current - previous),
+            current_completion,
+            previous_completion,
+            streak,
+        });
+    }
+
+    // Ties on completion go to the habit that collected the most
+    let mut ranked: Vec<&HabitReview> = reviews
+        .iter()
+        .filter(|review| review.current_completion.is_some())
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.current_completion
+            .partial_cmp(&a.current_completion)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.current_total.cmp(&a.current_total))
+    });
+
+    Review {
+        period,
+        current_start_date: current_start,
+        current_end_date: current_end,
+        previous_start_date: previous_start,
+        previous_end_date: previous_end,
+        most_consistent: ranked.first().map(|review| review.hab_id),
+        least_consistent: ranked.last().map(|review| review.hab_id),
+        habits: reviews,
+    }
+}
+
+fn get_review_title(review: &Review) -> &'static str {
+    match review.period {
+        ReviewPeriod::Week => "Weekly review",
+        ReviewPeriod::Month => "Monthly review",
+    }
+}
+
+fn format_amount(amount: &BigDecimal) -> String {
+    format!("{}", amount.to_f64().unwrap_or(0.0))
+}
+
+fn format_amount_delta(amount: &BigDecimal) -> String {
+    let value = amount.to_f64().unwrap_or(0.0);
+
+    match value > 0.0 {
+        true => format!("+{}", value),
+        false => format!("{}", value),
+    }
+}
+
+fn format_rate(rate: Option<f64>) -> String {
+    match rate {
+        Some(rate) => format!("{:.0}%", rate * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+fn format_rate_delta(rate: Option<f64>) -> String {
+    match rate {
+        Some(rate) if rate > 0.0 => format!("+{:.0}%", rate * 100.0),
+        Some(rate) => format!("{:.0}%", rate * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+fn format_streak(streak: ReviewStreakChange) -> &'static str {
+    match streak {
+        ReviewStreakChange::Started => "started",
+        ReviewStreakChange::Broken => "broken",
+        ReviewStreakChange::Kept => "kept",
+        ReviewStreakChange::None => "-",
+    }
+}
+
+// Habits that missed their goal at least once within the current window
+fn get_missed_habits(review: &Review) -> Vec<&str> {
+    review
+        .habits
+        .iter()
+        .filter(|habit| matches!(habit.current_completion, Some(rate) if rate < 1.0))
+        .map(|habit| habit.hab_name.as_str())
+        .collect()
+}
+
+fn get_habit_name(review: &Review, hab_id: Option<uuid::Uuid>) -> Option<&str> {
+    review
+        .habits
+        .iter()
+        .find(|habit| Some(habit.hab_id) == hab_id)
+        .map(|habit| habit.hab_name.as_str())
+}
+
+// Row cells of a habit, in the order of the digest table headers
+fn get_review_cells(habit: &HabitReview) -> [String; 6] {
+    [
+        habit.hab_name.clone(),
+        format!(
+            "{} {}",
+            format_amount(&habit.current_total),
+            habit.hab_units
+        ),
+        format_amount_delta(&habit.total_delta),
+        format_rate(habit.current_completion),
+        format_rate_delta(habit.completion_delta),
+        format_streak(habit.streak).to_string(),
+    ]
+}
+
+const REVIEW_HEADERS: [&str; 6] = ["Habit", "Total", "Change", "Completion", "Change", "Streak"];
+
+pub fn render_review_markdown(review: &Review) -> String {
+    let mut markdown = format!(
+        "# {}\n\n{} to {}, compared with {} to {}\n\n",
+        get_review_title(review),
+        review.current_start_date,
+        review.current_end_date,
+        review.previous_start_date,
+        review.previous_end_date,
+    );
+
+    markdown.push_str(&format!("| {} |\n", REVIEW_HEADERS.join(" | ")));
+    markdown.push_str(&format!("|{}\n", " --- |".repeat(REVIEW_HEADERS.len())));
+
+    for habit in &review.habits {
+        let cells: Vec<String> = get_review_cells(habit)
+            .iter()
+            .map(|cell| cell.replace('|', "\\|"))
+            .collect();
+
+        markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+
+    if let Some(name) = get_habit_name(review, review.most_consistent) {
+        markdown.push_str(&format!("\nMost consistent: **{}**\n", name));
+    }
+
+    if let Some(name) = get_habit_name(review, review.least_consistent) {
+        markdown.push_str(&format!("\nLeast consistent: **{}**\n", name));
+    }
+
+    let missed = get_missed_habits(review);
+
+    if !missed.is_empty() {
+        markdown.push_str(&format!("\nMissed goals: {}\n", missed.join(", ")));
+    }
+
+    markdown
+}
+
+pub fn render_review_html(review: &Review) -> String {
+    let mut html = format!(
+        "<h1>{}</h1><p>{} to {}, compared with {} to {}</p><table><thead><tr>",
+        get_review_title(review),
+        review.current_start_date,
+        review.current_end_date,
+        review.previous_start_date,
+        review.previous_end_date,
+    );
+
+    for header in REVIEW_HEADERS {
+        html.push_str(&format!("<th>{}</th>", header));
+    }
+
+    html.push_str("</tr></thead><tbody>");
+
+    for habit in &review.habits {
+        html.push_str("<tr>");
+
+        for cell in get_review_cells(habit) {
+            html.push_str(&format!("<td>{}</td>", escape_xml(&cell)));
+        }
+
+        html.push_str("</tr>");
+    }
+
+    html.push_str("</tbody></table>");
+
+    if let Some(name) = get_habit_name(review, review.most_consistent) {
+        html.push_str(&format!(
+            "<p>Most consistent: <strong>{}</strong></p>",
+            escape_xml(name)
+        ));
+    }
+
+    if let Some(name) = get_habit_name(review, review.least_consistent) {
+        html.push_str(&format!(
+            "<p>Least consistent: <strong>{}</strong></p>",
+            escape_xml(name)
+        ));
+    }
+
+    let missed = get_missed_habits(review);
+
+    if !missed.is_empty() {
+        html.push_str(&format!(
+            "<p>Missed goals: {}</p>",
+            escape_xml(&missed.join(", "))
+        ));
+    }
+
+    html
+}
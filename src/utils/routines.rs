@@ -0,0 +1,97 @@
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        api::{
+            events_api_models::HabitDailyAmount,
+            routine_api_models::{RoutineHabitProgress, RoutineProgress},
+        },
+        database::Habit,
+    },
+    utils::{periods::is_habit_active, ROUTINE_HISTORY_DAYS},
+};
+
+// Progress of a routine on a day along with its streaks. A day is complete once every habit of
+// the routine running on it was logged, days without any running habit don't break streaks.
+// Habits come in the routine's order, daily amounts should cover the history window
+pub fn build_routine_progress(
+    rou_id: Uuid,
+    habits: &[Habit],
+    daily_amounts: &[HabitDailyAmount],
+    date: NaiveDate,
+) -> RoutineProgress {
+    let logged: HashMap<(Uuid, NaiveDate), &BigDecimal> = daily_amounts
+        .iter()
+        .filter(|item| item.records > 0)
+        .map(|item| ((item.hab_id, item.date), &item.amount))
+        .collect();
+
+    let is_complete_on = |day: NaiveDate| -> Option<bool> {
+        let active: Vec<&Habit> = habits
+            .iter()
+            .filter(|habit| is_habit_active(habit, day))
+            .collect();
+
+        match active.is_empty() {
+            true => None,
+            false => Some(
+                active
+                    .iter()
+                    .all(|habit| logged.contains_key(&(habit.hab_id, day))),
+            ),
+        }
+    };
+
+    let mut streak = 0;
+    let mut longest_streak = 0;
+    let mut day = date - Duration::days(ROUTINE_HISTORY_DAYS);
+
+    while day <= date {
+        match is_complete_on(day) {
+            Some(true) => {
+                streak += 1;
+                longest_streak = longest_streak.max(streak);
+            }
+            // The day itself is still in progress
+            Some(false) if day < date => streak = 0,
+            _ => {}
+        }
+
+        day += Duration::days(1);
+    }
+
+    let progress: Vec<RoutineHabitProgress> = habits
+        .iter()
+        .filter(|habit| is_habit_active(habit, date))
+        .map(|habit| {
+            let amount = logged.get(&(habit.hab_id, date));
+
+            RoutineHabitProgress {
+                hab_id: habit.hab_id,
+                hab_name: habit.hab_name.clone(),
+                amount: amount.map(|amount| (*amount).clone()).unwrap_or_default(),
+                is_done: amount.is_some(),
+            }
+        })
+        .collect();
+
+    let done_count = progress.iter().filter(|habit| habit.is_done).count() as i64;
+
+    RoutineProgress {
+        rou_id,
+        date,
+        done_count,
+        completion_rate: match progress.len() {
+            0 => None,
+            length => Some(done_count as f64 / length as f64),
+        },
+        is_complete: is_complete_on(date).unwrap_or(false),
+        habits: progress,
+        streak,
+        longest_streak,
+    }
+}
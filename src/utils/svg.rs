@@ -0,0 +1,217 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::models::api::events_api_models::{HeatmapDay, HeatmapDayStatus};
+
+const DEFAULT_COLOR: &str = "#4c1";
+const FONT_FAMILY: &str = "Verdana,Geneva,DejaVu Sans,sans-serif";
+
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 160.0;
+const CHART_PADDING: f64 = 24.0;
+
+const HEATMAP_CELL: i64 = 11;
+const HEATMAP_GAP: i64 = 2;
+
+// Escape text placed inside SVG elements or attributes
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// Habit colors are stored as 6 hex digits without '#'
+pub fn get_svg_color(hab_color: &str) -> String {
+    match hab_color.len() == 6 && hab_color.chars().all(|c| c.is_ascii_hexdigit()) {
+        true => format!("#{}", hab_color),
+        false => DEFAULT_COLOR.to_string(),
+    }
+}
+
+// Rough text width for the badge font, there's no font metrics available server side
+fn get_text_width(text: &str) -> i64 {
+    text.chars().count() as i64 * 7 + 10
+}
+
+// Flat two-part badge, label on the left and value on the right
+pub fn render_badge(label: &str, value: &str, color: &str) -> String {
+    let label_width = get_text_width(label);
+    let value_width = get_text_width(value);
+    let width = label_width + value_width;
+
+    let label = escape_xml(label);
+    let value = escape_xml(value);
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"20\" role=\"img\" \
+            aria-label=\"{label}: {value}\">\
+            <title>{label}: {value}</title>\
+            <rect width=\"{label_width}\" height=\"20\" fill=\"#555\"/>\
+            <rect x=\"{label_width}\" width=\"{value_width}\" height=\"20\" fill=\"{color}\"/>\
+            <g fill=\"#fff\" text-anchor=\"middle\" font-family=\"{FONT_FAMILY}\" font-size=\"11\">\
+                <text x=\"{label_x}\" y=\"14\">{label}</text>\
+                <text x=\"{value_x}\" y=\"14\">{value}</text>\
+            </g>\
+        </svg>",
+        label_x = label_width as f64 / 2.0,
+        value_x = label_width as f64 + value_width as f64 / 2.0,
+    )
+}
+
+// Year grid with a column per week and a row per weekday (Monday first)
+pub fn render_heatmap(
+    title: &str,
+    days: &[HeatmapDay],
+    start_date: NaiveDate,
+    color: &str,
+) -> String {
+    let step = HEATMAP_CELL + HEATMAP_GAP;
+    let offset = start_date.weekday().num_days_from_monday() as i64;
+    let weeks = (days.len() as i64 + offset + 6) / 7;
+
+    let width = weeks * step + HEATMAP_GAP;
+    let height = 7 * step + HEATMAP_GAP + 20;
+
+    let mut cells = String::new();
+
+    for (index, day) in days.iter().enumerate() {
+        let position = index as i64 + offset;
+        let x = (position / 7) * step + HEATMAP_GAP;
+        let y = (position % 7) * step + HEATMAP_GAP + 20;
+
+        let date = start_date + chrono::Duration::days(index as i64);
+
+        // Intensity goes through the habit's color opacity, empty days stay grey
+        let (fill, opacity, label) = match day.status {
+            HeatmapDayStatus::Active if day.value > 0.0 => (
+                color,
+                0.2 + 0.8 * day.value,
+                format!("{}: {:.0}%", date, day.value * 100.0),
+            ),
+            HeatmapDayStatus::Active => ("#ebedf0", 1.0, format!("{}: 0%", date)),
+            HeatmapDayStatus::Paused => ("#f6f8fa", 1.0, format!("{}: paused", date)),
+            HeatmapDayStatus::Upcoming => ("#ffffff", 1.0, format!("{}", date)),
+        };
+
+        cells.push_str(&format!(
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{HEATMAP_CELL}\" height=\"{HEATMAP_CELL}\" \
+                rx=\"2\" fill=\"{fill}\" fill-opacity=\"{opacity:.2}\" stroke=\"#d0d7de\" \
+                stroke-width=\"0.5\"><title>{label}</title></rect>",
+        ));
+    }
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" role=\"img\">\
+            <text x=\"{HEATMAP_GAP}\" y=\"14\" font-family=\"{FONT_FAMILY}\" font-size=\"12\">{title}</text>\
+            {cells}\
+        </svg>",
+        title = escape_xml(title),
+    )
+}
+
+// Chart frame shared by bar and line charts: title, baseline, maximum and range labels
+fn render_chart(title: &str, points: &[(NaiveDate, f64)], content: &str) -> String {
+    let max = points.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+    let baseline = CHART_HEIGHT - CHART_PADDING;
+
+    let range = match (points.first(), points.last()) {
+        (Some((first, _)), Some((last, _))) => format!(
+            "<text x=\"{CHART_PADDING}\" y=\"{y}\">{first}</text>\
+             <text x=\"{x}\" y=\"{y}\" text-anchor=\"end\">{last}</text>",
+            x = CHART_WIDTH - CHART_PADDING,
+            y = CHART_HEIGHT - 8.0,
+        ),
+        _ => String::new(),
+    };
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\" \
+            role=\"img\">\
+            <g font-family=\"{FONT_FAMILY}\" font-size=\"11\" fill=\"#555\">\
+                <text x=\"{CHART_PADDING}\" y=\"14\" font-size=\"12\" fill=\"#000\">{title}</text>\
+                <text x=\"{max_x}\" y=\"14\" text-anchor=\"end\">max {max}</text>\
+                {range}\
+            </g>\
+            <line x1=\"{CHART_PADDING}\" y1=\"{baseline}\" x2=\"{max_x}\" y2=\"{baseline}\" \
+                stroke=\"#d0d7de\"/>\
+            {content}\
+        </svg>",
+        title = escape_xml(title),
+        max_x = CHART_WIDTH - CHART_PADDING,
+    )
+}
+
+// Scale a value to the chart's drawing area, returns its y coordinate
+fn get_chart_y(value: f64, max: f64) -> f64 {
+    let baseline = CHART_HEIGHT - CHART_PADDING;
+
+    match max > 0.0 {
+        true => baseline - (value / max) * (CHART_HEIGHT - 2.0 * CHART_PADDING),
+        false => baseline,
+    }
+}
+
+pub fn render_bar_chart(title: &str, points: &[(NaiveDate, f64)], color: &str) -> String {
+    let max = points.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+    let bar_width = (CHART_WIDTH - 2.0 * CHART_PADDING) / points.len().max(1) as f64;
+
+    let bars: String = points
+        .iter()
+        .enumerate()
+        .map(|(index, (date, value))| {
+            let y = get_chart_y(*value, max);
+
+            format!(
+                "<rect x=\"{x:.2}\" y=\"{y:.2}\" width=\"{width:.2}\" height=\"{height:.2}\" \
+                    fill=\"{color}\"><title>{date}: {value}</title></rect>",
+                x = CHART_PADDING + index as f64 * bar_width + bar_width * 0.1,
+                width = bar_width * 0.8,
+                height = CHART_HEIGHT - CHART_PADDING - y,
+            )
+        })
+        .collect();
+
+    render_chart(title, points, &bars)
+}
+
+pub fn render_line_chart(title: &str, points: &[(NaiveDate, f64)], color: &str) -> String {
+    let max = points.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+
+    // Points are spread evenly, a single point sits in the middle
+    let step = match points.len() {
+        0 | 1 => 0.0,
+        length => (CHART_WIDTH - 2.0 * CHART_PADDING) / (length - 1) as f64,
+    };
+
+    let get_x = |index: usize| match points.len() {
+        1 => CHART_WIDTH / 2.0,
+        _ => CHART_PADDING + index as f64 * step,
+    };
+
+    let line: Vec<String> = points
+        .iter()
+        .enumerate()
+        .map(|(index, (_, value))| format!("{:.2},{:.2}", get_x(index), get_chart_y(*value, max)))
+        .collect();
+
+    let dots: String = points
+        .iter()
+        .enumerate()
+        .map(|(index, (date, value))| {
+            format!(
+                "<circle cx=\"{x:.2}\" cy=\"{y:.2}\" r=\"2.5\" fill=\"{color}\">\
+                    <title>{date}: {value}</title></circle>",
+                x = get_x(index),
+                y = get_chart_y(*value, max),
+            )
+        })
+        .collect();
+
+    let content = format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"2\"/>{dots}",
+        line.join(" "),
+    );
+
+    render_chart(title, points, &content)
+}
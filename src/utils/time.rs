@@ -1,7 +1,6 @@
-use chrono::{Datelike, Duration, Months, NaiveDate};
-use diesel::sql_types::Date;
+use chrono::{Duration, Months, NaiveDate};
 
-use crate::{models::database::HabFreqTypeEnum, schema::sql_types};
+use crate::models::database::HabFreqTypeEnum;
 use std::mem;
 
 pub const REFERENCE_DATE: Option<NaiveDate> = NaiveDate::from_ymd_opt(2018, 1, 1);
@@ -14,7 +13,7 @@ pub struct DateRange {
     end_date: NaiveDate,
 
     // Function to get next date based on recurrence type
-    get_next_date: fn(NaiveDate) -> NaiveDate,
+    get_next_date: Box<dyn Fn(NaiveDate) -> NaiveDate>,
 }
 
 impl DateRange {
@@ -60,116 +59,82 @@ impl DateRange {
         }
     }
 
-    pub fn generate_date_generator(frequency_type: HabFreqTypeEnum) -> fn(NaiveDate) -> NaiveDate {
-        let get_next_date = match frequency_type {
-            HabFreqTypeEnum::daily => |date: NaiveDate| date + Duration::days(1),
-            HabFreqTypeEnum::daily2 => |date: NaiveDate| date + Duration::days(2),
-            HabFreqTypeEnum::weekly => |date: NaiveDate| date + Duration::weeks(1),
-            HabFreqTypeEnum::weekly2 => |date: NaiveDate| date + Duration::weeks(2),
-            HabFreqTypeEnum::monthly => {
-                |date: NaiveDate| date.checked_add_months(Months::new(1)).unwrap()
-            }
-            HabFreqTypeEnum::monthly2 => {
-                |date: NaiveDate| date.checked_add_months(Months::new(2)).unwrap()
-            }
+    pub fn generate_date_generator(
+        frequency_type: HabFreqTypeEnum,
+    ) -> Box<dyn Fn(NaiveDate) -> NaiveDate> {
+        // Every recurrence step goes through the same closure computation used by jobs
+        Box::new(move |date: NaiveDate| get_next_closure_date(frequency_type, date))
+    }
+
+    // First day of the period that ends right before the given closure date
+    pub fn get_period_start_date(
+        frequency_type: HabFreqTypeEnum,
+        closure_date: NaiveDate,
+    ) -> NaiveDate {
+        let period_start = match frequency_type {
+            HabFreqTypeEnum::daily => closure_date.checked_sub_signed(Duration::days(1)),
+            HabFreqTypeEnum::daily2 => closure_date.checked_sub_signed(Duration::days(2)),
+            HabFreqTypeEnum::weekly => closure_date.checked_sub_signed(Duration::weeks(1)),
+            HabFreqTypeEnum::weekly2 => closure_date.checked_sub_signed(Duration::weeks(2)),
+            HabFreqTypeEnum::monthly => closure_date.checked_sub_months(Months::new(1)),
+            HabFreqTypeEnum::monthly2 => closure_date.checked_sub_months(Months::new(2)),
         };
 
-        get_next_date
+        period_start.unwrap_or(MINIMUM_DATE.unwrap())
     }
 
+    // Closest occurrence on or after start date of the recurrence that begins at the reference
+    // date, occurrences being the dates reached by repeatedly applying get_next_closure_date
     pub fn get_next_closest_date(
         frequency_type: HabFreqTypeEnum,
         start_date: Option<NaiveDate>,
         reference_date: Option<NaiveDate>,
     ) -> NaiveDate {
-        let frequency_type = frequency_type;
-
         // Start date should be always the current date
-        let mut start_date = match start_date {
+        let start_date = match start_date {
             Some(date) => date,
             None => chrono::Utc::now().naive_utc().date(),
         };
-        let frequency_data = match reference_date {
+        let reference_date = match reference_date {
             Some(date) => date,
             None => REFERENCE_DATE.unwrap_or(chrono::Utc::now().naive_utc().date()),
         };
 
-        // Reference date represents the start of the habit, so we must match the start date with the reference date
-        if start_date < frequency_data {
-            start_date = frequency_data;
+        // Reference date represents the start of the habit, nothing happens before it
+        if start_date <= reference_date {
+            return reference_date;
         }
 
-        // When start date is less than end date, an offset must be applied to initial date
-
-        // Get separation between dates
-        let difference = frequency_data - start_date;
-
-        let reference_day_of_week = frequency_data.weekday().num_days_from_monday();
-        let start_day_of_week = start_date.weekday().num_days_from_monday();
-        let reference_day_of_month = frequency_data.day();
-        let start_day_of_month = start_date.day();
-
-        let new_start_date = match frequency_type {
-            HabFreqTypeEnum::daily => start_date + Duration::days(difference.num_days()),
+        let step_days: Option<i64> = match frequency_type {
+            HabFreqTypeEnum::daily => Some(1),
+            HabFreqTypeEnum::daily2 => Some(2),
+            HabFreqTypeEnum::weekly => Some(7),
+            HabFreqTypeEnum::weekly2 => Some(14),
+            HabFreqTypeEnum::monthly | HabFreqTypeEnum::monthly2 => None,
+        };
 
-            // TODO: Fix this
-            HabFreqTypeEnum::daily2 => {
-                let difference_parity = difference.num_days() % 2;
+        match step_days {
+            Some(step_days) => {
+                // Fixed length steps, round the elapsed days up to a whole number of steps
+                let elapsed_days = (start_date - reference_date).num_days();
+                let steps = (elapsed_days + step_days - 1) / step_days;
 
-                start_date + Duration::days(difference.num_days() + difference_parity)
+                reference_date
+                    .checked_add_signed(Duration::days(steps * step_days))
+                    .unwrap_or(MAXIMUM_DATE.unwrap())
             }
-            HabFreqTypeEnum::weekly => {
-                let offset_days = (reference_day_of_week - start_day_of_week) % 7;
+            None => {
+                // Month steps clamp to the last day of shorter months, which moves every following
+                // occurrence, so the recurrence is walked instead of computed
+                let mut date = reference_date;
 
-                start_date + Duration::days(offset_days as i64)
-            }
-            HabFreqTypeEnum::weekly2 => {
-                let full_weeks_difference_parity = difference.num_weeks() % 2;
-                let offset_days = (reference_day_of_week - start_day_of_week) % 7;
-
-                // Complete a week when difference is odd
-                start_date
-                    + Duration::days(offset_days as i64)
-                    + Duration::weeks(full_weeks_difference_parity)
-            }
-            HabFreqTypeEnum::monthly => {
-                if start_day_of_month < reference_day_of_month {
-                    // Just a fixed days offset is needed
-                    start_date
-                        + Duration::days((reference_day_of_month - start_day_of_month) as i64)
-                } else {
-                    // Start day has passed reference day, so we must add a month and then subtract the difference
-                    // to set the same day of the month
-                    start_date.checked_add_months(Months::new(1)).unwrap()
-                        + Duration::days(-((start_day_of_month - reference_day_of_month) as i64))
-                }
-            }
-            HabFreqTypeEnum::monthly2 => {
-                let reference_day_of_month = frequency_data.day();
-                let reference_month = frequency_data.month();
-                let start_day_of_month = start_date.day();
-                let start_month = start_date.month();
-
-                let month_difference_parity = (reference_month - start_month) % 2;
-
-                if start_day_of_month < reference_day_of_month {
-                    // Just a fixed days offset is needed
-                    start_date
-                        .checked_sub_months(Months::new(month_difference_parity))
-                        .unwrap()
-                        + Duration::days((reference_day_of_month - start_day_of_month) as i64)
-                } else {
-                    // Start day has passed reference day, so we must add a month and then subtract the difference
-                    // to set the same day of the month
-                    start_date
-                        .checked_sub_months(Months::new(month_difference_parity))
-                        .unwrap()
-                        + Duration::days(-((start_day_of_month - reference_day_of_month) as i64))
+                while date < start_date && date < MAXIMUM_DATE.unwrap() {
+                    date = get_next_closure_date(frequency_type, date);
                 }
-            }
-        };
 
-        new_start_date
+                date
+            }
+        }
     }
 }
 
@@ -183,14 +148,57 @@ impl Iterator for DateRange {
         }
 
         let next = (self.get_next_date)(self.start_date);
+
+        // Generator saturated at the maximum date, so this is the last occurrence
+        if next <= self.start_date {
+            self.end_date = MINIMUM_DATE.unwrap();
+        }
+
         Some(mem::replace(&mut self.start_date, next))
     }
 }
 
-// Define a SQL function that handles database closure date update
-diesel::sql_function!(
-    fn get_next_closure_date(
-        frequency_type: sql_types::HabFreqTypeEnum,
-        prev_closure_date: Date,
-    ) -> Date
-);
+// Next closure date of a period closing at the given date. It matches the database
+// get_next_closure_date function: months are added PostgreSQL's way (clamping to the
+// last day of the month), so Jan 31 + 1 month is Feb 28 / 29
+pub fn get_next_closure_date(
+    frequency_type: HabFreqTypeEnum,
+    prev_closure_date: NaiveDate,
+) -> NaiveDate {
+    let next_closure_date = match frequency_type {
+        HabFreqTypeEnum::daily => prev_closure_date.checked_add_signed(Duration::days(1)),
+        HabFreqTypeEnum::daily2 => prev_closure_date.checked_add_signed(Duration::days(2)),
+        HabFreqTypeEnum::weekly => prev_closure_date.checked_add_signed(Duration::weeks(1)),
+        HabFreqTypeEnum::weekly2 => prev_closure_date.checked_add_signed(Duration::weeks(2)),
+        HabFreqTypeEnum::monthly => prev_closure_date.checked_add_months(Months::new(1)),
+        HabFreqTypeEnum::monthly2 => prev_closure_date.checked_add_months(Months::new(2)),
+    };
+
+    next_closure_date.unwrap_or(MAXIMUM_DATE.unwrap())
+}
+
+// Days a range such as "30d", "12w", "6m" or "1y" covers, plain numbers are days. Months and
+// years are taken as 30 and 365 days
+pub fn parse_range_days(range: &str) -> Option<i64> {
+    let range = range.trim().to_lowercase();
+
+    let (amount, unit) = match range.chars().last()? {
+        unit if unit.is_ascii_digit() => (range.as_str(), 'd'),
+        unit => (&range[..range.len() - unit.len_utf8()], unit),
+    };
+
+    let amount = amount.parse::<i64>().ok()?;
+
+    let days = match unit {
+        'd' => Some(amount),
+        'w' => amount.checked_mul(7),
+        'm' => amount.checked_mul(30),
+        'y' => amount.checked_mul(365),
+        _ => None,
+    }?;
+
+    match days > 0 {
+        true => Some(days),
+        false => None,
+    }
+}
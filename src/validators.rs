@@ -1,4 +1,4 @@
-use crate::utils::MAX_DAYS_OFFSET;
+use crate::utils::{MAX_CHECKLIST_ITEMS, MAX_DAYS_OFFSET};
 use bigdecimal::BigDecimal;
 use validator::ValidationError;
 
@@ -31,3 +31,20 @@ pub fn validate_habdata_collected_at(value: &chrono::NaiveDate) -> Result<(), Va
 
     Ok(())
 }
+
+pub fn validate_checklist(value: &[String]) -> Result<(), ValidationError> {
+    if value.len() > MAX_CHECKLIST_ITEMS {
+        return Err(ValidationError::new("Too many checklist items"));
+    }
+
+    if value
+        .iter()
+        .any(|name| name.trim().is_empty() || name.chars().count() > 255)
+    {
+        return Err(ValidationError::new(
+            "Checklist item names must be 1 to 255 characters long",
+        ));
+    }
+
+    Ok(())
+}